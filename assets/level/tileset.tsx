@@ -1,5 +1,5 @@
 <?xml version="1.0" encoding="UTF-8"?>
-<tileset version="1.9" tiledversion="1.9.1" name="tileset" tilewidth="50" tileheight="50" tilecount="47" columns="0">
+<tileset version="1.9" tiledversion="1.9.1" name="tileset" tilewidth="50" tileheight="50" tilecount="54" columns="0">
  <grid orientation="orthogonal" width="1" height="1"/>
  <tile id="2">
   <image width="50" height="50" source="../img/player.png"/>
@@ -20,12 +20,18 @@
   <image width="50" height="50" source="../img/wall.png"/>
  </tile>
  <tile id="10">
+  <properties>
+   <property name="destructible" type="bool" value="true"/>
+  </properties>
   <image width="50" height="50" source="../img/carboard_box.png"/>
  </tile>
  <tile id="11">
   <image width="50" height="50" source="../img/carboard_box_knife.png"/>
  </tile>
  <tile id="12">
+  <properties>
+   <property name="destructible" type="bool" value="true"/>
+  </properties>
   <image width="50" height="50" source="../img/table.png"/>
  </tile>
  <tile id="13">
@@ -71,6 +77,9 @@
   <image width="50" height="50" source="../img/wall_threeway_4.png"/>
  </tile>
  <tile id="27">
+  <properties>
+   <property name="door" value="proximity"/>
+  </properties>
   <image width="50" height="50" source="../img/door.png"/>
  </tile>
  <tile id="28">
@@ -104,6 +113,9 @@
   <image width="50" height="50" source="../img/flowers_6.png"/>
  </tile>
  <tile id="38">
+  <properties>
+   <property name="door" value="interact"/>
+  </properties>
   <image width="50" height="50" source="../img/door_inside.png"/>
  </tile>
  <tile id="40">
@@ -142,4 +154,43 @@
  <tile id="51">
   <image width="50" height="50" source="../img/fun_dust.png"/>
  </tile>
+ <tile id="52">
+  <image width="50" height="50" source="../img/sugar.png"/>
+ </tile>
+ <tile id="53">
+  <properties>
+   <property name="enemy_type" value="fast"/>
+  </properties>
+  <image width="50" height="50" source="../img/enemy_idle.png"/>
+ </tile>
+ <tile id="54">
+  <properties>
+   <property name="enemy_type" value="tank"/>
+  </properties>
+  <image width="50" height="50" source="../img/enemy_idle.png"/>
+ </tile>
+ <tile id="55">
+  <properties>
+   <property name="key" value="red"/>
+  </properties>
+  <image width="50" height="50" source="../img/coin.png"/>
+ </tile>
+ <tile id="56">
+  <properties>
+   <property name="lock" value="red"/>
+  </properties>
+  <image width="50" height="50" source="../img/door_inside.png"/>
+ </tile>
+ <tile id="57">
+  <properties>
+   <property name="chest" value="common"/>
+  </properties>
+  <image width="50" height="50" source="../img/carboard_box.png"/>
+ </tile>
+ <tile id="58">
+  <properties>
+   <property name="chest" value="rare"/>
+  </properties>
+  <image width="50" height="50" source="../img/boxy_boy.png"/>
+ </tile>
 </tileset>
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use bevy_kira_audio::{AudioSource, Audio, AudioControl};
+use bevy_kira_audio::{AudioApp, AudioSource, Audio, AudioControl};
 
 use crate::time::TimeCounter;
 
@@ -21,11 +21,30 @@ pub struct SnortingSounds(pub Vec<Handle<AudioSource>>);
 #[derive(Deref, DerefMut)]
 pub struct CraftingSound(pub Handle<AudioSource>);
 
+#[derive(Deref, DerefMut)]
+pub struct ShieldBreakSound(pub Handle<AudioSource>);
+
+/// Played when a weapon overheats and locks up
+#[derive(Deref, DerefMut)]
+pub struct OverheatSound(pub Handle<AudioSource>);
+
+#[derive(Deref, DerefMut)]
+pub struct PlayerHurtSounds(pub Vec<Handle<AudioSource>>);
+
+#[derive(Deref, DerefMut)]
+pub struct PlayerDeathSound(pub Handle<AudioSource>);
+
+/// Sound effects played at their own playback rate, like footsteps with a varied pitch.
+/// They need a separate channel, as the main one has its rate overwritten by the timescale every frame.
+pub struct PitchedSfxChannel;
+
 pub struct AudioLoadPlugin;
 
 impl Plugin for AudioLoadPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(load_audio).add_system(update_playback_rate);
+        app.add_audio_channel::<PitchedSfxChannel>()
+            .add_startup_system(load_audio)
+            .add_system(update_playback_rate);
     }
 }
 
@@ -55,8 +74,17 @@ fn load_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
     ]));
 
     commands.insert_resource(CraftingSound(asset_server.load("./audio/craft_drug.wav")));
+    commands.insert_resource(ShieldBreakSound(asset_server.load("./audio/shot_dawid.wav")));
+    commands.insert_resource(OverheatSound(asset_server.load("./audio/enemy_shot.wav")));
+
+    // The player's voice reuses the screams, played at a different pitch to set it apart from the enemies
+    commands.insert_resource(PlayerHurtSounds(vec![
+        asset_server.load("./audio/scream_1.wav"),
+        asset_server.load("./audio/scream_3.wav"),
+    ]));
+    commands.insert_resource(PlayerDeathSound(asset_server.load("./audio/scream_2.wav")));
 }
 
 fn update_playback_rate(audio: Res<Audio>, time: Res<TimeCounter>) {
-    audio.set_playback_rate(time.timescale as f64);
+    audio.set_playback_rate(time.scale() as f64);
 }
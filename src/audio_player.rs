@@ -1,28 +1,142 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioSource, AudioControl};
+use bevy_kira_audio::{Audio, AudioChannel, AudioSource, AudioControl, AudioTween};
 
-use crate::settings::Settings;
+use crate::{music::MusicChannel, settings::Settings, TILE_SIZE};
 
 pub const MUSIC_VOLUME: f64 = 0.025;
 pub const PLAYER_SHOT_VOLUME: f64 = 0.05;
 pub const PLAYER_FOOTSTEP_VOLUME: f64 = 1.0;
+pub const PLAYER_MELEE_VOLUME: f64 = 1.5;
 pub const PLAYER_SNORTING_VOLUME: f64 = 0.1;
 pub const PLAYER_CRAFTING_VOLUME: f64 = 0.1;
+pub const SHIELD_BREAK_VOLUME: f64 = 0.2;
+pub const OVERHEAT_VOLUME: f64 = 0.2;
+pub const PLAYER_HURT_VOLUME: f64 = 0.15;
+pub const PLAYER_DEATH_VOLUME: f64 = 0.4;
+pub const PICKUP_VOLUME: f64 = 0.1;
+pub const TELEPORT_VOLUME: f64 = 0.1;
 pub const ENEMY_SHOT_VOLUME: f64 = 0.1;
 pub const ENEMY_DEATH_SCREAM_VOLUME: f64 = 0.3;
+pub const BOSS_ROAR_VOLUME: f64 = 0.5;
+/// Sounds closer to the listener than this play at their full volume
+pub const SPATIAL_FULL_VOLUME_DISTANCE: f32 = 4.0 * TILE_SIZE;
+/// Sounds further away than this can't be heard at all
+pub const SPATIAL_MAX_DISTANCE: f32 = 30.0 * TILE_SIZE;
+/// Horizontal offset at which a sound is panned as far as it goes
+pub const SPATIAL_PAN_DISTANCE: f32 = 12.0 * TILE_SIZE;
+/// How far to the side a sound can be panned, a fully one-sided sound is too jarring
+pub const SPATIAL_MAX_PAN: f32 = 0.75;
 
 pub struct AudioPlayer;
 
 impl AudioPlayer {
+	/// Volume a sound effect is played at, after applying the settings
+	pub fn sfx_volume(default_volume: f64, settings: &Settings) -> f64 {
+		settings.sfx_volume * default_volume
+	}
+
 	pub fn play_sfx(audio: &Audio, sound: Handle<AudioSource>, default_volume: f64, settings: &Settings) {
-		audio.play(sound).with_volume(settings.sfx_volume * default_volume);
+		let volume = Self::sfx_volume(default_volume, settings);
+
+		// Don't bother starting sounds nobody can hear
+		if volume <= 0.0 {
+			return;
+		}
+
+		audio.play(sound).with_volume(volume);
+	}
+
+	/// Plays a sound effect sped up or slowed down, which also shifts its pitch
+	pub fn play_sfx_with_rate(
+		channel: &impl AudioControl,
+		sound: Handle<AudioSource>,
+		default_volume: f64,
+		settings: &Settings,
+		playback_rate: f64,
+	) {
+		let volume = Self::sfx_volume(default_volume, settings);
+
+		if volume <= 0.0 {
+			return;
+		}
+
+		channel.play(sound).with_volume(volume).with_playback_rate(playback_rate);
 	}
 
-	pub fn play_music(audio: &Audio, sound: Handle<AudioSource>, default_volume: f64, settings: &Settings, loop_start: f64) {
-		audio.play(sound).with_volume(settings.music_volume * default_volume).loop_from(loop_start);
+	/// Plays a sound effect coming from `position`, panned to its side and quieter the further
+	/// it is from the `listener`
+	pub fn emit_spatial_sound(
+		audio: &Audio,
+		sound: Handle<AudioSource>,
+		default_volume: f64,
+		settings: &Settings,
+		position: Vec2,
+		listener: Vec2,
+	) {
+		let offset = position - listener;
+
+		let attenuation = 1.0
+			- ((offset.length() - SPATIAL_FULL_VOLUME_DISTANCE)
+				/ (SPATIAL_MAX_DISTANCE - SPATIAL_FULL_VOLUME_DISTANCE))
+				.clamp(0.0, 1.0);
+
+		let volume = Self::sfx_volume(default_volume, settings) * attenuation as f64;
+
+		if volume <= 0.0 {
+			return;
+		}
+
+		// A panning of 0 is fully on the left, 1 fully on the right
+		let panning = 0.5 + 0.5 * SPATIAL_MAX_PAN * (offset.x / SPATIAL_PAN_DISTANCE).clamp(-1.0, 1.0);
+
+		audio.play(sound).with_volume(volume).with_panning(panning as f64);
 	}
 
-	pub fn stop(audio: &Audio) {
-		audio.stop();
+	pub fn play_music(channel: &AudioChannel<MusicChannel>, sound: Handle<AudioSource>, default_volume: f64, settings: &Settings, loop_start: f64, fade_in: Duration) {
+		Self::set_music_volume(channel, default_volume, settings);
+		channel.play(sound).loop_from(loop_start).fade_in(AudioTween::linear(fade_in));
+	}
+
+	pub fn stop_music(channel: &AudioChannel<MusicChannel>, fade_out: Duration) {
+		channel.stop().fade_out(AudioTween::linear(fade_out));
+	}
+
+	/// The music volume is set on the whole channel, so it also affects the song that's already playing
+	pub fn set_music_volume(channel: &AudioChannel<MusicChannel>, default_volume: f64, settings: &Settings) {
+		channel.set_volume(settings.music_volume * default_volume);
+	}
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn muted_sfx_play_at_no_volume() {
+		let settings = Settings {
+			sfx_volume: 0.0,
+			..Default::default()
+		};
+
+		assert_eq!(AudioPlayer::sfx_volume(PLAYER_SHOT_VOLUME, &settings), 0.0);
+		assert_eq!(AudioPlayer::sfx_volume(BOSS_ROAR_VOLUME, &settings), 0.0);
+	}
+
+	#[test]
+	fn sfx_volume_scales_the_default_volume() {
+		let settings = Settings {
+			sfx_volume: 0.5,
+			..Default::default()
+		};
+
+		assert!((AudioPlayer::sfx_volume(PLAYER_DEATH_VOLUME, &settings) - PLAYER_DEATH_VOLUME * 0.5).abs() < 1e-9);
+	}
+
+	#[test]
+	fn full_sfx_volume_keeps_the_default_volume() {
+		let settings = Settings::default();
+
+		assert_eq!(AudioPlayer::sfx_volume(PLAYER_SHOT_VOLUME, &settings), PLAYER_SHOT_VOLUME);
 	}
-}
\ No newline at end of file
+}
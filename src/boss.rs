@@ -0,0 +1,375 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::audio::{EnemyShotSound, Screams};
+use crate::audio_player::{AudioPlayer, BOSS_ROAR_VOLUME, ENEMY_DEATH_SCREAM_VOLUME, ENEMY_SHOT_VOLUME};
+use crate::bullet::{BulletSpawner, ShotEvent};
+use crate::damage_number::DamageEvent;
+use crate::enemy::{shoot, EnemyBodyBundle, EnemyTextures, BLOOD_COLOR, ENEMY_SIGHT};
+use crate::line_of_sight::has_line_of_sight;
+use crate::particle::{spawn_burst, Particle, ParticleSettings};
+use crate::pool::Pool;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::stats::{Score, Stats};
+use crate::status_effect::{StatusEffect, StatusEffectKind, StatusEffects};
+use crate::tilemap::{Tile, TileCollider, Tilemap};
+use crate::time::TimeCounter;
+use crate::unit::{Health, ShootEvent};
+use crate::{GameState, TILE_SIZE};
+
+pub const BOSS_HEALTH: f32 = 1200.0;
+pub const BOSS_POINTS: u32 = 2000;
+pub const BOSS_SIZE: f32 = 1.6 * TILE_SIZE;
+/// Health fractions below which the boss switches to bursts and then to charging
+pub const BOSS_PHASE_THRESHOLDS: [f32; 2] = [0.66, 0.33];
+pub const BOSS_SHOT_DAMAGE: f32 = 30.0;
+pub const AIMED_SHOT_COOLDOWN: f32 = 1.2;
+pub const BURST_COOLDOWN: f32 = 1.8;
+pub const BURST_SHOTS: u32 = 5;
+pub const BURST_SHOT_INTERVAL: f32 = 0.1;
+pub const CHARGE_COOLDOWN: f32 = 1.5;
+pub const CHARGE_DURATION: f32 = 0.8;
+/// In pixels per second
+pub const CHARGE_SPEED: f32 = 12.0 * TILE_SIZE;
+pub const CHARGE_DAMAGE: f32 = 40.0;
+/// Set on the player by the boss's shots
+pub const BOSS_BURN: StatusEffect = StatusEffect::new(StatusEffectKind::Burn, 2.0, 0.4, 3.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossPhase {
+	/// Slow shots aimed at the player
+	Aimed,
+	/// Quick bursts of shots
+	Burst,
+	/// Runs straight at the player
+	Charge,
+}
+
+impl BossPhase {
+	fn from_health(fraction: f32, thresholds: &[f32; 2]) -> Self {
+		if fraction > thresholds[0] {
+			Self::Aimed
+		} else if fraction > thresholds[1] {
+			Self::Burst
+		} else {
+			Self::Charge
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct Boss {
+	pub phase: BossPhase,
+	pub phase_thresholds: [f32; 2],
+	pub points: u32,
+	attack_timer: Timer,
+	burst_shots_left: u32,
+	/// Direction of the charge the boss is in the middle of
+	charge: Option<Vec2>,
+}
+
+impl Default for Boss {
+	fn default() -> Self {
+		Self {
+			phase: BossPhase::Aimed,
+			phase_thresholds: BOSS_PHASE_THRESHOLDS,
+			points: BOSS_POINTS,
+			attack_timer: Timer::from_seconds(AIMED_SHOT_COOLDOWN, false),
+			burst_shots_left: 0,
+			charge: None,
+		}
+	}
+}
+
+#[derive(Bundle)]
+pub struct BossBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	name: Name,
+	boss: Boss,
+	health: Health,
+	rapier_collider: Collider,
+	status_effects: StatusEffects,
+}
+
+impl Default for BossBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			name: Name::new("Boss"),
+			boss: Boss::default(),
+			health: Health::new(BOSS_HEALTH),
+			rapier_collider: Collider::cuboid(BOSS_SIZE / 2.0, BOSS_SIZE / 2.0),
+			status_effects: StatusEffects::default(),
+		}
+	}
+}
+
+impl Tile for BossBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_xyz(position.x, position.y, 45.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					// The boss uses the enemy sprite, only bigger and angrier
+					color: Color::rgb(1.0, 0.45, 0.45),
+					custom_size: Some(Vec2::splat(BOSS_SIZE)),
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_system_set(
+			SystemSet::on_update(GameState::Game)
+				.with_system(boss_ai)
+				.with_system(boss_get_shot),
+		);
+	}
+}
+
+fn boss_ai(
+	mut commands: Commands,
+	mut bosses: Query<(Entity, &mut Transform, &mut Boss, &Health)>,
+	player: Query<(Entity, &Transform), (With<Player>, Without<Boss>)>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	walls: Query<(), With<TileCollider>>,
+	mut shoot_event: EventWriter<ShootEvent>,
+	mut shot_event: EventWriter<ShotEvent>,
+	rapier_context: Res<RapierContext>,
+	time: Res<TimeCounter>,
+	audio: Res<Audio>,
+	settings: Res<Settings>,
+	shot_sound: Res<EnemyShotSound>,
+	screams: Res<Screams>,
+	mut bullets: BulletSpawner,
+) {
+	let (player, player_transform) = player.single();
+	let tilemap = tilemap.single();
+
+	let player_position = player_transform.translation.truncate();
+
+	for (entity, mut transform, mut boss, health) in bosses.iter_mut() {
+		let position = transform.translation.truncate();
+
+		if let Some(direction) = boss.charge {
+			boss.attack_timer.tick(time.delta());
+
+			let shape = Collider::cuboid(BOSS_SIZE / 2.0, BOSS_SIZE / 2.0);
+			let velocity = direction * CHARGE_SPEED * time.delta_seconds();
+			let filter = QueryFilter::default()
+				.exclude_collider(entity)
+				.exclude_sensors();
+
+			// The charge ends when running into something, hurting the player if it was them
+			match rapier_context.cast_shape(position, 0.0, velocity, &shape, 1.0, filter) {
+				Some((hit, _)) => {
+					if hit == player {
+						shot_event.send(ShotEvent(player, CHARGE_DAMAGE, None, false));
+					}
+
+					boss.charge = None;
+				}
+				None => transform.translation += velocity.extend(0.0),
+			}
+
+			if boss.attack_timer.finished() {
+				boss.charge = None;
+			}
+
+			if boss.charge.is_none() {
+				boss.attack_timer.reset();
+			}
+
+			continue;
+		}
+
+		let sees_player = (player_position - position).length() <= ENEMY_SIGHT
+			&& has_line_of_sight(&rapier_context, &walls, position, player_position);
+
+		if !sees_player {
+			continue;
+		}
+
+		let phase = BossPhase::from_health(
+			health.get_health() / health.get_max_health(),
+			&boss.phase_thresholds,
+		);
+
+		if phase != boss.phase {
+			boss.phase = phase;
+			boss.burst_shots_left = 0;
+			boss.attack_timer.reset();
+
+			AudioPlayer::emit_spatial_sound(
+				audio.as_ref(),
+				screams
+					.choose(&mut rand::thread_rng())
+					.expect("No scream sounds found.")
+					.clone(),
+				BOSS_ROAR_VOLUME,
+				settings.as_ref(),
+				position,
+				player_position,
+			);
+		}
+
+		transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_between(player_position - position));
+
+		let cooldown = match boss.phase {
+			BossPhase::Aimed => AIMED_SHOT_COOLDOWN,
+			BossPhase::Burst if boss.burst_shots_left > 0 => BURST_SHOT_INTERVAL,
+			BossPhase::Burst => BURST_COOLDOWN,
+			BossPhase::Charge => CHARGE_COOLDOWN,
+		};
+
+		boss.attack_timer.set_duration(Duration::from_secs_f32(cooldown));
+		boss.attack_timer.tick(time.delta());
+
+		if !boss.attack_timer.finished() {
+			continue;
+		}
+
+		boss.attack_timer.reset();
+
+		match boss.phase {
+			BossPhase::Aimed | BossPhase::Burst => {
+				if boss.phase == BossPhase::Burst {
+					if boss.burst_shots_left == 0 {
+						// The pause between bursts is over
+						boss.burst_shots_left = BURST_SHOTS;
+						continue;
+					}
+
+					boss.burst_shots_left -= 1;
+				}
+
+				shoot(
+					&mut commands,
+					&transform,
+					player_transform,
+					&player,
+					&tilemap,
+					&mut bullets,
+					BOSS_SHOT_DAMAGE,
+					Some(BOSS_BURN),
+					&mut shoot_event,
+					&mut shot_event,
+				);
+
+				AudioPlayer::emit_spatial_sound(
+					audio.as_ref(),
+					shot_sound.clone(),
+					ENEMY_SHOT_VOLUME,
+					settings.as_ref(),
+					position,
+					player_position,
+				);
+			}
+			BossPhase::Charge => {
+				boss.charge = Some((player_position - position).normalize_or_zero());
+				boss.attack_timer.set_duration(Duration::from_secs_f32(CHARGE_DURATION));
+			}
+		}
+	}
+}
+
+fn boss_get_shot(
+	mut commands: Commands,
+	mut bosses: Query<(Entity, &Transform, &Boss, &mut Health)>,
+	player: Query<&Transform, With<Player>>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	mut shot_events: EventReader<ShotEvent>,
+	enemy_textures: Res<EnemyTextures>,
+	audio: Res<Audio>,
+	settings: Res<Settings>,
+	screams: Res<Screams>,
+	mut stats: ResMut<Stats>,
+	mut score: ResMut<Score>,
+	mut state: ResMut<State<GameState>>,
+	particle_settings: Res<ParticleSettings>,
+	mut particle_pool: ResMut<Pool<Particle>>,
+	mut damage_events: EventWriter<DamageEvent>,
+) {
+	for shot in shot_events.iter() {
+		let (entity, transform, boss, mut health) = match bosses.get_mut(shot.0) {
+			Ok(boss) => boss,
+			Err(_) => continue,
+		};
+
+		damage_events.send(DamageEvent {
+			position: transform.translation.truncate(),
+			amount: shot.1,
+			to_player: false,
+			critical: shot.3,
+		});
+
+		if !health.take_damage(shot.1) {
+			continue;
+		}
+
+		stats.enemies_killed += 1;
+		score.points += boss.points;
+
+		let body = commands
+			.spawn_bundle(EnemyBodyBundle {
+				sprite_bundle: SpriteBundle {
+					transform: Transform::from_translation(transform.translation - Vec3::Z * 10.0)
+						.with_rotation(Quat::from_rotation_z(rand::random::<f32>() * 2.0 * PI)),
+					texture: enemy_textures.body.clone(),
+					sprite: Sprite {
+						custom_size: Some(Vec2::splat(BOSS_SIZE)),
+						..Default::default()
+					},
+					..Default::default()
+				},
+			})
+			.id();
+
+		// A bigger enemy makes a bigger mess
+		spawn_burst(
+			&mut commands,
+			&mut particle_pool,
+			tilemap.single(),
+			transform.translation,
+			BLOOD_COLOR,
+			particle_settings.death_burst_count * 3,
+		);
+
+		commands.entity(tilemap.single()).add_child(body);
+		commands.entity(entity).despawn_recursive();
+
+		AudioPlayer::emit_spatial_sound(
+			audio.as_ref(),
+			screams
+				.choose(&mut rand::thread_rng())
+				.expect("No scream sounds found.")
+				.clone(),
+			ENEMY_DEATH_SCREAM_VOLUME,
+			settings.as_ref(),
+			transform.translation.truncate(),
+			player.single().translation.truncate(),
+		);
+
+		if state.set(GameState::Win).is_err() {}
+
+		// Later hits in the same frame would kill it a second time
+		break;
+	}
+}
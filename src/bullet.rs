@@ -1,16 +1,24 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use rand::random;
 use bevy_rapier2d::prelude::{Collider, QueryFilter, RapierContext, Sensor};
 
-use crate::{GameState, time::TimeCounter};
+use crate::{GameState, TILE_SIZE, lifetime::Lifetime, pool::Pool, rng::GameRng, status_effect::StatusEffect, tilemap::{TileCollider, Tilemap}, time::TimeCounter, unit::Falloff};
 
 pub const BULLET_COLLIDER_WIDTH: f32 = 10.0;
 pub const BULLET_COLLIDER_HEIGHT: f32 = 10.0;
+pub const BULLET_RANGE: f32 = 30.0 * TILE_SIZE;
+pub const IMPACT_SIZE: f32 = 10.0;
+pub const IMPACT_LIFETIME: f32 = 0.08;
 
 pub struct BulletPlugin;
 
 impl Plugin for BulletPlugin {
 	fn build(&self, app: &mut App) {
 		app.add_event::<ShotEvent>()
+			.add_event::<KnockbackEvent>()
 			.add_startup_system(load_bullet_texture)
 			.add_system_set(
 				SystemSet::on_update(GameState::Game)
@@ -22,6 +30,19 @@ impl Plugin for BulletPlugin {
 #[derive(Component)]
 pub struct Bullet {
 	pub speed: f32,
+	/// Distance the bullet can still travel before it goes back into the pool
+	pub range: f32,
+	pub knockback: f32,
+	pub damage: f32,
+	pub status_effect: Option<StatusEffect>,
+	pub critical: bool,
+	pub falloff: Option<Falloff>,
+	/// Distance flown so far, for the falloff
+	pub traveled: f32,
+	/// Enemies the bullet can still fly through
+	pub pierce_left: u32,
+	/// Enemies already flown through, so that they don't get hit again on the way out
+	pub pierced: Vec<Entity>,
 }
 
 #[derive(Bundle)]
@@ -37,7 +58,18 @@ pub struct BulletBundle {
 impl Default for BulletBundle {
 	fn default() -> Self {
 		Self {
-			bullet: Bullet { speed: 4000.0 },
+			bullet: Bullet {
+				speed: 4000.0,
+				range: BULLET_RANGE,
+				knockback: 0.0,
+				damage: 0.0,
+				status_effect: None,
+				critical: false,
+				falloff: None,
+				traveled: 0.0,
+				pierce_left: 0,
+				pierced: Vec::new(),
+			},
 			collider: Collider::cuboid(BULLET_COLLIDER_WIDTH, BULLET_COLLIDER_HEIGHT),
 			sensor: Sensor,
 			sprite_bundle: SpriteBundle::default(),
@@ -46,7 +78,41 @@ impl Default for BulletBundle {
 	}
 }
 
-pub struct ShotEvent(pub Entity);
+/// Spark where a shot hit something
+#[derive(Bundle)]
+pub struct ImpactBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	lifetime: Lifetime,
+	name: Name,
+}
+
+impl ImpactBundle {
+	pub fn new(position: Vec2) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				sprite: Sprite {
+					color: Color::rgb(1.0, 0.9, 0.6),
+					custom_size: Some(Vec2::splat(IMPACT_SIZE)),
+					..Default::default()
+				},
+				// Turned randomly, so that rapid fire doesn't look the same every time
+				transform: Transform::from_translation(position.extend(55.0))
+					.with_rotation(Quat::from_rotation_z(random::<f32>() * std::f32::consts::PI)),
+				..Default::default()
+			},
+			lifetime: Lifetime::from_seconds(IMPACT_LIFETIME),
+			name: Name::new("Impact"),
+		}
+	}
+}
+
+/// The entity got hit for the given damage, with the status effect the hit applies
+/// and whether it was a critical hit
+pub struct ShotEvent(pub Entity, pub f32, pub Option<StatusEffect>, pub bool);
+
+/// Pushes the entity away with the given velocity
+pub struct KnockbackEvent(pub Entity, pub Vec2);
 
 #[derive(Deref, DerefMut)]
 pub struct BulletTexture(Handle<Image>);
@@ -55,36 +121,304 @@ fn load_bullet_texture(mut commands: Commands, asset_server: Res<AssetServer>) {
 	commands.insert_resource(BulletTexture(asset_server.load("./img/bullet.png")));
 }
 
+/// Everything a shooting system needs to fire bullets, taken as a single system parameter.
+/// The generator is the game's own, for the spread and the damage rolls.
+#[derive(SystemParam)]
+pub struct BulletSpawner<'w, 's> {
+	texture: Res<'w, BulletTexture>,
+	pool: ResMut<'w, Pool<Bullet>>,
+	pub rng: ResMut<'w, GameRng>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
+impl BulletSpawner<'_, '_> {
+	/// Takes a bullet from the pool and sends it off from the transform, the way the transform faces.
+	pub fn fire(&mut self, commands: &mut Commands, parent: Entity, transform: Transform, bullet: Bullet) -> Entity {
+		let texture = self.texture.0.clone();
+
+		self.pool.spawn(
+			commands,
+			parent,
+			BulletBundle {
+				sprite_bundle: SpriteBundle {
+					transform,
+					texture,
+					..Default::default()
+				},
+				bullet,
+				..Default::default()
+			},
+		)
+	}
+}
+
+/// Puts the bullet back into the pool, without the collider so that nothing can hit it in the meantime
+fn release_bullet(commands: &mut Commands, pool: &mut Pool<Bullet>, bullet: Entity) {
+	commands.entity(bullet).remove::<Collider>();
+	pool.release(commands, bullet);
+}
+
 fn update_bullets(
 	mut commands: Commands,
-	mut bullets: Query<(Entity, &mut Transform, &Bullet)>,
+	mut bullets: Query<(Entity, &mut Transform, &mut Bullet)>,
+	mut pool: ResMut<Pool<Bullet>>,
+	walls: Query<(), With<TileCollider>>,
+	tilemap: Query<Entity, With<Tilemap>>,
 	time: Res<TimeCounter>,
 	rapier_context: Res<RapierContext>,
 	mut event_shot: EventWriter<ShotEvent>,
+	mut event_knockback: EventWriter<KnockbackEvent>,
 ) {
-	for (bullet_entity, mut transform, bullet) in bullets.iter_mut() {
+	for (bullet_entity, mut transform, mut bullet) in bullets.iter_mut() {
 		let direction = transform.up();
 
 		let movement_vector = direction * bullet.speed * time.delta_seconds();
 
-		let filter = QueryFilter::default().exclude_sensors();
+		let start = transform.translation.truncate();
+		let mut origin = start;
+		let mut remaining = movement_vector.truncate();
+		let mut stopped = false;
+
+		// A piercing bullet keeps checking the rest of the way from every enemy it flies through,
+		// so that it can't skip over a wall or another enemy right behind it
+		loop {
+			let hit = {
+				let predicate = |entity| !bullet.pierced.contains(&entity);
+				let filter = QueryFilter::default().exclude_sensors().predicate(&predicate);
+
+				rapier_context.cast_ray(origin, remaining, 1.0, true, filter)
+			};
+
+			let (hit_entity, toi) = match hit {
+				Some(hit) => hit,
+				None => break,
+			};
+
+			// The time of impact is a fraction of the rest of this frame's movement
+			let hit_point = origin + remaining * toi;
+			let distance = bullet.traveled + (hit_point - start).length();
 
-		// Check for collisions
-		if let Some((hit_entity, _)) = rapier_context.cast_ray(
-			transform.translation.truncate(),
-			movement_vector.truncate(),
-			1.0,
-			true,
-			filter,
-		) {
-			commands.entity(bullet_entity).despawn_recursive();
+			let impact = commands.spawn_bundle(ImpactBundle::new(hit_point)).id();
 
-			event_shot.send(ShotEvent(hit_entity));
+			commands.entity(tilemap.single()).add_child(impact);
 
+			let damage = match bullet.falloff {
+				Some(falloff) => falloff.damage_at(bullet.damage, distance),
+				None => bullet.damage,
+			};
+
+			event_shot.send(ShotEvent(hit_entity, damage, bullet.status_effect, bullet.critical));
+
+			if bullet.knockback > 0.0 {
+				event_knockback.send(KnockbackEvent(
+					hit_entity,
+					direction.truncate() * bullet.knockback,
+				));
+			}
+
+			if walls.contains(hit_entity) || bullet.pierce_left == 0 {
+				stopped = true;
+				break;
+			}
+
+			// Keeps flying through the enemy
+			bullet.pierce_left -= 1;
+			bullet.pierced.push(hit_entity);
+
+			origin = hit_point;
+			remaining *= 1.0 - toi;
+		}
+
+		if stopped {
+			release_bullet(&mut commands, &mut pool, bullet_entity);
 			continue;
 		}
 
-		// If no collision occured, move
+		// Nothing stopped the bullet, so move
 		transform.translation += movement_vector;
+
+		bullet.range -= movement_vector.length();
+		bullet.traveled += movement_vector.length();
+
+		if bullet.range <= 0.0 {
+			release_bullet(&mut commands, &mut pool, bullet_entity);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::FRAC_PI_2;
+
+	use bevy::ecs::system::CommandQueue;
+
+	use crate::test_app::{spawn_wall, step, test_app};
+
+	use super::*;
+
+	const FRAME: f32 = 1.0 / 60.0;
+
+	fn bullet_app() -> App {
+		let mut app = test_app();
+
+		app.add_event::<ShotEvent>()
+			.add_event::<KnockbackEvent>()
+			.init_resource::<Pool<Bullet>>()
+			.add_system_set(SystemSet::on_update(GameState::Game).with_system(update_bullets));
+
+		app.world.spawn().insert(Tilemap);
+
+		app
+	}
+
+	/// A bullet at the origin flying to the right
+	fn spawn_bullet(app: &mut App, speed: f32) -> Entity {
+		let mut bullet = BulletBundle::default().bullet;
+		bullet.speed = speed;
+
+		app.world
+			.spawn()
+			.insert_bundle(TransformBundle::from_transform(Transform::from_rotation(Quat::from_rotation_z(-FRAC_PI_2))))
+			.insert(bullet)
+			.id()
+	}
+
+	fn shot_entities(app: &App) -> Vec<Entity> {
+		let events = app.world.resource::<Events<ShotEvent>>();
+
+		events.get_reader().iter(events).map(|shot| shot.0).collect()
+	}
+
+	#[test]
+	fn fast_bullet_hits_a_thin_wall_it_would_fly_past_in_one_frame() {
+		let mut app = bullet_app();
+
+		// A tile thick, and the bullet covers twenty tiles a frame
+		let wall = spawn_wall(&mut app, Vec2::new(10.0 * TILE_SIZE, 0.0), Vec2::splat(TILE_SIZE / 2.0));
+		step(&mut app, 0.0);
+
+		let bullet = spawn_bullet(&mut app, 20.0 * TILE_SIZE / FRAME);
+		step(&mut app, FRAME);
+
+		assert_eq!(shot_entities(&app), vec![wall]);
+		// Released instead of moved past the wall
+		assert!(app.world.get::<Bullet>(bullet).is_none());
+		assert!(app.world.get::<Transform>(bullet).unwrap().translation.x < 10.0 * TILE_SIZE);
+	}
+
+	#[test]
+	fn bullet_flies_on_when_nothing_is_in_the_way() {
+		let mut app = bullet_app();
+		step(&mut app, 0.0);
+
+		let bullet = spawn_bullet(&mut app, 20.0 * TILE_SIZE / FRAME);
+		step(&mut app, FRAME);
+
+		assert!(shot_entities(&app).is_empty());
+		assert!((app.world.get::<Transform>(bullet).unwrap().translation.x - 20.0 * TILE_SIZE).abs() < 0.5);
+	}
+
+	/// A bullet flying through `pierce` enemies, at twenty tiles a frame
+	fn spawn_piercing_bullet(app: &mut App, pierce: u32) -> Entity {
+		let bullet = spawn_bullet(app, 20.0 * TILE_SIZE / FRAME);
+		app.world.get_mut::<Bullet>(bullet).unwrap().pierce_left = pierce;
+
+		bullet
+	}
+
+	/// An enemy sized target in front of the bullet, `x` tiles to the right
+	fn spawn_target(app: &mut App, x: f32) -> Entity {
+		app.world
+			.spawn()
+			.insert_bundle(TransformBundle::from_transform(Transform::from_xyz(x * TILE_SIZE, 0.0, 10.0)))
+			.insert(Collider::ball(TILE_SIZE / 2.0))
+			.id()
+	}
+
+	#[test]
+	fn piercing_bullet_stops_at_a_wall_behind_an_enemy_in_the_same_frame() {
+		let mut app = bullet_app();
+
+		let enemy = spawn_target(&mut app, 3.0);
+		let wall = spawn_wall(&mut app, Vec2::new(6.0 * TILE_SIZE, 0.0), Vec2::splat(TILE_SIZE / 2.0));
+		step(&mut app, 0.0);
+
+		let bullet = spawn_piercing_bullet(&mut app, 3);
+		step(&mut app, FRAME);
+
+		assert_eq!(shot_entities(&app), vec![enemy, wall]);
+		assert!(app.world.get::<Bullet>(bullet).is_none());
+		assert!(app.world.get::<Transform>(bullet).unwrap().translation.x < 6.0 * TILE_SIZE);
+	}
+
+	#[test]
+	fn piercing_bullet_stops_at_the_enemy_after_its_pierce_runs_out() {
+		let mut app = bullet_app();
+
+		let enemies = [2.0, 4.0, 6.0].map(|x| spawn_target(&mut app, x));
+		step(&mut app, 0.0);
+
+		let bullet = spawn_piercing_bullet(&mut app, 1);
+		step(&mut app, FRAME);
+
+		assert_eq!(shot_entities(&app), enemies[..2].to_vec());
+		assert!(app.world.get::<Bullet>(bullet).is_none());
+	}
+
+	#[test]
+	fn piercing_bullet_flies_through_every_enemy_in_a_line() {
+		let mut app = bullet_app();
+
+		let enemies = [2.0, 4.0, 6.0].map(|x| spawn_target(&mut app, x));
+		step(&mut app, 0.0);
+
+		let bullet = spawn_piercing_bullet(&mut app, 3);
+		step(&mut app, FRAME);
+
+		assert_eq!(shot_entities(&app), enemies.to_vec());
+		// Still flying, with all of its pierce used up
+		let bullet = app.world.get::<Bullet>(bullet).unwrap();
+		assert_eq!(bullet.pierce_left, 0);
+		assert_eq!(bullet.pierced, enemies.to_vec());
+	}
+
+	/// Fires a default bullet from the origin, straight up, the way the weapons get one from the pool
+	fn fire_from_pool(app: &mut App) -> Entity {
+		let parent = app.world.spawn().id();
+		let mut queue = CommandQueue::default();
+
+		let bullet = app.world.resource_scope(|world, mut pool: Mut<Pool<Bullet>>| {
+			let mut commands = Commands::new(&mut queue, world);
+			pool.spawn(&mut commands, parent, BulletBundle::default())
+		});
+
+		queue.apply(&mut app.world);
+		bullet
+	}
+
+	#[test]
+	fn released_bullet_is_reused_for_the_next_shot() {
+		let mut app = bullet_app();
+
+		spawn_wall(&mut app, Vec2::new(0.0, TILE_SIZE), Vec2::splat(TILE_SIZE / 2.0));
+		step(&mut app, 0.0);
+
+		let bullet = fire_from_pool(&mut app);
+		assert_eq!(app.world.resource::<Pool<Bullet>>().free_count(), 0);
+
+		// Straight into the wall, which puts it back into the pool
+		step(&mut app, FRAME);
+
+		assert_eq!(app.world.resource::<Pool<Bullet>>().free_count(), 1);
+		assert!(app.world.get::<Bullet>(bullet).is_none());
+		assert!(app.world.get::<Collider>(bullet).is_none());
+
+		let next_bullet = fire_from_pool(&mut app);
+
+		assert_eq!(next_bullet, bullet);
+		assert_eq!(app.world.resource::<Pool<Bullet>>().free_count(), 0);
+		assert!(app.world.get::<Bullet>(next_bullet).is_some());
+		assert!(app.world.get::<Collider>(next_bullet).is_some());
 	}
 }
@@ -0,0 +1,178 @@
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use rand::Rng;
+
+use crate::audio::CraftingSound;
+use crate::audio_player::{AudioPlayer, PICKUP_VOLUME};
+use crate::key_bindings::KeyBindings;
+use crate::loot::{spawn_loot, LootDrop, LootTable};
+use crate::player::Player;
+use crate::rng::GameRng;
+use crate::settings::Settings;
+use crate::tilemap::{CullableTile, TexturesMemo, Tile, Tilemap};
+use crate::TILE_SIZE;
+
+/// How close the player has to be to open a chest
+pub const CHEST_OPEN_RANGE: f32 = 1.5 * TILE_SIZE;
+/// What an opened chest looks like, whatever it looked like before
+pub const OPENED_CHEST_TEXTURE: &str = "img/carboard_box_knife.png";
+/// Farthest the drops land from the chest, so that several of them don't cover each other up
+pub const LOOT_SCATTER: f32 = 0.6 * TILE_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChestTier {
+	Common,
+	Rare,
+}
+
+impl ChestTier {
+	/// Parses the "chest" property of a chest tile.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"common" => Some(Self::Common),
+			"rare" => Some(Self::Rare),
+			_ => None,
+		}
+	}
+
+	pub fn loot_table(&self) -> LootTable {
+		match self {
+			ChestTier::Common => LootTable::new(vec![(LootDrop::Health, 4), (LootDrop::Ammo, 5), (LootDrop::Weapon, 1)]),
+			ChestTier::Rare => LootTable::new(vec![(LootDrop::Health, 3), (LootDrop::Ammo, 3), (LootDrop::Weapon, 4)]),
+		}
+	}
+
+	/// How many drops the chest rolls for
+	pub fn drop_count(&self) -> RangeInclusive<u32> {
+		match self {
+			ChestTier::Common => 1..=2,
+			ChestTier::Rare => 2..=3,
+		}
+	}
+}
+
+/// Spills out random pickups the first time the player opens it
+#[derive(Component)]
+pub struct Chest {
+	pub tier: ChestTier,
+	pub opened: bool,
+}
+
+#[derive(Bundle)]
+pub struct ChestBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	chest: Chest,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for ChestBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			chest: Chest {
+				tier: ChestTier::Common,
+				opened: false,
+			},
+			cullable: CullableTile,
+			name: Name::new("Chest"),
+		}
+	}
+}
+
+impl Tile for ChestBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				// Under the pickups it drops
+				transform: Transform::from_xyz(position.x, position.y, 20.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+impl ChestBundle {
+	pub fn with_tier(mut self, tier: ChestTier) -> Self {
+		self.chest.tier = tier;
+		self
+	}
+}
+
+pub fn open_chests(
+	mut commands: Commands,
+	mut chests: Query<(&Transform, &mut Chest, &mut Handle<Image>)>,
+	player: Query<&Transform, (With<Player>, Without<Chest>)>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	mut rng: ResMut<GameRng>,
+	mut textures: ResMut<TexturesMemo>,
+	asset_server: Res<AssetServer>,
+	audio: Res<Audio>,
+	settings: Res<Settings>,
+	crafting_sound: Res<CraftingSound>,
+) {
+	if !keyboard.just_pressed(key_bindings.interact) {
+		return;
+	}
+
+	let player_position = player.single().translation.truncate();
+
+	// Only the one chest per press, even when standing between a few of them
+	let chest = chests.iter_mut().find(|(transform, chest, _)| {
+		!chest.opened && transform.translation.truncate().distance(player_position) <= CHEST_OPEN_RANGE
+	});
+
+	let (transform, mut chest, mut texture) = match chest {
+		Some(chest) => chest,
+		None => return,
+	};
+
+	chest.opened = true;
+	*texture = textures.get(&PathBuf::from(OPENED_CHEST_TEXTURE), &asset_server);
+
+	let chest_position = transform.translation.truncate();
+	let loot_table = chest.tier.loot_table();
+	let drop_count = rng.gen_range(chest.tier.drop_count());
+
+	let mut drops = Vec::new();
+
+	for _ in 0..drop_count {
+		let drop = match loot_table.roll(&mut rng.0) {
+			Some(drop) => drop,
+			None => break,
+		};
+
+		let offset = Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)) * LOOT_SCATTER;
+
+		drops.push(spawn_loot(
+			&mut commands,
+			drop,
+			chest_position + offset,
+			&mut textures,
+			&asset_server,
+			&mut rng.0,
+		));
+	}
+
+	commands.entity(tilemap.single()).push_children(&drops);
+
+	AudioPlayer::play_sfx(
+		audio.as_ref(),
+		crafting_sound.0.clone(),
+		PICKUP_VOLUME,
+		settings.as_ref(),
+	);
+}
@@ -1,6 +1,24 @@
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use serde::{Deserialize, Serialize};
 
-use crate::{GameState, HEIGHT, WIDTH, post_processing::MainCamera};
+use crate::{GameState, TILE_SIZE, post_processing::MainCamera, player::{cursor_to_world, Aim, AimDevice, Player}, settings::Settings, unit::Shooting};
+
+/// How far from the player the crosshair sits when aiming with a gamepad
+const GAMEPAD_CROSSHAIR_DISTANCE: f32 = 4.0 * TILE_SIZE;
+const DOT_CROSSHAIR_SIZE: f32 = 6.0;
+const CROSS_CROSSHAIR_LENGTH: f32 = 24.0;
+const CROSS_CROSSHAIR_THICKNESS: f32 = 3.0;
+const COOLDOWN_INDICATOR_SIZE: Vec2 = Vec2::new(30.0, 4.0);
+/// How far under the crosshair the cooldown indicator sits
+const COOLDOWN_INDICATOR_OFFSET: f32 = 22.0;
+const COOLDOWN_INDICATOR_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.8);
+const RELOAD_INDICATOR_COLOR: Color = Color::rgba(1.0, 0.8, 0.2, 0.8);
+const HEAT_INDICATOR_COLOR: Color = Color::rgba(1.0, 0.45, 0.1, 0.8);
+const OVERHEAT_INDICATOR_COLOR: Color = Color::rgba(1.0, 0.1, 0.1, 0.9);
+const CHARGE_INDICATOR_COLOR: Color = Color::rgba(0.4, 0.8, 1.0, 0.8);
+/// Once the charge can't go any higher
+const FULL_CHARGE_INDICATOR_COLOR: Color = Color::rgba(0.8, 1.0, 1.0, 1.0);
 
 pub struct CrosshairPlugin;
 
@@ -8,7 +26,7 @@ impl Plugin for CrosshairPlugin {
 	fn build(&self, app: &mut App) {
 		app.add_startup_system(load_crosshair_sprite)
 			.add_system_set(SystemSet::on_enter(GameState::Game).with_system(crosshair_setup))
-			.add_system_set(SystemSet::on_update(GameState::Game).with_system(crosshair_update.after("camera_follow_player")))
+			.add_system_set(SystemSet::on_update(GameState::Game).with_system(crosshair_update.after("camera_follow_player")).with_system(update_cooldown_indicator))
 			.add_system_set(SystemSet::on_exit(GameState::Game).with_system(crosshair_drop));
 	}
 }
@@ -16,6 +34,36 @@ impl Plugin for CrosshairPlugin {
 #[derive(Component)]
 pub struct Crosshair;
 
+/// Bar under the crosshair filling up until the active weapon can fire again, or showing its heat or charge
+#[derive(Component)]
+pub struct CooldownIndicator;
+
+/// Looks of the crosshair, picked in the settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrosshairStyle {
+	Classic,
+	Dot,
+	Cross,
+}
+
+impl CrosshairStyle {
+	pub fn next(self) -> Self {
+		match self {
+			Self::Classic => Self::Dot,
+			Self::Dot => Self::Cross,
+			Self::Cross => Self::Classic,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Classic => "Classic",
+			Self::Dot => "Dot",
+			Self::Cross => "Cross",
+		}
+	}
+}
+
 #[derive(Deref, DerefMut)]
 struct CrosshairSprite(Handle<Image>);
 
@@ -27,35 +75,134 @@ fn crosshair_setup(
 	mut commands: Commands,
 	mut windows: ResMut<Windows>,
 	sprite: Res<CrosshairSprite>,
+	settings: Res<Settings>,
 ) {
-	let window = windows.get_primary_mut().unwrap();
+	if let Some(window) = windows.get_primary_mut() {
+		window.set_cursor_visibility(false);
+	}
 
-	window.set_cursor_visibility(false);
+	let solid = |size: Vec2| SpriteBundle {
+		sprite: Sprite {
+			color: Color::WHITE,
+			custom_size: Some(size),
+			..Default::default()
+		},
+		..Default::default()
+	};
 
-	commands
-		.spawn_bundle(SpriteBundle {
+	let mut crosshair = match settings.crosshair {
+		CrosshairStyle::Classic => commands.spawn_bundle(SpriteBundle {
 			texture: sprite.clone(),
 			..Default::default()
-		})
+		}),
+		CrosshairStyle::Dot => commands.spawn_bundle(solid(Vec2::splat(DOT_CROSSHAIR_SIZE))),
+		CrosshairStyle::Cross => {
+			let mut crosshair = commands.spawn_bundle(solid(Vec2::ZERO));
+
+			crosshair.with_children(|parent| {
+				parent.spawn_bundle(solid(Vec2::new(CROSS_CROSSHAIR_LENGTH, CROSS_CROSSHAIR_THICKNESS)));
+				parent.spawn_bundle(solid(Vec2::new(CROSS_CROSSHAIR_THICKNESS, CROSS_CROSSHAIR_LENGTH)));
+			});
+
+			crosshair
+		}
+	};
+
+	crosshair
 		.insert(Crosshair)
-		.insert(Name::new("Crosshair"));
+		.insert(Name::new("Crosshair"))
+		.with_children(|parent| {
+			parent
+				.spawn_bundle(SpriteBundle {
+					sprite: Sprite {
+						color: COOLDOWN_INDICATOR_COLOR,
+						custom_size: Some(COOLDOWN_INDICATOR_SIZE),
+						// Grows from the left
+						anchor: Anchor::CenterLeft,
+						..Default::default()
+					},
+					transform: Transform::from_xyz(
+						-COOLDOWN_INDICATOR_SIZE.x / 2.0,
+						-COOLDOWN_INDICATOR_OFFSET,
+						0.1,
+					),
+					visibility: Visibility { is_visible: false },
+					..Default::default()
+				})
+				.insert(CooldownIndicator)
+				.insert(Name::new("CooldownIndicator"));
+		});
+}
+
+fn update_cooldown_indicator(
+	mut indicator_query: Query<(&mut Sprite, &mut Visibility), With<CooldownIndicator>>,
+	player_query: Query<&Shooting, With<Player>>,
+) {
+	let (mut sprite, mut visibility) = indicator_query.single_mut();
+	let shooting = player_query.single();
+
+	// The charge comes first, as it's only there while the trigger is held.
+	// A reload keeps the weapon from firing as well, and usually takes longer than the cooldown
+	let (progress, color, visible) = if let Some(charge) = shooting.charge_fraction() {
+		let color = if charge >= 1.0 {
+			FULL_CHARGE_INDICATOR_COLOR
+		} else {
+			CHARGE_INDICATOR_COLOR
+		};
+
+		(charge, color, true)
+	} else if shooting.is_reloading() {
+		let progress = shooting.reload.percent();
+		(progress, RELOAD_INDICATOR_COLOR, progress < 1.0)
+	} else if let Some(overheat) = shooting.overheat.as_ref() {
+		// The heat matters more than the short cooldown between the shots
+		let color = if overheat.is_locked() {
+			OVERHEAT_INDICATOR_COLOR
+		} else {
+			HEAT_INDICATOR_COLOR
+		};
+
+		(overheat.heat(), color, overheat.heat() > 0.0)
+	} else {
+		let progress = shooting.cooldown.percent();
+		// Gone once the weapon is ready
+		(progress, COOLDOWN_INDICATOR_COLOR, progress < 1.0)
+	};
+
+	if visibility.is_visible != visible {
+		visibility.is_visible = visible;
+	}
+
+	sprite.color = color;
+	sprite.custom_size = Some(Vec2::new(
+		COOLDOWN_INDICATOR_SIZE.x * progress,
+		COOLDOWN_INDICATOR_SIZE.y,
+	));
 }
 
 fn crosshair_update(
 	mut crosshair_query: Query<&mut Transform, With<Crosshair>>,
-	camera_query: Query<&Transform, (With<MainCamera>, Without<Crosshair>)>,
+	camera_query: Query<(&Transform, &OrthographicProjection), (With<MainCamera>, Without<Crosshair>)>,
+	player_query: Query<&Transform, (With<Player>, Without<Crosshair>, Without<MainCamera>)>,
 	windows: Res<Windows>,
+	aim: Res<Aim>,
 ) {
 	let mut crosshair_transform = crosshair_query.single_mut();
-	let camera_transform = camera_query.single();
-	let window = windows.get_primary().unwrap();
+	let (camera_transform, projection) = camera_query.single();
 
-	if let Some(cursor_position) = window.cursor_position() {
-		crosshair_transform.translation = camera_transform.translation
-			- Vec3::new(WIDTH, HEIGHT, 0.0) / 2.0
-			+ cursor_position.extend(0.0);
+	// Stay the same size on screen no matter the zoom
+	crosshair_transform.scale = Vec3::splat(projection.scale);
 
-			crosshair_transform.translation.z = 100.0;
+	if aim.device == AimDevice::Gamepad {
+		let player_transform = player_query.single();
+
+		crosshair_transform.translation = player_transform.translation
+			+ aim.direction.extend(0.0) * GAMEPAD_CROSSHAIR_DISTANCE;
+
+		crosshair_transform.translation.z = 100.0;
+	} else if let Some(cursor_position) = windows.get_primary().and_then(|window| window.cursor_position()) {
+		crosshair_transform.translation =
+			cursor_to_world(cursor_position, camera_transform, projection).extend(100.0);
 	}
 }
 
@@ -65,9 +212,10 @@ fn crosshair_drop(
 	mut windows: ResMut<Windows>,
 ) {
 	let crosshair = crosshair_query.single();
-	let window = windows.get_primary_mut().unwrap();
 
-	window.set_cursor_visibility(true);
+	if let Some(window) = windows.get_primary_mut() {
+		window.set_cursor_visibility(true);
+	}
 
 	commands.entity(crosshair).despawn_recursive();
 }
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use crate::fonts::RobotoFont;
+use crate::tilemap::Tilemap;
+use crate::time::TimeCounter;
+use crate::GameState;
+
+pub const DAMAGE_NUMBER_LIFETIME: f32 = 1.0;
+/// In pixels per second
+pub const DAMAGE_NUMBER_RISE_SPEED: f32 = 60.0;
+pub const DAMAGE_NUMBER_FONT_SIZE: f32 = 24.0;
+pub const CRIT_DAMAGE_NUMBER_FONT_SIZE: f32 = 40.0;
+
+/// Something at the position took the given damage
+pub struct DamageEvent {
+	pub position: Vec2,
+	pub amount: f32,
+	/// Whether the player is the one who got hurt
+	pub to_player: bool,
+	pub critical: bool,
+}
+
+#[derive(Component)]
+pub struct DamageNumber {
+	pub lifetime: Timer,
+}
+
+pub struct DamageNumberPlugin;
+
+impl Plugin for DamageNumberPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<DamageEvent>().add_system_set(
+			SystemSet::on_update(GameState::Game)
+				.with_system(spawn_damage_numbers)
+				.with_system(update_damage_numbers),
+		);
+	}
+}
+
+fn spawn_damage_numbers(
+	mut commands: Commands,
+	mut damage_events: EventReader<DamageEvent>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	font: Res<RobotoFont>,
+) {
+	for damage in damage_events.iter() {
+		let color = if damage.to_player {
+			Color::rgb(0.95, 0.04, 0.07)
+		} else if damage.critical {
+			Color::rgb(1.0, 0.85, 0.1)
+		} else {
+			Color::WHITE
+		};
+
+		// Crits are bigger and louder
+		let (text, font_size) = if damage.critical {
+			(format!("{:.0}!", damage.amount), CRIT_DAMAGE_NUMBER_FONT_SIZE)
+		} else {
+			(format!("{:.0}", damage.amount), DAMAGE_NUMBER_FONT_SIZE)
+		};
+
+		let number = commands
+			.spawn_bundle(Text2dBundle {
+				text: Text::from_section(
+					text,
+					TextStyle {
+						font: font.0.clone(),
+						font_size,
+						color,
+					},
+				)
+				.with_alignment(TextAlignment::CENTER),
+				transform: Transform::from_translation(damage.position.extend(80.0)),
+				..Default::default()
+			})
+			.insert(Name::new("DamageNumber"))
+			.insert(DamageNumber {
+				lifetime: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, false),
+			})
+			.id();
+
+		commands.entity(tilemap.single()).add_child(number);
+	}
+}
+
+fn update_damage_numbers(
+	mut commands: Commands,
+	mut numbers: Query<(Entity, &mut Transform, &mut Text, &mut DamageNumber)>,
+	time: Res<TimeCounter>,
+) {
+	for (entity, mut transform, mut text, mut number) in numbers.iter_mut() {
+		number.lifetime.tick(time.delta());
+
+		if number.lifetime.finished() {
+			commands.entity(entity).despawn_recursive();
+			continue;
+		}
+
+		transform.translation.y += DAMAGE_NUMBER_RISE_SPEED * time.delta_seconds();
+
+		let alpha = 1.0 - number.lifetime.percent();
+
+		for section in text.sections.iter_mut() {
+			section.style.color.set_a(alpha);
+		}
+	}
+}
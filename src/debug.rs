@@ -2,13 +2,40 @@ use bevy::prelude::*;
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_prototype_debug_lines::DebugLinesPlugin;
 
+use crate::key_bindings::KeyBindings;
+use crate::time::TimeCounter;
+
+/// Speeds the debug key goes through, in order
+pub const DEBUG_TIME_SCALES: [f32; 3] = [0.25, 1.0, 2.0];
+
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         if cfg!(debug_assertions) {
             app.add_plugin(WorldInspectorPlugin::new())
-                .add_plugin(DebugLinesPlugin::default());
+                .add_plugin(DebugLinesPlugin::default())
+                .add_system(cycle_debug_time_scale);
         }
     }
 }
+
+fn cycle_debug_time_scale(
+    keyboard: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut time: ResMut<TimeCounter>,
+) {
+    if !keyboard.just_pressed(key_bindings.debug_time_scale) {
+        return;
+    }
+
+    // A scale set from elsewhere, like zero for a pause, goes back to normal speed
+    let next = DEBUG_TIME_SCALES
+        .iter()
+        .position(|scale| *scale == time.debug_scale)
+        .map_or(1, |index| (index + 1) % DEBUG_TIME_SCALES.len());
+
+    time.debug_scale = DEBUG_TIME_SCALES[next];
+
+    info!("Game running at {}x speed", time.debug_scale);
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::bullet::ShotEvent;
+use crate::particle::{spawn_burst, Particle, ParticleSettings};
+use crate::pool::Pool;
+use crate::tilemap::{CullableTile, Tile, TileCollider, Tilemap};
+use crate::unit::Health;
+use crate::TILE_SIZE;
+
+pub const DESTRUCTIBLE_WALL_HEALTH: f32 = 90.0;
+pub const DEBRIS_COLOR: Color = Color::rgb(0.55, 0.4, 0.25);
+
+/// Wall tile that breaks after taking enough hits
+#[derive(Component, Default)]
+pub struct Destructible;
+
+/// Unlike the other walls, each of these has its own collider, so it can be taken out on its own
+#[derive(Bundle)]
+pub struct DestructibleWallBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	destructible: Destructible,
+	health: Health,
+	collider: TileCollider,
+	rapier_collider: Collider,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for DestructibleWallBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			destructible: Destructible,
+			health: Health::new(DESTRUCTIBLE_WALL_HEALTH),
+			collider: TileCollider {
+				half_size: Vec2::splat(TILE_SIZE / 2.0),
+			},
+			rapier_collider: Collider::cuboid(TILE_SIZE / 2.0, TILE_SIZE / 2.0),
+			cullable: CullableTile,
+			name: Name::new("DestructibleWall"),
+		}
+	}
+}
+
+impl Tile for DestructibleWallBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_xyz(position.x, position.y, 10.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+/// Bullets already stop at the first collider they hit, so only the damage needs handling here.
+pub fn damage_destructibles(
+	mut commands: Commands,
+	mut destructibles: Query<(&Transform, &mut Health), With<Destructible>>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	mut shot_events: EventReader<ShotEvent>,
+	particle_settings: Res<ParticleSettings>,
+	mut particle_pool: ResMut<Pool<Particle>>,
+) {
+	let mut destroyed: Vec<Entity> = Vec::new();
+
+	for shot in shot_events.iter() {
+		if destroyed.contains(&shot.0) {
+			continue;
+		}
+
+		if let Ok((transform, mut health)) = destructibles.get_mut(shot.0) {
+			if !health.take_damage(shot.1) {
+				continue;
+			}
+
+			spawn_burst(
+				&mut commands,
+				&mut particle_pool,
+				tilemap.single(),
+				transform.translation,
+				DEBRIS_COLOR,
+				particle_settings.death_burst_count,
+			);
+
+			commands.entity(shot.0).despawn_recursive();
+
+			destroyed.push(shot.0);
+		}
+	}
+}
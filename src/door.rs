@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::key_bindings::KeyBindings;
+use crate::player::{Player, PLAYER_RADIUS};
+use crate::tilemap::{CullableTile, Tile, TileCollider};
+use crate::time::TimeCounter;
+use crate::unit::Inventory;
+use crate::TILE_SIZE;
+
+/// How close the player has to be for a proximity door to open
+pub const DOOR_OPEN_RANGE: f32 = 1.5 * TILE_SIZE;
+/// How close the player has to be to open or close a door by hand
+pub const DOOR_INTERACT_RANGE: f32 = 1.5 * TILE_SIZE;
+/// Seconds the door takes to slide open or shut
+pub const DOOR_ANIMATION_TIME: f32 = 0.25;
+/// How far into the wall next to it an open door slides
+pub const DOOR_SLIDE: f32 = 0.8 * TILE_SIZE;
+
+/// Keys only open the locked doors of their own color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyColor {
+	Red,
+	Blue,
+	Green,
+	Yellow,
+}
+
+impl KeyColor {
+	pub const ALL: [KeyColor; 4] = [KeyColor::Red, KeyColor::Blue, KeyColor::Green, KeyColor::Yellow];
+
+	/// Parses the "lock" property of a door tile and the "key" property of a key tile.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"red" => Some(Self::Red),
+			"blue" => Some(Self::Blue),
+			"green" => Some(Self::Green),
+			"yellow" => Some(Self::Yellow),
+			_ => None,
+		}
+	}
+
+	/// Tint of the keys and doors of this color
+	pub fn color(&self) -> Color {
+		match self {
+			KeyColor::Red => Color::rgb(1.0, 0.35, 0.35),
+			KeyColor::Blue => Color::rgb(0.4, 0.55, 1.0),
+			KeyColor::Green => Color::rgb(0.4, 1.0, 0.45),
+			KeyColor::Yellow => Color::rgb(1.0, 0.9, 0.3),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoorMode {
+	/// Opens by itself when the player comes close
+	Proximity,
+	/// The player opens and closes it with the interact key
+	Interact,
+	/// Needs a key of the color to open, after that it works like an `Interact` door
+	Locked(KeyColor),
+}
+
+impl DoorMode {
+	/// Parses the "door" property of a wall tile.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"proximity" => Some(Self::Proximity),
+			"interact" => Some(Self::Interact),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct Door {
+	pub mode: DoorMode,
+	pub open: bool,
+	closed_position: Vec2,
+	slide_direction: Vec2,
+	/// 0 when shut, 1 when fully open
+	progress: f32,
+}
+
+#[derive(Bundle)]
+pub struct DoorBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	door: Door,
+	collider: TileCollider,
+	rapier_collider: Collider,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for DoorBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			door: Door {
+				mode: DoorMode::Proximity,
+				open: false,
+				closed_position: Vec2::ZERO,
+				slide_direction: Vec2::X,
+				progress: 0.0,
+			},
+			collider: door_collider(),
+			rapier_collider: door_rapier_collider(),
+			cullable: CullableTile,
+			name: Name::new("Door"),
+		}
+	}
+}
+
+impl Tile for DoorBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		let mut bundle = Self {
+			sprite_bundle: SpriteBundle {
+				// Just under the walls, so that it slides beneath them
+				transform: Transform::from_xyz(position.x, position.y, 9.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		bundle.door.closed_position = position;
+		bundle
+	}
+}
+
+impl DoorBundle {
+	pub fn with_mode(mut self, mode: DoorMode) -> Self {
+		self.door.mode = mode;
+
+		if let DoorMode::Locked(key_color) = mode {
+			self.sprite_bundle.sprite.color = key_color.color();
+		}
+
+		self
+	}
+
+	/// Sets which way the door slides when opening, towards the wall it's set in.
+	pub fn sliding_towards(mut self, direction: Vec2) -> Self {
+		self.door.slide_direction = direction;
+		self
+	}
+}
+
+fn door_collider() -> TileCollider {
+	TileCollider {
+		half_size: Vec2::splat(TILE_SIZE / 2.0),
+	}
+}
+
+fn door_rapier_collider() -> Collider {
+	Collider::cuboid(TILE_SIZE / 2.0, TILE_SIZE / 2.0)
+}
+
+pub fn update_doors(
+	mut commands: Commands,
+	mut doors: Query<(Entity, &mut Door, &mut Transform)>,
+	mut player: Query<(&Transform, &mut Inventory), (With<Player>, Without<Door>)>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	time: Res<TimeCounter>,
+) {
+	let (player_transform, mut inventory) = player.single_mut();
+	let player_position = player_transform.translation.truncate();
+
+	for (entity, mut door, mut transform) in doors.iter_mut() {
+		let offset = player_position - door.closed_position;
+
+		let wants_open = match door.mode {
+			DoorMode::Proximity => offset.length() <= DOOR_OPEN_RANGE,
+			DoorMode::Interact => {
+				if keyboard.just_pressed(key_bindings.interact) && offset.length() <= DOOR_INTERACT_RANGE {
+					!door.open
+				} else {
+					door.open
+				}
+			}
+			DoorMode::Locked(key_color) => {
+				let interacted =
+					keyboard.just_pressed(key_bindings.interact) && offset.length() <= DOOR_INTERACT_RANGE;
+
+				// The key is used up, but the door stays unlocked for good
+				if interacted && inventory.use_key(key_color) {
+					door.mode = DoorMode::Interact;
+					true
+				} else {
+					door.open
+				}
+			}
+		};
+
+		// Shutting the door on the player would trap them inside of it
+		let player_in_doorway = offset.x.abs() < TILE_SIZE / 2.0 + PLAYER_RADIUS
+			&& offset.y.abs() < TILE_SIZE / 2.0 + PLAYER_RADIUS;
+
+		if wants_open != door.open && (wants_open || !player_in_doorway) {
+			door.open = wants_open;
+
+			if door.open {
+				commands.entity(entity).remove::<TileCollider>().remove::<Collider>();
+			} else {
+				commands
+					.entity(entity)
+					.insert(door_collider())
+					.insert(door_rapier_collider());
+			}
+		}
+
+		let step = time.delta_seconds() / DOOR_ANIMATION_TIME;
+
+		door.progress = if door.open {
+			(door.progress + step).min(1.0)
+		} else {
+			(door.progress - step).max(0.0)
+		};
+
+		let position = door.closed_position + door.slide_direction * DOOR_SLIDE * door.progress;
+		transform.translation = position.extend(transform.translation.z);
+	}
+}
@@ -0,0 +1,342 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::destructible::DestructibleWallBundle;
+use crate::fonts::RobotoFont;
+use crate::key_bindings::KeyBindings;
+use crate::player::cursor_to_world;
+use crate::post_processing::MainCamera;
+use crate::save;
+use crate::tilemap::{FloorBundle, Tile, TileCollider, TexturesMemo, WallBundle};
+use crate::{GameState, TILE_SIZE};
+
+/// File in the save directory the edited level is exported to, and loaded back from when the editor opens
+pub const EDITOR_LEVEL_FILE: &str = "editor_level.ron";
+/// In tiles per second
+pub const EDITOR_CAMERA_SPEED: f32 = 15.0;
+/// Keys picking the palette entries, in order
+const PALETTE_KEYS: [KeyCode; 3] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+
+/// What can be placed on a grid cell
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditorTile {
+	Floor,
+	Wall,
+	DestructibleWall,
+}
+
+impl EditorTile {
+	/// The palette, in the order of the keys selecting it
+	pub const ALL: [EditorTile; 3] = [EditorTile::Floor, EditorTile::Wall, EditorTile::DestructibleWall];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			EditorTile::Floor => "Floor",
+			EditorTile::Wall => "Wall",
+			EditorTile::DestructibleWall => "Destructible Wall",
+		}
+	}
+
+	fn texture(&self) -> &'static str {
+		match self {
+			EditorTile::Floor => "img/concrete.png",
+			EditorTile::Wall => "img/pillar.png",
+			EditorTile::DestructibleWall => "img/brick_1.png",
+		}
+	}
+}
+
+/// The layout as it's written to the file, the cells are in tiles with y going up like in the world
+#[derive(Default, Serialize, Deserialize)]
+pub struct EditorLevel {
+	pub tiles: Vec<((i32, i32), EditorTile)>,
+}
+
+/// State of the editor while it's open
+pub struct Editor {
+	pub brush: EditorTile,
+	tiles: HashMap<IVec2, (EditorTile, Entity)>,
+}
+
+impl Default for Editor {
+	fn default() -> Self {
+		Self {
+			brush: EditorTile::Wall,
+			tiles: HashMap::new(),
+		}
+	}
+}
+
+impl Editor {
+	fn to_level(&self) -> EditorLevel {
+		EditorLevel {
+			tiles: self
+				.tiles
+				.iter()
+				.map(|(cell, (tile, _))| ((cell.x, cell.y), *tile))
+				.collect(),
+		}
+	}
+}
+
+/// Everything spawned by the editor is put under this, so that it can be dropped at once
+#[derive(Component)]
+struct EditorRoot;
+
+#[derive(Component)]
+struct EditorUi;
+
+#[derive(Component)]
+struct BrushText;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(Editor::default())
+			.add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(open_editor))
+			.add_system_set(SystemSet::on_enter(GameState::Editor).with_system(setup_editor))
+			.add_system_set(
+				SystemSet::on_update(GameState::Editor)
+					.with_system(pick_brush)
+					.with_system(paint_tiles)
+					.with_system(move_editor_camera)
+					.with_system(export_level)
+					.with_system(close_editor)
+					.with_system(update_ui),
+			)
+			.add_system_set(SystemSet::on_exit(GameState::Editor).with_system(drop_editor));
+	}
+}
+
+fn open_editor(
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	mut state: ResMut<State<GameState>>,
+) {
+	if keyboard.just_pressed(key_bindings.open_editor) && state.set(GameState::Editor).is_err() {}
+}
+
+fn setup_editor(
+	mut commands: Commands,
+	mut editor: ResMut<Editor>,
+	mut textures: ResMut<TexturesMemo>,
+	asset_server: Res<AssetServer>,
+	roboto_font: Res<RobotoFont>,
+) {
+	let root = commands
+		.spawn()
+		.insert(Name::new("EditorRoot"))
+		.insert(Visibility::default())
+		.insert(ComputedVisibility::default())
+		.insert(Transform::default())
+		.insert(GlobalTransform::default())
+		.insert(EditorRoot)
+		.id();
+
+	// Picks up where the last export left off
+	let level = save::load::<EditorLevel>(EDITOR_LEVEL_FILE);
+
+	editor.tiles.clear();
+
+	for ((x, y), tile) in level.tiles {
+		let cell = IVec2::new(x, y);
+		let entity = spawn_tile(&mut commands, root, cell, tile, &mut textures, &asset_server);
+
+		editor.tiles.insert(cell, (tile, entity));
+	}
+
+	commands
+		.spawn_bundle(
+			TextBundle::from_section(
+				"",
+				TextStyle {
+					font: roboto_font.0.clone(),
+					font_size: 24.0,
+					color: Color::WHITE,
+				},
+			)
+			.with_style(Style {
+				position_type: PositionType::Absolute,
+				position: UiRect {
+					left: Val::Px(10.0),
+					top: Val::Px(10.0),
+					..Default::default()
+				},
+				..Default::default()
+			}),
+		)
+		.insert(BrushText)
+		.insert(EditorUi)
+		.insert(Name::new("EditorUi"));
+}
+
+fn spawn_tile(
+	commands: &mut Commands,
+	root: Entity,
+	cell: IVec2,
+	tile: EditorTile,
+	textures: &mut TexturesMemo,
+	asset_server: &Res<AssetServer>,
+) -> Entity {
+	let position = cell.as_vec2() * TILE_SIZE;
+	let texture = textures.get(&PathBuf::from(tile.texture()), asset_server);
+
+	let entity = match tile {
+		EditorTile::Floor => commands.spawn_bundle(FloorBundle::spawn(position, texture, false, false)).id(),
+		// The walls can't be merged into bigger colliders like in a loaded level, since they change all the time
+		EditorTile::Wall => commands
+			.spawn_bundle(WallBundle::spawn(position, texture, false, false))
+			.insert(TileCollider {
+				half_size: Vec2::splat(TILE_SIZE / 2.0),
+			})
+			.insert(Collider::cuboid(TILE_SIZE / 2.0, TILE_SIZE / 2.0))
+			.id(),
+		EditorTile::DestructibleWall => commands
+			.spawn_bundle(DestructibleWallBundle::spawn(position, texture, false, false))
+			.id(),
+	};
+
+	commands.entity(root).add_child(entity);
+
+	entity
+}
+
+fn pick_brush(keyboard: Res<Input<KeyCode>>, mut editor: ResMut<Editor>) {
+	for (key, tile) in PALETTE_KEYS.iter().zip(EditorTile::ALL) {
+		if keyboard.just_pressed(*key) {
+			editor.brush = tile;
+		}
+	}
+}
+
+/// Holding the left mouse button places the brush on the cells under the cursor, the right one erases them
+fn paint_tiles(
+	mut commands: Commands,
+	mut editor: ResMut<Editor>,
+	mouse_input: Res<Input<MouseButton>>,
+	windows: Res<Windows>,
+	camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+	root_query: Query<Entity, With<EditorRoot>>,
+	mut textures: ResMut<TexturesMemo>,
+	asset_server: Res<AssetServer>,
+) {
+	let placing = mouse_input.pressed(MouseButton::Left);
+	let erasing = mouse_input.pressed(MouseButton::Right);
+
+	if !placing && !erasing {
+		return;
+	}
+
+	let cursor_position = match windows.get_primary().and_then(|window| window.cursor_position()) {
+		Some(position) => position,
+		None => return,
+	};
+
+	let (camera_transform, projection) = camera_query.single();
+	let world_position = cursor_to_world(cursor_position, camera_transform, projection);
+
+	// Tile positions are their centers
+	let cell = (world_position / TILE_SIZE).round().as_ivec2();
+
+	let existing = editor.tiles.get(&cell).copied();
+
+	if erasing {
+		if let Some((_, entity)) = existing {
+			commands.entity(entity).despawn_recursive();
+			editor.tiles.remove(&cell);
+		}
+
+		return;
+	}
+
+	let brush = editor.brush;
+
+	match existing {
+		Some((tile, _)) if tile == brush => return,
+		Some((_, entity)) => commands.entity(entity).despawn_recursive(),
+		None => {}
+	}
+
+	let entity = spawn_tile(
+		&mut commands,
+		root_query.single(),
+		cell,
+		brush,
+		&mut textures,
+		&asset_server,
+	);
+
+	editor.tiles.insert(cell, (brush, entity));
+}
+
+fn move_editor_camera(
+	mut camera_query: Query<&mut Transform, With<MainCamera>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	time: Res<Time>,
+) {
+	let mut direction = Vec2::ZERO;
+
+	if keyboard.pressed(key_bindings.up) {
+		direction.y += 1.0;
+	}
+
+	if keyboard.pressed(key_bindings.down) {
+		direction.y -= 1.0;
+	}
+
+	if keyboard.pressed(key_bindings.right) {
+		direction.x += 1.0;
+	}
+
+	if keyboard.pressed(key_bindings.left) {
+		direction.x -= 1.0;
+	}
+
+	let mut camera_transform = camera_query.single_mut();
+	camera_transform.translation +=
+		(direction.normalize_or_zero() * EDITOR_CAMERA_SPEED * TILE_SIZE * time.delta_seconds()).extend(0.0);
+}
+
+fn export_level(keyboard: Res<Input<KeyCode>>, key_bindings: Res<KeyBindings>, editor: Res<Editor>) {
+	if keyboard.just_pressed(key_bindings.export_level) {
+		save::save(EDITOR_LEVEL_FILE, &editor.to_level());
+
+		info!("Exported a level of {} tiles", editor.tiles.len());
+	}
+}
+
+fn close_editor(keyboard: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+	if keyboard.just_pressed(KeyCode::Escape) && state.set(GameState::MainMenu).is_err() {}
+}
+
+fn update_ui(editor: Res<Editor>, key_bindings: Res<KeyBindings>, mut text_query: Query<&mut Text, With<BrushText>>) {
+	if !editor.is_changed() {
+		return;
+	}
+
+	let mut text = text_query.single_mut();
+	text.sections[0].value = format!(
+		"Brush: {} (1-3 to pick, right click to erase)\n{:?} to export, Escape to leave",
+		editor.brush.name(),
+		key_bindings.export_level,
+	);
+}
+
+fn drop_editor(
+	mut commands: Commands,
+	mut editor: ResMut<Editor>,
+	root_query: Query<Entity, With<EditorRoot>>,
+	ui_query: Query<Entity, With<EditorUi>>,
+) {
+	for entity in root_query.iter().chain(ui_query.iter()) {
+		commands.entity(entity).despawn_recursive();
+	}
+
+	editor.tiles.clear();
+}
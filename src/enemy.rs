@@ -6,39 +6,85 @@ use bevy::prelude::*;
 use bevy_kira_audio::Audio;
 use bevy_rapier2d::prelude::*;
 use navmesh::NavVec3;
-use rand::random;
+use rand::{random, Rng};
 use rand::seq::SliceRandom;
 
 use crate::audio::{EnemyShotSound, Screams};
 use crate::audio_player::{AudioPlayer, ENEMY_SHOT_VOLUME, ENEMY_DEATH_SCREAM_VOLUME};
 use crate::bullet::{
-	Bullet, BulletBundle, BulletTexture, ShotEvent, BULLET_COLLIDER_HEIGHT, BULLET_COLLIDER_WIDTH,
+	Bullet, BulletSpawner, KnockbackEvent, ShotEvent, BULLET_COLLIDER_HEIGHT,
+	BULLET_COLLIDER_WIDTH, BULLET_RANGE,
 };
+use crate::damage_number::DamageEvent;
+use crate::enemy_health_bar::{spawn_enemy_health_bars, update_enemy_health_bars};
 use crate::enemy_nav_mesh::EnemyNavMesh;
+use crate::enemy_type::{load_enemy_types, EnemyStats};
 use crate::player::Player;
 use crate::post_processing::MainCamera;
-use crate::stats::Stats;
+use crate::stats::{Score, Stats};
+use crate::status_effect::{StatusEffect, StatusEffects};
 use crate::settings::Settings;
-use crate::tilemap::{TexturesMemo, Tile, Tilemap};
+use crate::line_of_sight::has_line_of_sight;
+use crate::loot::{LootSpawner, LootTable};
+use crate::particle::{spawn_burst, Particle, ParticleSettings};
+use crate::pool::Pool;
+use crate::tilemap::{TexturesMemo, Tile, TileCollider, Tilemap};
 use crate::time::TimeCounter;
-use crate::unit::{Movement, ShootEvent, Shooting};
+use crate::unit::{Armor, Health, Knockback, Movement, ShootEvent, Shooting, Weapon};
 use crate::{GameState, TILE_SIZE};
 
 pub const ENEMY_SIGHT: f32 = 12.0 * TILE_SIZE;
 pub const ENEMY_HEARING: f32 = 10.0 * TILE_SIZE;
+/// Seconds an enemy keeps chasing after the player got out of sight
+pub const LOSE_AGGRO_DELAY: f32 = 0.5;
 pub const SHOCK_DURATION: f32 = 0.5;
+/// How quickly a knocked back enemy comes to a halt
+pub const KNOCKBACK_DAMPING: f32 = 8.0;
+/// Color of the particles flying out of a dying enemy
+pub const BLOOD_COLOR: Color = Color::rgb(0.6, 0.02, 0.04);
+/// Score the player gets for killing an enemy
+pub const ENEMY_POINTS: u32 = 100;
+/// Enemies further than this from the player stop running their AI
+pub const ENEMY_ACTIVE_DISTANCE: f32 = 30.0 * TILE_SIZE;
+/// Extra distance a dormant enemy needs to be within to wake up, so that one on the edge doesn't flicker
+pub const ENEMY_WAKE_MARGIN: f32 = 2.0 * TILE_SIZE;
+
+/// How far the enemies keep running their AI, lower it if there is a performance problem
+pub struct EnemyCulling {
+	pub active_distance: f32,
+	/// Also hides the dormant enemies
+	pub hide_dormant: bool,
+}
+
+impl Default for EnemyCulling {
+	fn default() -> Self {
+		Self {
+			active_distance: ENEMY_ACTIVE_DISTANCE,
+			hide_dormant: false,
+		}
+	}
+}
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_startup_system(load_enemy_textures).add_system_set(
+		app.insert_resource(load_enemy_types())
+			.insert_resource(EnemyCulling::default())
+			.add_startup_system(load_enemy_textures)
+			.add_system_set(
 			SystemSet::on_update(GameState::Game)
-				.with_system(update_enemy_ai.label("update_enemy_ai"))
+				.with_system(cull_enemies.label("cull_enemies"))
+				.with_system(enemy_detection.label("enemy_detection").after("cull_enemies"))
+				.with_system(update_enemy_ai.label("update_enemy_ai").after("enemy_detection"))
 				.with_system(alert_on_shot_sound)
 				.with_system(update_enemy_position.after("update_enemy_ai"))
 				.with_system(update_enemy_texture)
-				.with_system(get_shot),
+				.with_system(apply_knockback.label("apply_knockback"))
+				.with_system(update_knockback.after("apply_knockback").after("update_enemy_ai"))
+				.with_system(get_shot)
+				.with_system(spawn_enemy_health_bars)
+				.with_system(update_enemy_health_bars),
 		);
 	}
 }
@@ -47,6 +93,41 @@ impl Plugin for EnemyPlugin {
 pub struct Enemy {
 	ai_state: EnemyAiState,
 	shock_timer: Timer,
+	points: u32,
+	drop_chance: f32,
+	loot: LootTable,
+}
+
+/// Enemy too far from the player to bother with. It keeps its state and picks up where it left off once woken up.
+#[derive(Component)]
+pub struct Dormant;
+
+/// Whether an enemy has noticed the player
+#[derive(Component)]
+pub struct Detection {
+	pub aggro_range: f32,
+	pub lose_aggro_delay: Timer,
+	aggroed: bool,
+}
+
+impl Detection {
+	pub fn new(aggro_range: f32, lose_aggro_delay: f32) -> Self {
+		Self {
+			aggro_range,
+			lose_aggro_delay: Timer::from_seconds(lose_aggro_delay, false),
+			aggroed: false,
+		}
+	}
+
+	pub fn is_aggroed(&self) -> bool {
+		self.aggroed
+	}
+}
+
+impl Default for Detection {
+	fn default() -> Self {
+		Self::new(ENEMY_SIGHT, LOSE_AGGRO_DELAY)
+	}
 }
 
 #[derive(Bundle)]
@@ -55,13 +136,20 @@ pub struct EnemyBundle {
 	sprite_budle: SpriteBundle,
 	name: Name,
 	enemy: Enemy,
+	detection: Detection,
+	health: Health,
+	armor: Armor,
 	movement: Movement,
 	shooting: Shooting,
+	knockback: Knockback,
 	rapier_collider: Collider,
+	status_effects: StatusEffects,
 }
 
 impl Default for EnemyBundle {
 	fn default() -> Self {
+		let stats = EnemyStats::default();
+
 		Self {
 			sprite_budle: SpriteBundle {
 				sprite: Sprite {
@@ -75,16 +163,51 @@ impl Default for EnemyBundle {
 			enemy: Enemy {
 				ai_state: EnemyAiState::Idle,
 				shock_timer: Timer::new(Duration::from_secs_f32(SHOCK_DURATION), false),
+				points: stats.points,
+				drop_chance: stats.drop_chance,
+				loot: stats.loot.clone(),
 			},
-			movement: Movement { speed: 3.0 },
-			shooting: Shooting {
-				cooldown: Timer::from_seconds(1.0, false),
-			},
+			detection: Detection::default(),
+			health: Health::new(stats.health),
+			armor: stats.armor,
+			movement: Movement { speed: stats.speed },
+			shooting: Shooting::single(enemy_weapon(&stats)),
+			knockback: Knockback::default(),
 			rapier_collider: Collider::cuboid(TILE_SIZE / 2.0, TILE_SIZE / 2.0),
+			status_effects: StatusEffects::default(),
 		}
 	}
 }
 
+fn enemy_weapon(stats: &EnemyStats) -> Weapon {
+	// Enemies don't keep track of their ammo
+	let mut weapon = Weapon::new(stats.shooting_cooldown, BULLET_RANGE, 30.0, 0, 0.0);
+	weapon.status_effect = stats.status_effect;
+	weapon
+}
+
+impl EnemyBundle {
+	/// Turns the enemy into the given kind.
+	pub fn with_stats(mut self, stats: &EnemyStats) -> Self {
+		self.enemy.points = stats.points;
+		self.enemy.drop_chance = stats.drop_chance;
+		self.enemy.loot = stats.loot.clone();
+		self.health = Health::new(stats.health);
+		self.armor = stats.armor;
+		self.movement.speed = stats.speed;
+		self.shooting = Shooting::single(enemy_weapon(stats));
+		self.sprite_budle.sprite.color = stats.color();
+		self
+	}
+
+	/// Makes the enemy walk towards the target right away, instead of waiting to notice the player.
+	pub fn alerted_towards(mut self, target: Vec2, nav_mesh: &EnemyNavMesh) -> Self {
+		let position = self.sprite_budle.transform.translation.truncate();
+		self.enemy.alert_towards(position, target, nav_mesh);
+		self
+	}
+}
+
 impl Tile for EnemyBundle {
 	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
 		Self {
@@ -140,20 +263,95 @@ enum EnemyAiState {
 	},
 }
 
-fn is_on_screen(point: Vec2, window: &Window, camera: &Transform) -> bool {
-	let screen_position = point - camera.translation.truncate();
+impl Enemy {
+	fn alert_towards(&mut self, position: Vec2, target: Vec2, nav_mesh: &EnemyNavMesh) {
+		let path = nav_mesh
+			.get_nav_mesh()
+			.expect("The nav mesh has not been baked!")
+			.find_path(
+				position.to_array().into(),
+				target.to_array().into(),
+				navmesh::NavQuery::Closest,
+				navmesh::NavPathMode::Accuracy,
+			);
+
+		self.ai_state = EnemyAiState::Alert { path, current: 0 };
+	}
+}
+
+fn is_on_screen(point: Vec2, window: &Window, camera: &Transform, projection: &OrthographicProjection) -> bool {
+	// Zooming out shows more of the world
+	let screen_position = (point - camera.translation.truncate()) / projection.scale;
 
 	screen_position.x.abs() < (window.width() - TILE_SIZE) / 2.0
 		&& screen_position.y.abs() < (window.height() - TILE_SIZE) / 2.0
 }
 
+fn cull_enemies(
+	mut commands: Commands,
+	mut enemies: Query<(Entity, &Transform, &Enemy, &mut Visibility, Option<&Dormant>)>,
+	player: Query<&Transform, (With<Player>, Without<Enemy>)>,
+	culling: Res<EnemyCulling>,
+) {
+	let player_position = player.single().translation.truncate();
+
+	for (entity, transform, enemy, mut visibility, dormant) in enemies.iter_mut() {
+		let distance = transform.translation.truncate().distance(player_position);
+
+		// Enemies on their way somewhere, like the ones sent by a wave, have to get there first
+		let idle = matches!(enemy.ai_state, EnemyAiState::Idle);
+
+		match dormant {
+			Some(_) if distance <= culling.active_distance - ENEMY_WAKE_MARGIN => {
+				commands.entity(entity).remove::<Dormant>();
+				visibility.is_visible = true;
+			}
+			None if idle && distance > culling.active_distance => {
+				commands.entity(entity).insert(Dormant);
+				visibility.is_visible = !culling.hide_dormant;
+			}
+			_ => {}
+		}
+	}
+}
+
+fn enemy_detection(
+	mut enemies: Query<(&Transform, &mut Detection), Without<Dormant>>,
+	player: Query<&Transform, (With<Player>, Without<Detection>)>,
+	walls: Query<(), With<TileCollider>>,
+	rapier_context: Res<RapierContext>,
+	time: Res<TimeCounter>,
+) {
+	let player_position = player.single().translation.truncate();
+
+	for (transform, mut detection) in enemies.iter_mut() {
+		let position = transform.translation.truncate();
+
+		// Walls hide the player, but other enemies in the way don't
+		let sees_player = (player_position - position).length() <= detection.aggro_range
+			&& has_line_of_sight(&rapier_context, &walls, position, player_position);
+
+		if sees_player {
+			detection.aggroed = true;
+			detection.lose_aggro_delay.reset();
+		} else if detection.aggroed {
+			// Give the player a moment to actually get away, so peeking around a corner doesn't flicker
+			detection.lose_aggro_delay.tick(time.delta());
+
+			if detection.lose_aggro_delay.finished() {
+				detection.aggroed = false;
+			}
+		}
+	}
+}
+
 fn update_enemy_ai(
 	mut commands: Commands,
-	mut enemies: Query<(Entity, &mut Transform, &mut Shooting, &mut Enemy)>,
+	mut enemies: Query<(Entity, &mut Transform, &mut Shooting, &mut Enemy, &Detection), Without<Dormant>>,
 	mut player: Query<(Entity, &Transform), (With<Player>, Without<Enemy>)>,
 	tilemap: Query<Entity, (With<Tilemap>, Without<Player>, Without<Enemy>)>,
 	camera: Query<
-		&Transform,
+		(&Transform, &OrthographicProjection),
 		(
 			With<MainCamera>,
 			Without<Player>,
@@ -170,104 +368,124 @@ fn update_enemy_ai(
 	nav_mesh: Res<EnemyNavMesh>,
 	audio: Res<Audio>,
 	shot_sound: Res<EnemyShotSound>,
-	bullet_texture: Res<BulletTexture>,
+	mut bullets: BulletSpawner,
 ) {
 	let (player, player_transform) = player.single_mut();
 	let tilemap = tilemap.single();
-	let camera = camera.single();
+	let (camera, projection) = camera.single();
 
 	let player_position = player_transform.translation.truncate();
 
-	for (entity, mut transform, mut shooting, mut enemy) in enemies.iter_mut() {
+	for (entity, mut transform, mut shooting, mut enemy, detection) in enemies.iter_mut() {
 		shooting.cooldown.tick(time.delta());
 
-		let position = transform.translation.truncate();
+		// Idle enemies that haven't noticed anything have nothing to do
+		if !detection.is_aggroed() && matches!(enemy.ai_state, EnemyAiState::Idle) {
+			continue;
+		}
 
-		// Look if there is a direct line of sight to the player
-		let shape = Collider::cuboid(BULLET_COLLIDER_WIDTH, BULLET_COLLIDER_HEIGHT);
-		let shape_origin = position;
-		let shape_direction = (player_position - position).normalize();
-		let shape_rotation = transform.rotation.z;
-		let max_time_of_impact = ENEMY_SIGHT;
-		let filter = QueryFilter::default()
-			.exclude_collider(entity)
-			.exclude_sensors();
+		let position = transform.translation.truncate();
 
-		if let Some((entity, _)) = rapier_context.cast_shape(
-			shape_origin,
-			shape_rotation,
-			shape_direction,
-			&shape,
-			max_time_of_impact,
-			filter,
-		) {
-			if entity.id() == player.id() {
-				// The enemy can see the player
-				enemy.ai_state = EnemyAiState::Combat { player_position };
-
-				transform.rotation =
-					Quat::from_rotation_z(Vec2::Y.angle_between(player_position - position));
-
-				// Don't shoot when off-screen
-				if is_on_screen(position, windows.primary(), camera) {
-					// Don't shoot immediately
-					enemy.shock_timer.tick(time.delta());
-
-					if enemy.shock_timer.finished() && shooting.cooldown.finished() {
-						// Shoot at the player
-						shoot(
-							&mut commands,
-							&transform,
-							&player_transform,
-							&player,
-							&tilemap,
-							bullet_texture.clone(),
-							&mut shoot_event,
-							&mut shot_event,
-						);
-
-						AudioPlayer::play_sfx(
-							audio.as_ref(),
-							shot_sound.clone(),
-							ENEMY_SHOT_VOLUME,
-							settings.as_ref(),
-						);
-
-						shooting.cooldown.reset();
-					}
-
-					continue;
-				}
-			}
+		if detection.is_aggroed() {
+			// The enemy can see the player
+			enemy.ai_state = EnemyAiState::Combat { player_position };
+
+			transform.rotation =
+				Quat::from_rotation_z(Vec2::Y.angle_between(player_position - position));
+
+			// Don't shoot when off-screen, without a window there is no screen to hide behind
+			if windows
+				.get_primary()
+				.map_or(true, |window| is_on_screen(position, window, camera, projection))
+			{
+				// Don't shoot immediately
+				enemy.shock_timer.tick(time.delta());
+
+				if enemy.shock_timer.finished()
+					&& shooting.cooldown.finished()
+					&& has_clear_shot(&rapier_context, entity, player, position, player_position)
+				{
+					// Shoot at the player
+					shoot(
+						&mut commands,
+						&transform,
+						&player_transform,
+						&player,
+						&tilemap,
+						&mut bullets,
+						shooting.damage,
+						shooting.status_effect,
+						&mut shoot_event,
+						&mut shot_event,
+					);
 
-			// Not in combat
-			if let EnemyAiState::Combat { player_position } = enemy.ai_state {
-				// When exiting combat
-				let path = nav_mesh
-					.get_nav_mesh()
-					.expect("The nav mesh has not been baked!")
-					.find_path(
-						transform.translation.to_array().into(),
-						player_position.to_array().into(),
-						navmesh::NavQuery::Closest,
-						navmesh::NavPathMode::Accuracy,
+					AudioPlayer::emit_spatial_sound(
+						audio.as_ref(),
+						shot_sound.clone(),
+						ENEMY_SHOT_VOLUME,
+						settings.as_ref(),
+						position,
+						player_position,
 					);
 
-				enemy.ai_state = EnemyAiState::Alert { path, current: 0 };
+					shooting.cooldown.reset();
+				}
+
+				continue;
 			}
+		}
+
+		// Not in combat
+		if let EnemyAiState::Combat { player_position } = enemy.ai_state {
+			// When exiting combat
+			let path = nav_mesh
+				.get_nav_mesh()
+				.expect("The nav mesh has not been baked!")
+				.find_path(
+					transform.translation.to_array().into(),
+					player_position.to_array().into(),
+					navmesh::NavQuery::Closest,
+					navmesh::NavPathMode::Accuracy,
+				);
 
-			enemy.shock_timer.reset();
+			enemy.ai_state = EnemyAiState::Alert { path, current: 0 };
 		}
+
+		enemy.shock_timer.reset();
+	}
+}
+
+/// # Returns
+/// True if a bullet fired at the player would reach them, without hitting another enemy first.
+fn has_clear_shot(
+	rapier_context: &RapierContext,
+	shooter: Entity,
+	player: Entity,
+	position: Vec2,
+	player_position: Vec2,
+) -> bool {
+	let shape = Collider::cuboid(BULLET_COLLIDER_WIDTH, BULLET_COLLIDER_HEIGHT);
+	let direction = (player_position - position).normalize();
+	let filter = QueryFilter::default()
+		.exclude_collider(shooter)
+		.exclude_sensors();
+
+	match rapier_context.cast_shape(position, 0.0, direction, &shape, ENEMY_SIGHT, filter) {
+		Some((entity, _)) => entity == player,
+		None => false,
 	}
 }
 
-fn shoot(
+/// Fires a bullet the way the enemy is facing.
+pub fn shoot(
 	commands: &mut Commands,
 	enemy_transform: &Transform,
 	player_transform: &Transform,
 	player_entity: &Entity,
 	tilemap: &Entity,
-	bullet_texture: Handle<Image>,
+	bullets: &mut BulletSpawner,
+	damage: f32,
+	status_effect: Option<StatusEffect>,
 	shoot_event: &mut EventWriter<ShootEvent>,
 	shot_event: &mut EventWriter<ShotEvent>,
 ) {
@@ -278,23 +496,27 @@ fn shoot(
 		let mut bullet_transform = enemy_transform
 			.with_translation(enemy_transform.translation + enemy_transform.up() * TILE_SIZE);
 
-		bullet_transform.rotate_z(random::<f32>() * 0.05);
-
-		let bullet = commands
-			.spawn_bundle(BulletBundle {
-				sprite_bundle: SpriteBundle {
-					transform: bullet_transform,
-					texture: bullet_texture,
-					..Default::default()
-				},
-				bullet: Bullet { speed: 2000.0 },
-				..Default::default()
-			})
-			.id();
-
-		commands.entity(*tilemap).push_children(&[bullet]);
+		bullet_transform.rotate_z(bullets.rng.gen::<f32>() * 0.05);
+
+		bullets.fire(
+			commands,
+			*tilemap,
+			bullet_transform,
+			Bullet {
+				speed: 2000.0,
+				range: BULLET_RANGE,
+				knockback: 0.0,
+				damage,
+				status_effect,
+				critical: false,
+				falloff: None,
+				traveled: 0.0,
+				pierce_left: 0,
+				pierced: Vec::new(),
+			},
+		);
 	} else {
-		shot_event.send(ShotEvent(*player_entity));
+		shot_event.send(ShotEvent(*player_entity, damage, status_effect, false));
 	}
 
 	shoot_event.send(ShootEvent(enemy_transform.translation.truncate()));
@@ -310,24 +532,14 @@ fn alert_on_shot_sound(
 			let enemy_position = enemy_transform.translation.truncate();
 
 			if (enemy_position - shot_event.0).length() <= ENEMY_HEARING {
-				let path = nav_mesh
-					.get_nav_mesh()
-					.expect("The nav mesh has not been baked!")
-					.find_path(
-						enemy_position.to_array().into(),
-						shot_event.0.to_array().into(),
-						navmesh::NavQuery::Closest,
-						navmesh::NavPathMode::Accuracy,
-					);
-
-				enemy.ai_state = EnemyAiState::Alert { path, current: 0 };
+				enemy.alert_towards(enemy_position, shot_event.0, &nav_mesh);
 			}
 		}
 	}
 }
 
 fn update_enemy_position(
-	mut enemies: Query<(&mut Transform, &mut Enemy, &Movement)>,
+	mut enemies: Query<(&mut Transform, &mut Enemy, &Movement), Without<Dormant>>,
 	time: Res<TimeCounter>,
 ) {
 	for (mut transform, mut enemy, movement) in enemies.iter_mut() {
@@ -369,8 +581,59 @@ fn update_enemy_position(
 	}
 }
 
+fn apply_knockback(
+	mut enemies: Query<&mut Knockback, With<Enemy>>,
+	mut knockback_events: EventReader<KnockbackEvent>,
+) {
+	for KnockbackEvent(entity, velocity) in knockback_events.iter() {
+		if let Ok(mut knockback) = enemies.get_mut(*entity) {
+			knockback.velocity += *velocity;
+		}
+	}
+}
+
+fn update_knockback(
+	mut enemies: Query<(Entity, &mut Transform, &mut Knockback, &Collider), With<Enemy>>,
+	rapier_context: Res<RapierContext>,
+	time: Res<TimeCounter>,
+) {
+	for (entity, mut transform, mut knockback, collider) in enemies.iter_mut() {
+		if knockback.velocity == Vec2::ZERO {
+			continue;
+		}
+
+		let movement_vector = knockback.velocity * time.delta_seconds();
+		let distance = movement_vector.length();
+
+		let filter = QueryFilter::default()
+			.exclude_collider(entity)
+			.exclude_sensors();
+
+		// Don't push the enemy through walls
+		let movement_vector = match rapier_context.cast_shape(
+			transform.translation.truncate(),
+			0.0,
+			movement_vector / distance,
+			collider,
+			distance,
+			filter,
+		) {
+			Some((_, hit)) => movement_vector / distance * hit.toi,
+			None => movement_vector,
+		};
+
+		transform.translation += movement_vector.extend(0.0);
+
+		knockback.velocity *= (-KNOCKBACK_DAMPING * time.delta_seconds()).exp();
+
+		if knockback.velocity.length() < 1.0 {
+			knockback.velocity = Vec2::ZERO;
+		}
+	}
+}
+
 fn update_enemy_texture(
-	mut enemy_query: Query<(&mut Handle<Image>, &Enemy)>,
+	mut enemy_query: Query<(&mut Handle<Image>, &Enemy), Without<Dormant>>,
 	textures: Res<EnemyTextures>,
 ) {
 	for (mut enemy_texture, enemy) in enemy_query.iter_mut() {
@@ -388,28 +651,48 @@ fn update_enemy_texture(
 fn get_shot(
 	mut commands: Commands,
 	tilemap_query: Query<Entity, With<Tilemap>>,
-	enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+	mut enemy_query: Query<(&Transform, &Enemy, &mut Health, &Armor)>,
+	player_query: Query<&Transform, With<Player>>,
 	mut shot_events: EventReader<ShotEvent>,
 	enemy_textures: Res<EnemyTextures>,
 	audio: Res<Audio>,
 	settings: Res<Settings>,
 	screams: Res<Screams>,
 	mut stats: ResMut<Stats>,
+	mut score: ResMut<Score>,
+	particle_settings: Res<ParticleSettings>,
+	mut particle_pool: ResMut<Pool<Particle>>,
+	mut damage_events: EventWriter<DamageEvent>,
+	mut loot: LootSpawner,
 ) {
 	let tilemap = tilemap_query.single();
-	let mut enemies: Vec<(Entity, &Transform)> = enemy_query.iter().collect();
+	let player_position = player_query.single().translation.truncate();
+	// The despawn only happens at the end of the frame, so remember who already died
+	let mut killed: Vec<Entity> = Vec::new();
 
 	for shot in shot_events.iter() {
-		let shot_entity = shot.0;
+		let enemy = shot.0;
+
+		if killed.contains(&enemy) {
+			continue;
+		}
 
-		let index = enemies.iter().position(|enemy| enemy.0 == shot_entity);
+		if let Ok((enemy_transform, enemy_component, mut health, armor)) = enemy_query.get_mut(enemy) {
+			let damage = armor.reduce(shot.1);
 
-		if let Some(index) = index {
-			let enemy_tuple = enemies[index];
-			let enemy_transform = enemy_tuple.1;
-			let enemy = enemy_tuple.0;
+			damage_events.send(DamageEvent {
+				position: enemy_transform.translation.truncate(),
+				amount: damage,
+				to_player: false,
+				critical: shot.3,
+			});
+
+			if !health.take_damage(damage) {
+				continue;
+			}
 
 			stats.enemies_killed += 1;
+			score.points += enemy_component.points;
 
 			// Spawn the enemy body
 			let body = commands
@@ -425,14 +708,16 @@ fn get_shot(
 
 			commands.entity(tilemap).push_children(&[body]);
 
-			AudioPlayer::play_sfx(
+			AudioPlayer::emit_spatial_sound(
 				audio.as_ref(),
 				screams
 					.choose(&mut rand::thread_rng())
 					.expect("No scream sounds found.")
 					.clone(),
 				ENEMY_DEATH_SCREAM_VOLUME,
-				settings.as_ref()
+				settings.as_ref(),
+				enemy_transform.translation.truncate(),
+				player_position,
 			);
 
 			// Spawn a few blood splatters
@@ -466,9 +751,27 @@ fn get_shot(
 
 			commands.entity(tilemap).push_children(&splatters);
 
+			spawn_burst(
+				&mut commands,
+				&mut particle_pool,
+				tilemap,
+				enemy_transform.translation,
+				BLOOD_COLOR,
+				particle_settings.death_burst_count,
+			);
+
+			if let Some(pickup) = loot.roll(
+				&mut commands,
+				&enemy_component.loot,
+				enemy_component.drop_chance,
+				enemy_transform.translation.truncate(),
+			) {
+				commands.entity(tilemap).add_child(pickup);
+			}
+
 			commands.entity(enemy).despawn_recursive();
 
-			enemies.remove(index);
+			killed.push(enemy);
 		}
 	}
 }
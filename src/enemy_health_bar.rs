@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use crate::enemy::Enemy;
+use crate::tilemap::Tilemap;
+use crate::unit::Health;
+use crate::TILE_SIZE;
+
+pub const HEALTH_BAR_WIDTH: f32 = TILE_SIZE;
+pub const HEALTH_BAR_HEIGHT: f32 = 6.0;
+/// How far above the enemy's center the bar floats
+pub const HEALTH_BAR_OFFSET: f32 = 0.75 * TILE_SIZE;
+
+/// Bar floating above an enemy. It isn't a child of the enemy, so that it doesn't turn with it.
+#[derive(Component)]
+pub struct EnemyHealthBar {
+	pub enemy: Entity,
+}
+
+#[derive(Component)]
+pub struct EnemyHealthBarFill;
+
+pub fn spawn_enemy_health_bars(
+	mut commands: Commands,
+	enemies: Query<(Entity, &Transform), (With<Enemy>, Added<Health>)>,
+	tilemap: Query<Entity, With<Tilemap>>,
+) {
+	for (enemy, transform) in enemies.iter() {
+		let bar = commands
+			.spawn_bundle(SpriteBundle {
+				sprite: Sprite {
+					color: Color::BLACK,
+					custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+					..Default::default()
+				},
+				transform: Transform::from_translation(bar_position(transform)),
+				visibility: Visibility { is_visible: false },
+				..Default::default()
+			})
+			.insert(Name::new("EnemyHealthBar"))
+			.insert(EnemyHealthBar { enemy })
+			.with_children(|parent| {
+				parent
+					.spawn_bundle(SpriteBundle {
+						sprite: Sprite {
+							color: Color::rgb(0.95, 0.04, 0.07),
+							custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+							// Shrinks towards the left edge
+							anchor: Anchor::CenterLeft,
+							..Default::default()
+						},
+						transform: Transform::from_xyz(-HEALTH_BAR_WIDTH / 2.0, 0.0, 1.0),
+						visibility: Visibility { is_visible: false },
+						..Default::default()
+					})
+					.insert(EnemyHealthBarFill);
+			})
+			.id();
+
+		commands.entity(tilemap.single()).add_child(bar);
+	}
+}
+
+pub fn update_enemy_health_bars(
+	mut commands: Commands,
+	enemies: Query<(&Transform, &Health), With<Enemy>>,
+	mut bars: Query<
+		(Entity, &EnemyHealthBar, &Children, &mut Transform, &mut Visibility),
+		Without<Enemy>,
+	>,
+	mut fills: Query<(&mut Sprite, &mut Visibility), (With<EnemyHealthBarFill>, Without<EnemyHealthBar>)>,
+) {
+	for (bar, health_bar, children, mut transform, mut visibility) in bars.iter_mut() {
+		let (enemy_transform, health) = match enemies.get(health_bar.enemy) {
+			Ok(enemy) => enemy,
+			Err(_) => {
+				// The enemy died
+				commands.entity(bar).despawn_recursive();
+				continue;
+			}
+		};
+
+		transform.translation = bar_position(enemy_transform);
+
+		// Full health bars are just clutter
+		let fraction = health.get_health() / health.get_max_health();
+		visibility.is_visible = fraction < 1.0;
+
+		for child in children.iter() {
+			if let Ok((mut sprite, mut fill_visibility)) = fills.get_mut(*child) {
+				sprite.custom_size = Some(Vec2::new(
+					HEALTH_BAR_WIDTH * fraction.max(0.0),
+					HEALTH_BAR_HEIGHT,
+				));
+				fill_visibility.is_visible = visibility.is_visible;
+			}
+		}
+	}
+}
+
+fn bar_position(enemy_transform: &Transform) -> Vec3 {
+	enemy_transform.translation.truncate().extend(70.0) + Vec3::Y * HEALTH_BAR_OFFSET
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::enemy::ENEMY_POINTS;
+use crate::loot::LootTable;
+use crate::status_effect::StatusEffect;
+use crate::unit::Armor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum EnemyType {
+	Grunt,
+	Fast,
+	Tank,
+}
+
+impl EnemyType {
+	/// Parses the "enemy_type" property of an enemy tile.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"grunt" => Some(Self::Grunt),
+			"fast" => Some(Self::Fast),
+			"tank" => Some(Self::Tank),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyStats {
+	pub health: f32,
+	pub speed: f32,
+	pub shooting_cooldown: f32,
+	pub points: u32,
+	/// Color multiplied with the sprite, to tell the kinds apart
+	pub tint: (f32, f32, f32),
+	#[serde(default)]
+	pub armor: Armor,
+	/// Applied to the player by the enemy's shots
+	#[serde(default)]
+	pub status_effect: Option<StatusEffect>,
+	/// From 0 to 1, how likely the enemy is to leave a pickup behind when it dies
+	#[serde(default)]
+	pub drop_chance: f32,
+	/// What that pickup is
+	#[serde(default)]
+	pub loot: LootTable,
+}
+
+impl EnemyStats {
+	pub fn color(&self) -> Color {
+		Color::rgb(self.tint.0, self.tint.1, self.tint.2)
+	}
+}
+
+impl Default for EnemyStats {
+	fn default() -> Self {
+		Self {
+			health: 30.0,
+			speed: 3.0,
+			shooting_cooldown: 1.0,
+			points: ENEMY_POINTS,
+			tint: (1.0, 1.0, 1.0),
+			armor: Armor::default(),
+			status_effect: None,
+			drop_chance: 0.0,
+			loot: LootTable::default(),
+		}
+	}
+}
+
+/// Stats of every kind of enemy, read from `assets/data/enemies.ron`
+pub struct EnemyTypes {
+	stats: HashMap<EnemyType, EnemyStats>,
+	fallback: EnemyStats,
+}
+
+impl EnemyTypes {
+	/// Stats of the given kind, or the default ones if the file doesn't define it.
+	pub fn get(&self, enemy_type: EnemyType) -> &EnemyStats {
+		self.stats.get(&enemy_type).unwrap_or(&self.fallback)
+	}
+}
+
+pub fn load_enemy_types() -> EnemyTypes {
+	// Baked into the binary, the same way as the level, so that it works on the web too
+	let source = include_str!("../assets/data/enemies.ron");
+
+	let stats = match ron::from_str::<HashMap<EnemyType, EnemyStats>>(source) {
+		Ok(stats) => stats,
+		Err(error) => {
+			error!("Failed to parse enemies.ron, every enemy will use the default stats: {}", error);
+			HashMap::new()
+		}
+	};
+
+	EnemyTypes {
+		stats,
+		fallback: EnemyStats::default(),
+	}
+}
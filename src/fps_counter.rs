@@ -0,0 +1,102 @@
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::fonts::RobotoFont;
+use crate::key_bindings::KeyBindings;
+
+/// Seconds between updates of the numbers, so that they can be read
+pub const FPS_COUNTER_UPDATE_INTERVAL: f32 = 0.25;
+
+pub struct FpsCounterPlugin;
+
+impl Plugin for FpsCounterPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_plugin(FrameTimeDiagnosticsPlugin)
+			.insert_resource(FpsCounterTimer(Timer::from_seconds(
+				FPS_COUNTER_UPDATE_INTERVAL,
+				true,
+			)))
+			.add_system(toggle_fps_counter)
+			.add_system(update_fps_counter);
+	}
+}
+
+#[derive(Component)]
+pub struct FpsCounter;
+
+#[derive(Deref, DerefMut)]
+struct FpsCounterTimer(Timer);
+
+/// Spawns the counter when it's off and despawns it when it's on, it's off by default
+fn toggle_fps_counter(
+	mut commands: Commands,
+	counter_query: Query<Entity, With<FpsCounter>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	font: Res<RobotoFont>,
+) {
+	if !keyboard.just_pressed(key_bindings.fps_counter) {
+		return;
+	}
+
+	if let Ok(counter) = counter_query.get_single() {
+		commands.entity(counter).despawn_recursive();
+		return;
+	}
+
+	commands
+		.spawn_bundle(
+			TextBundle::from_section(
+				"",
+				TextStyle {
+					font: font.0.clone(),
+					font_size: 20.0,
+					color: Color::WHITE,
+				},
+			)
+			.with_style(Style {
+				position_type: PositionType::Absolute,
+				position: UiRect {
+					left: Val::Px(10.0),
+					top: Val::Px(10.0),
+					..Default::default()
+				},
+				..Default::default()
+			}),
+		)
+		.insert(Name::new("FpsCounter"))
+		.insert(FpsCounter);
+}
+
+fn update_fps_counter(
+	mut counter_query: Query<&mut Text, With<FpsCounter>>,
+	diagnostics: Res<Diagnostics>,
+	mut timer: ResMut<FpsCounterTimer>,
+	// Real time, so that it keeps going while the game is paused
+	time: Res<Time>,
+) {
+	timer.tick(time.delta());
+
+	let mut text = match counter_query.get_single_mut() {
+		Ok(text) => text,
+		Err(_) => return,
+	};
+
+	// Freshly spawned counters are filled in right away instead of staying empty
+	if !timer.just_finished() && !text.sections[0].value.is_empty() {
+		return;
+	}
+
+	let fps = diagnostics
+		.get(FrameTimeDiagnosticsPlugin::FPS)
+		.and_then(|fps| fps.average());
+
+	let frame_time = diagnostics
+		.get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+		.and_then(|frame_time| frame_time.average());
+
+	text.sections[0].value = match (fps, frame_time) {
+		(Some(fps), Some(frame_time)) => format!("{:.0} FPS ({:.2} ms)", fps, frame_time * 1000.0),
+		_ => "- FPS".to_string(),
+	};
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::{GameState, fonts::{PaintFont, RobotoFont}, button::ColoredButton, stats::Stats};
+use crate::{GameState, fonts::{PaintFont, RobotoFont}, button::ColoredButton, stats::{HighScore, Score, Stats}};
 
 #[derive(Component)]
 struct GameOverUi;
@@ -32,7 +32,9 @@ impl Plugin for GameOverPlugin {
 	}
 }
 
-fn load_ui(mut commands: Commands, paint_font: Res<PaintFont>, roboto_font: Res<RobotoFont>, stats: Res<Stats>) {
+fn load_ui(mut commands: Commands, paint_font: Res<PaintFont>, roboto_font: Res<RobotoFont>, stats: Res<Stats>, score: Res<Score>, mut high_score: ResMut<HighScore>) {
+	let new_high_score = high_score.submit(&score);
+
 	commands
 		.spawn_bundle(NodeBundle {
 			style: Style {
@@ -99,6 +101,61 @@ fn load_ui(mut commands: Commands, paint_font: Res<PaintFont>, roboto_font: Res<
 				)
 				.insert(Name::new("Time"));
 
+			parent
+				.spawn_bundle(
+					TextBundle::from_section(
+						format!("and took {} of them with you", stats.enemies_killed),
+						TextStyle {
+							font: paint_font.0.clone(),
+							font_size: 32.0,
+							color: Color::WHITE,
+						},
+					)
+					.with_style(Style {
+						margin: UiRect::all(Val::Px(5.0)),
+						..default()
+					}),
+				)
+				.insert(Name::new("EnemiesKilled"));
+
+			parent
+				.spawn_bundle(
+					TextBundle::from_section(
+						format!("Score: {}", score.points),
+						TextStyle {
+							font: paint_font.0.clone(),
+							font_size: 32.0,
+							color: Color::WHITE,
+						},
+					)
+					.with_style(Style {
+						margin: UiRect::all(Val::Px(5.0)),
+						..default()
+					}),
+				)
+				.insert(Name::new("Score"));
+
+			parent
+				.spawn_bundle(
+					TextBundle::from_section(
+						if new_high_score {
+							"New High Score!".to_string()
+						} else {
+							format!("High Score: {}", high_score.points)
+						},
+						TextStyle {
+							font: paint_font.0.clone(),
+							font_size: 32.0,
+							color: if new_high_score { Color::PINK } else { Color::WHITE },
+						},
+					)
+					.with_style(Style {
+						margin: UiRect::all(Val::Px(5.0)),
+						..default()
+					}),
+				)
+				.insert(Name::new("HighScore"));
+
 			parent
 				.spawn_bundle(NodeBundle {
 					style: Style {
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::bullet::ShotEvent;
+use crate::player::Player;
+use crate::tilemap::{CullableTile, Tile};
+use crate::time::TimeCounter;
+use crate::TILE_SIZE;
+
+/// Damage of stepping onto spikes
+pub const SPIKE_DAMAGE: f32 = 15.0;
+pub const LAVA_DAMAGE_PER_SECOND: f32 = 30.0;
+/// Continuous damage is dealt in ticks this far apart, so that it doesn't shake the screen every frame
+pub const HAZARD_TICK_INTERVAL: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HazardMode {
+	/// Hurts for as long as the player stands on it
+	Continuous { damage_per_second: f32 },
+	/// Hurts once every time the player steps onto it
+	OnEntry { damage: f32 },
+}
+
+impl HazardMode {
+	/// Parses the "hazard" property of a floor tile.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"spikes" => Some(Self::OnEntry { damage: SPIKE_DAMAGE }),
+			"lava" => Some(Self::Continuous {
+				damage_per_second: LAVA_DAMAGE_PER_SECOND,
+			}),
+			_ => None,
+		}
+	}
+}
+
+/// Floor tile that hurts the player standing on it
+#[derive(Component)]
+pub struct Hazard {
+	pub mode: HazardMode,
+}
+
+#[derive(Bundle)]
+pub struct HazardBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	hazard: Hazard,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for HazardBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			hazard: Hazard {
+				mode: HazardMode::OnEntry { damage: SPIKE_DAMAGE },
+			},
+			cullable: CullableTile,
+			name: Name::new("Hazard"),
+		}
+	}
+}
+
+impl Tile for HazardBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				// Same height as the floor it replaces
+				transform: Transform::from_xyz(position.x, position.y, 0.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+impl HazardBundle {
+	pub fn with_mode(mut self, mode: HazardMode) -> Self {
+		self.hazard.mode = mode;
+		self
+	}
+}
+
+/// The damage goes through the shot events, so that the shield, the armor and the hurt sounds all apply
+pub fn damage_on_hazards(
+	hazards: Query<(Entity, &Transform, &Hazard)>,
+	player: Query<(Entity, &Transform), (With<Player>, Without<Hazard>)>,
+	mut shot_events: EventWriter<ShotEvent>,
+	time: Res<TimeCounter>,
+	mut standing_on: Local<Option<Entity>>,
+	// Shared by all the continuous hazards, so that walking across a pool of lava doesn't restart it every tile
+	mut next_tick: Local<Option<f32>>,
+) {
+	let (player_entity, player_transform) = player.single();
+	let player_position = player_transform.translation.truncate();
+
+	// The player stands on the tile under their center, so they're never on two at once
+	let current = hazards.iter().find(|(_, transform, _)| {
+		let offset = (player_position - transform.translation.truncate()).abs();
+		offset.x < TILE_SIZE / 2.0 && offset.y < TILE_SIZE / 2.0
+	});
+
+	let entered = current.map(|(entity, _, _)| entity) != *standing_on;
+	*standing_on = current.map(|(entity, _, _)| entity);
+
+	match current.map(|(_, _, hazard)| hazard.mode) {
+		Some(HazardMode::OnEntry { damage }) => {
+			*next_tick = None;
+
+			if entered {
+				shot_events.send(ShotEvent(player_entity, damage, None, false));
+			}
+		}
+		Some(HazardMode::Continuous { damage_per_second }) => {
+			// Stepping in burns right away, so running across quickly still hurts
+			let remaining = next_tick.unwrap_or(0.0) - time.delta_seconds();
+
+			if remaining <= 0.0 {
+				shot_events.send(ShotEvent(player_entity, damage_per_second * HAZARD_TICK_INTERVAL, None, false));
+				*next_tick = Some(remaining + HAZARD_TICK_INTERVAL);
+			} else {
+				*next_tick = Some(remaining);
+			}
+		}
+		None => *next_tick = None,
+	}
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::enemy_nav_mesh::EnemyNavMesh;
+use crate::enemy_type::EnemyTypes;
+use crate::key_bindings::KeyBindings;
+use crate::player::Player;
+use crate::pool::Pools;
+use crate::tilemap::{spawn_level, spawn_tilemap, LevelSource, MapBounds, TexturesMemo, Tilemap};
+use crate::win::{WinGoal, WinMaterial};
+use crate::GameState;
+
+/// Reloads the level while playing, for iterating on the map layout without restarting the game.
+/// Only built with the `hot_reload` feature.
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_system_set(SystemSet::on_update(GameState::Game).with_system(reload_level));
+	}
+}
+
+/// Throws the whole level away and spawns it again from the level file, keeping the player as they are
+fn reload_level(
+	mut commands: Commands,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	tilemap_query: Query<Entity, With<Tilemap>>,
+	player_query: Query<Entity, With<Player>>,
+	asset_server: Res<AssetServer>,
+	mut textures: ResMut<TexturesMemo>,
+	mut nav_mesh: ResMut<EnemyNavMesh>,
+	mut win_materials: ResMut<Assets<WinMaterial>>,
+	mut map_bounds: ResMut<MapBounds>,
+	mut win_goal: ResMut<WinGoal>,
+	enemy_types: Res<EnemyTypes>,
+	level_source: Res<LevelSource>,
+	mut pools: Pools,
+) {
+	if !keyboard.just_pressed(key_bindings.reload_level) {
+		return;
+	}
+
+	let tilemap = tilemap_query.single();
+	let player = player_query.single();
+
+	// Taken out of the old tilemap so that it doesn't get despawned along with it
+	commands.entity(tilemap).remove_children(&[player]);
+	commands.entity(tilemap).despawn_recursive();
+
+	// The pooled entities were children of the old tilemap
+	pools.clear();
+	nav_mesh.clear();
+
+	let mut entities = spawn_level(
+		&mut commands,
+		&asset_server,
+		&mut textures,
+		&mut nav_mesh,
+		&mut win_materials,
+		&mut map_bounds,
+		&mut win_goal,
+		&enemy_types,
+		*level_source,
+		false,
+	);
+
+	entities.push(player);
+
+	spawn_tilemap(&mut commands, &entities);
+
+	info!("Reloaded the level");
+}
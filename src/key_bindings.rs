@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+/// Keys used by the gameplay systems, so that they can be remapped
+pub struct KeyBindings {
+	pub up: KeyCode,
+	pub down: KeyCode,
+	pub left: KeyCode,
+	pub right: KeyCode,
+	pub dash: KeyCode,
+	pub sprint: KeyCode,
+	pub small_powerup: KeyCode,
+	pub big_powerup: KeyCode,
+	pub craft: KeyCode,
+	pub reload: KeyCode,
+	pub melee: KeyCode,
+	/// Slows the game down for a moment
+	pub bullet_time: KeyCode,
+	/// Opens and closes doors
+	pub interact: KeyCode,
+	/// Shows and hides the minimap
+	pub minimap: KeyCode,
+	/// Held to make the mouse wheel zoom instead of switching weapons
+	pub zoom: KeyCode,
+	pub reset_zoom: KeyCode,
+	pub damage_yourself: KeyCode,
+	/// Cycles the speed of the whole game, in debug builds
+	pub debug_time_scale: KeyCode,
+	/// Shows and hides the frame rate
+	pub fps_counter: KeyCode,
+	/// Reloads the level, with the `hot_reload` feature
+	pub reload_level: KeyCode,
+	/// Opens the tile editor from the main menu
+	pub open_editor: KeyCode,
+	/// Writes the tile editor's level to a file
+	pub export_level: KeyCode,
+}
+
+impl Default for KeyBindings {
+	fn default() -> Self {
+		Self {
+			up: KeyCode::W,
+			down: KeyCode::S,
+			left: KeyCode::A,
+			right: KeyCode::D,
+			dash: KeyCode::LShift,
+			sprint: KeyCode::LControl,
+			small_powerup: KeyCode::E,
+			big_powerup: KeyCode::Q,
+			craft: KeyCode::T,
+			reload: KeyCode::R,
+			melee: KeyCode::F,
+			bullet_time: KeyCode::C,
+			interact: KeyCode::G,
+			minimap: KeyCode::M,
+			zoom: KeyCode::LAlt,
+			reset_zoom: KeyCode::Z,
+			damage_yourself: KeyCode::Space,
+			debug_time_scale: KeyCode::F4,
+			fps_counter: KeyCode::F3,
+			reload_level: KeyCode::F5,
+			open_editor: KeyCode::F6,
+			export_level: KeyCode::F7,
+		}
+	}
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Size of the area the rooms are placed in, in tiles
+pub const LEVEL_WIDTH: i32 = 64;
+pub const LEVEL_HEIGHT: i32 = 48;
+pub const MAX_ROOMS: usize = 10;
+pub const ROOM_ATTEMPTS: usize = 60;
+pub const MIN_ROOM_SIZE: i32 = 5;
+pub const MAX_ROOM_SIZE: i32 = 11;
+pub const CORRIDOR_WIDTH: i32 = 2;
+pub const MAX_ENEMIES_PER_ROOM: usize = 3;
+
+/// Layout of a generated level, in tile coordinates with y going down like in Tiled
+pub struct GeneratedLevel {
+	pub floors: HashSet<IVec2>,
+	pub walls: HashSet<IVec2>,
+	pub player: IVec2,
+	pub enemies: Vec<IVec2>,
+	pub exit: IVec2,
+}
+
+struct Room {
+	min: IVec2,
+	/// Exclusive
+	max: IVec2,
+}
+
+impl Room {
+	fn center(&self) -> IVec2 {
+		(self.min + self.max) / 2
+	}
+
+	fn intersects(&self, other: &Room, margin: i32) -> bool {
+		self.min.x - margin < other.max.x
+			&& other.min.x - margin < self.max.x
+			&& self.min.y - margin < other.max.y
+			&& other.min.y - margin < self.max.y
+	}
+
+	fn random_cell(&self, rng: &mut StdRng) -> IVec2 {
+		// Keep away from the walls
+		IVec2::new(
+			rng.gen_range(self.min.x + 1..self.max.x - 1),
+			rng.gen_range(self.min.y + 1..self.max.y - 1),
+		)
+	}
+}
+
+/// Generates rooms connected by corridors. The same seed always gives the same level.
+///
+/// Every room is connected to the one placed before it, so the player can reach every enemy and the exit.
+pub fn generate_level(seed: u64) -> GeneratedLevel {
+	let mut rng = StdRng::seed_from_u64(seed);
+
+	let mut rooms: Vec<Room> = Vec::new();
+
+	for _ in 0..ROOM_ATTEMPTS {
+		if rooms.len() == MAX_ROOMS {
+			break;
+		}
+
+		let size = IVec2::new(
+			rng.gen_range(MIN_ROOM_SIZE..=MAX_ROOM_SIZE),
+			rng.gen_range(MIN_ROOM_SIZE..=MAX_ROOM_SIZE),
+		);
+		let min = IVec2::new(
+			rng.gen_range(1..LEVEL_WIDTH - size.x - 1),
+			rng.gen_range(1..LEVEL_HEIGHT - size.y - 1),
+		);
+		let room = Room { min, max: min + size };
+
+		// Leave space for the walls between the rooms
+		if !rooms.iter().any(|other| other.intersects(&room, 2)) {
+			rooms.push(room);
+		}
+	}
+
+	let mut floors = HashSet::new();
+
+	for room in rooms.iter() {
+		for x in room.min.x..room.max.x {
+			for y in room.min.y..room.max.y {
+				floors.insert(IVec2::new(x, y));
+			}
+		}
+	}
+
+	for pair in rooms.windows(2) {
+		carve_corridor(&mut floors, pair[0].center(), pair[1].center(), rng.gen());
+	}
+
+	// Wall off everything walkable, diagonals included so that the corners are closed
+	let mut walls = HashSet::new();
+
+	for floor in floors.iter() {
+		for x in -1..=1 {
+			for y in -1..=1 {
+				let neighbour = *floor + IVec2::new(x, y);
+
+				if !floors.contains(&neighbour) {
+					walls.insert(neighbour);
+				}
+			}
+		}
+	}
+
+	let first_room = &rooms[0];
+	let player = first_room.center();
+
+	// The exit goes as far away from the player as it can
+	let exit = rooms[1..]
+		.iter()
+		.map(Room::center)
+		.max_by_key(|center| (*center - player).as_vec2().length() as i32)
+		.unwrap_or(first_room.max - IVec2::splat(2));
+
+	let mut enemies = Vec::new();
+
+	for room in rooms[1..].iter() {
+		for _ in 0..rng.gen_range(1..=MAX_ENEMIES_PER_ROOM) {
+			let cell = room.random_cell(&mut rng);
+
+			if cell != exit && !enemies.contains(&cell) {
+				enemies.push(cell);
+			}
+		}
+	}
+
+	GeneratedLevel {
+		floors,
+		walls,
+		player,
+		enemies,
+		exit,
+	}
+}
+
+/// Connects the two points with an L shaped corridor.
+fn carve_corridor(floors: &mut HashSet<IVec2>, from: IVec2, to: IVec2, horizontal_first: bool) {
+	let corner = if horizontal_first {
+		IVec2::new(to.x, from.y)
+	} else {
+		IVec2::new(from.x, to.y)
+	};
+
+	for (start, end) in [(from, corner), (corner, to)] {
+		for x in start.x.min(end.x)..=start.x.max(end.x) {
+			for y in start.y.min(end.y)..=start.y.max(end.y) {
+				for offset in 0..CORRIDOR_WIDTH {
+					floors.insert(IVec2::new(x, y) + IVec2::splat(offset));
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Every floor tile that can be walked to from the start, without going diagonally
+	fn reachable_floors(level: &GeneratedLevel) -> HashSet<IVec2> {
+		let mut reached = HashSet::new();
+		let mut open = vec![level.player];
+
+		while let Some(cell) = open.pop() {
+			if !level.floors.contains(&cell) || !reached.insert(cell) {
+				continue;
+			}
+
+			for offset in [IVec2::X, -IVec2::X, IVec2::Y, -IVec2::Y] {
+				open.push(cell + offset);
+			}
+		}
+
+		reached
+	}
+
+	#[test]
+	fn same_seed_generates_the_same_level() {
+		for seed in [0, 1, 42, 1234567] {
+			let first = generate_level(seed);
+			let second = generate_level(seed);
+
+			assert_eq!(first.floors, second.floors, "Different floors for the seed {}", seed);
+			assert_eq!(first.walls, second.walls, "Different walls for the seed {}", seed);
+			assert_eq!(first.player, second.player, "Different start for the seed {}", seed);
+			assert_eq!(first.enemies, second.enemies, "Different enemies for the seed {}", seed);
+			assert_eq!(first.exit, second.exit, "Different exit for the seed {}", seed);
+		}
+	}
+
+	#[test]
+	fn player_can_reach_every_enemy_and_the_exit() {
+		for seed in 0..20 {
+			let level = generate_level(seed);
+			let reached = reachable_floors(&level);
+
+			assert!(reached.contains(&level.exit), "The exit can't be reached for the seed {}", seed);
+
+			for enemy in level.enemies.iter() {
+				assert!(reached.contains(enemy), "The enemy at {} can't be reached for the seed {}", enemy, seed);
+			}
+		}
+	}
+
+	#[test]
+	fn walls_never_cover_the_floor() {
+		for seed in 0..20 {
+			let level = generate_level(seed);
+
+			assert!(level.walls.is_disjoint(&level.floors), "Walls on the floor for the seed {}", seed);
+		}
+	}
+}
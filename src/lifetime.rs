@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+
+use crate::time::TimeCounter;
+use crate::GameState;
+
+/// Despawns the entity once the timer runs out, for short lived effects
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+	pub fn from_seconds(seconds: f32) -> Self {
+		Self(Timer::from_seconds(seconds, false))
+	}
+}
+
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+	fn build(&self, app: &mut App) {
+		app.add_system_set(SystemSet::on_update(GameState::Game).with_system(despawn_expired));
+	}
+}
+
+fn despawn_expired(
+	mut commands: Commands,
+	mut entities: Query<(Entity, &mut Lifetime)>,
+	time: Res<TimeCounter>,
+) {
+	for (entity, mut lifetime) in entities.iter_mut() {
+		if lifetime.0.tick(time.delta()).finished() {
+			commands.entity(entity).despawn_recursive();
+		}
+	}
+}
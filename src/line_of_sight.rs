@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::tilemap::TileCollider;
+
+/// # Returns
+/// True if no wall stands between the two points.
+/// Only walls are taken into account, units and bullets don't block the view.
+pub fn has_line_of_sight(
+	rapier_context: &RapierContext,
+	walls: &Query<(), With<TileCollider>>,
+	from: Vec2,
+	to: Vec2,
+) -> bool {
+	let offset = to - from;
+	let distance = offset.length();
+
+	if distance == 0.0 {
+		return true;
+	}
+
+	let is_wall = |entity| walls.get(entity).is_ok();
+
+	let filter = QueryFilter::default()
+		.exclude_sensors()
+		.predicate(&is_wall);
+
+	rapier_context
+		.cast_ray(from, offset / distance, distance, true, filter)
+		.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_app::{spawn_wall, step, test_app};
+	use crate::{GameState, TILE_SIZE};
+
+	use super::*;
+
+	/// The two points to look between, and what the last check said
+	struct Sight {
+		from: Vec2,
+		to: Vec2,
+		visible: Option<bool>,
+	}
+
+	fn check_sight(rapier_context: Res<RapierContext>, walls: Query<(), With<TileCollider>>, mut sight: ResMut<Sight>) {
+		sight.visible = Some(has_line_of_sight(&rapier_context, &walls, sight.from, sight.to));
+	}
+
+	fn sight_app() -> App {
+		let mut app = test_app();
+
+		app.insert_resource(Sight {
+			from: Vec2::ZERO,
+			to: Vec2::new(6.0 * TILE_SIZE, 0.0),
+			visible: None,
+		})
+		.add_system_set(SystemSet::on_update(GameState::Game).with_system(check_sight));
+
+		app
+	}
+
+	/// Two frames, the first one only gets the colliders into the physics
+	fn look(app: &mut App) -> bool {
+		step(app, 0.0);
+		step(app, 0.0);
+
+		app.world.resource::<Sight>().visible.unwrap()
+	}
+
+	#[test]
+	fn wall_in_between_blocks_the_sight() {
+		let mut app = sight_app();
+
+		spawn_wall(&mut app, Vec2::new(3.0 * TILE_SIZE, 0.0), Vec2::splat(TILE_SIZE / 2.0));
+
+		assert!(!look(&mut app));
+	}
+
+	#[test]
+	fn nothing_in_between_lets_the_sight_through() {
+		let mut app = sight_app();
+
+		// Off to the side of the line
+		spawn_wall(&mut app, Vec2::new(3.0 * TILE_SIZE, 2.0 * TILE_SIZE), Vec2::splat(TILE_SIZE / 2.0));
+
+		assert!(look(&mut app));
+	}
+
+	#[test]
+	fn wall_behind_the_target_doesnt_block_the_sight() {
+		let mut app = sight_app();
+
+		spawn_wall(&mut app, Vec2::new(8.0 * TILE_SIZE, 0.0), Vec2::splat(TILE_SIZE / 2.0));
+
+		assert!(look(&mut app));
+	}
+
+	#[test]
+	fn units_dont_block_the_sight() {
+		let mut app = sight_app();
+
+		// A collider that isn't a wall, like an enemy's
+		app.world
+			.spawn()
+			.insert_bundle(TransformBundle::from_transform(Transform::from_xyz(3.0 * TILE_SIZE, 0.0, 0.0)))
+			.insert(Collider::ball(TILE_SIZE / 2.0));
+
+		assert!(look(&mut app));
+	}
+}
@@ -0,0 +1,113 @@
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::pickup::{AmmoPickupBundle, HealthPickupBundle, WeaponPickupBundle};
+use crate::rarity::{generate_weapon, Rarity};
+use crate::rng::GameRng;
+use crate::tilemap::{TexturesMemo, Tile};
+
+pub const HEALTH_LOOT_TEXTURE: &str = "img/sugar.png";
+pub const AMMO_LOOT_TEXTURE: &str = "img/magazine_idk.png";
+pub const WEAPON_LOOT_TEXTURE: &str = "img/shells.png";
+
+/// Kind of pickup a loot table can hand out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum LootDrop {
+	Health,
+	Ammo,
+	Weapon,
+}
+
+/// Drops with their weights, each one comes up with a chance of its weight over the sum of all of them
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct LootTable {
+	pub entries: Vec<(LootDrop, u32)>,
+}
+
+impl LootTable {
+	pub fn new(entries: Vec<(LootDrop, u32)>) -> Self {
+		Self { entries }
+	}
+
+	/// # Returns
+	/// A drop picked by weight, `None` if the table is empty or every weight is zero.
+	pub fn roll(&self, rng: &mut impl Rng) -> Option<LootDrop> {
+		self.entries
+			.choose_weighted(rng, |(_, weight)| *weight)
+			.ok()
+			.map(|(drop, _)| *drop)
+	}
+}
+
+/// Spawns the pickup of the drop, the caller has to put it under the tilemap so that it goes away with the level.
+pub fn spawn_loot(
+	commands: &mut Commands,
+	drop: LootDrop,
+	position: Vec2,
+	textures: &mut TexturesMemo,
+	asset_server: &Res<AssetServer>,
+	rng: &mut impl Rng,
+) -> Entity {
+	match drop {
+		LootDrop::Health => commands
+			.spawn_bundle(HealthPickupBundle::spawn(
+				position,
+				textures.get(&PathBuf::from(HEALTH_LOOT_TEXTURE), asset_server),
+				false,
+				false,
+			))
+			.id(),
+		LootDrop::Ammo => commands
+			.spawn_bundle(AmmoPickupBundle::spawn(
+				position,
+				textures.get(&PathBuf::from(AMMO_LOOT_TEXTURE), asset_server),
+				false,
+				false,
+			))
+			.id(),
+		LootDrop::Weapon => commands
+			.spawn_bundle(
+				WeaponPickupBundle::spawn(
+					position,
+					textures.get(&PathBuf::from(WEAPON_LOOT_TEXTURE), asset_server),
+					false,
+					false,
+				)
+				.with_weapon(generate_weapon(Rarity::roll(rng), rng)),
+			)
+			.id(),
+	}
+}
+
+/// What dropping loot takes, as a single system parameter. The rolls are made on the game's generator,
+/// so that a replay drops the same loot.
+#[derive(SystemParam)]
+pub struct LootSpawner<'w, 's> {
+	rng: ResMut<'w, GameRng>,
+	textures: ResMut<'w, TexturesMemo>,
+	asset_server: Res<'w, AssetServer>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
+impl LootSpawner<'_, '_> {
+	/// Drops something from the table `drop_chance` of the time.
+	/// # Returns
+	/// The pickup, for the caller to put under the tilemap.
+	pub fn roll(&mut self, commands: &mut Commands, table: &LootTable, drop_chance: f32, position: Vec2) -> Option<Entity> {
+		if self.rng.gen::<f32>() >= drop_chance {
+			return None;
+		}
+
+		let drop = table.roll(&mut self.rng.0)?;
+
+		Some(spawn_loot(commands, drop, position, &mut self.textures, &self.asset_server, &mut self.rng.0))
+	}
+}
@@ -22,50 +22,98 @@ pub const TILE_SIZE: f32 = 50.0;
 mod unit;
 mod player;
 mod enemy;
+mod boss;
 mod cocaine;
 mod bullet;
 mod time;
 mod debug;
+mod fps_counter;
 mod tilemap;
+#[cfg(feature = "hot_reload")]
+mod hot_reload;
+mod destructible;
+mod editor;
+mod door;
+mod chest;
+mod hazard;
+mod terrain;
+mod teleporter;
+mod level_gen;
 mod win;
 mod fonts;
 mod button;
 mod post_processing;
+mod rng;
 mod main_menu;
 mod game_over;
 mod crosshair;
+mod enemy_health_bar;
 mod enemy_nav_mesh;
+mod enemy_type;
 mod audio;
 mod audio_player;
 mod music;
 mod stats;
+mod status_effect;
 mod settings;
+mod spatial_grid;
+mod key_bindings;
+mod save;
+mod pause;
+mod replay;
+mod pickup;
+mod loot;
+mod rarity;
+mod pool;
+mod lifetime;
+mod line_of_sight;
+mod particle;
+mod damage_number;
+mod wave;
+#[cfg(test)]
+mod test_app;
 
 use bullet::BulletPlugin;
 use button::ButtonPlugin;
 use crosshair::CrosshairPlugin;
 use enemy::EnemyPlugin;
+use boss::BossPlugin;
 use fonts::FontPlugin;
 use main_menu::MainMenuPlugin;
 use music::MusicPlugin;
 use player::PlayerPlugin;
 use debug::DebugPlugin;
+use fps_counter::FpsCounterPlugin;
 use post_processing::PostProcessingPlugin;
+use rng::RngPlugin;
 use settings::SettingsPlugin;
+use spatial_grid::SpatialGridPlugin;
 use tilemap::TileMapPlugin;
 use game_over::GameOverPlugin;
 use audio::AudioLoadPlugin;
 use win::WinPlugin;
 use stats::StatsPlugin;
+use status_effect::StatusEffectPlugin;
+use pause::PausePlugin;
+use replay::ReplayPlugin;
+use pickup::PickupPlugin;
+use pool::PoolPlugin;
+use lifetime::LifetimePlugin;
+use particle::ParticlePlugin;
+use damage_number::DamageNumberPlugin;
+use wave::WavePlugin;
+use editor::EditorPlugin;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
 pub enum GameState {
     MainMenu,
     Settings,
     Game,
+    Paused,
     GameOver,
     Win,
     Stats,
+    Editor,
 }
 
 #[cfg(not(target_arch="wasm32"))]
@@ -96,7 +144,9 @@ fn set_window_icon(
 fn set_window_icon(){}
 
 fn main() {
-    App::new()
+    let mut app = App::new();
+
+    app
         // States
         .add_state(GameState::MainMenu)
 
@@ -115,6 +165,7 @@ fn main() {
         // Plugins
         .add_plugins(DefaultPlugins)
         .add_plugin(TimePlugin)
+        .add_plugin(RngPlugin)
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
         .add_plugin(AudioPlugin)
         .add_plugin(MusicPlugin)
@@ -125,15 +176,31 @@ fn main() {
         .add_plugin(TileMapPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(EnemyPlugin)
+        .add_plugin(SpatialGridPlugin)
+        .add_plugin(BossPlugin)
         .add_plugin(BulletPlugin)
         .add_plugin(MainMenuPlugin)
         .add_plugin(GameOverPlugin)
         .add_plugin(WinPlugin)
         .add_plugin(CrosshairPlugin)
         .add_plugin(DebugPlugin)
+        .add_plugin(FpsCounterPlugin)
         .add_plugin(StatsPlugin)
+        .add_plugin(StatusEffectPlugin)
         .add_plugin(SettingsPlugin)
-        .add_plugin(DebugLinesPlugin::default())
-
-        .run();
+        .add_plugin(PausePlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(PickupPlugin)
+        .add_plugin(PoolPlugin)
+        .add_plugin(ParticlePlugin)
+        .add_plugin(LifetimePlugin)
+        .add_plugin(DamageNumberPlugin)
+        .add_plugin(WavePlugin)
+        .add_plugin(EditorPlugin)
+        .add_plugin(DebugLinesPlugin::default());
+
+    #[cfg(feature = "hot_reload")]
+    app.add_plugin(hot_reload::HotReloadPlugin);
+
+    app.run();
 }
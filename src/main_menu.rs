@@ -1,6 +1,6 @@
 use bevy::{app::AppExit, prelude::*};
 
-use crate::{button::ColoredButton, fonts::{PaintFont, RobotoFont}, GameState};
+use crate::{button::ColoredButton, fonts::{PaintFont, RobotoFont}, tilemap::LevelSource, GameState};
 
 #[derive(Component)]
 struct MainMenuUi;
@@ -8,6 +8,9 @@ struct MainMenuUi;
 #[derive(Component)]
 struct PlayButton;
 
+#[derive(Component)]
+struct RandomLevelButton;
+
 #[derive(Component)]
 struct SettingsButton;
 
@@ -23,6 +26,7 @@ impl Plugin for MainMenuPlugin {
 			.add_system_set(
 				SystemSet::on_update(GameState::MainMenu)
 					.with_system(play_button)
+					.with_system(random_level_button)
 					.with_system(exit_button)
 					.with_system(settings_button),
 			)
@@ -67,9 +71,9 @@ fn load_ui(mut commands: Commands, paint_font: Res<PaintFont>, roboto_font: Res<
 				.spawn_bundle(NodeBundle {
 					style: Style {
 						#[cfg(not(target_arch="wasm32"))]
-						size: Size::new(Val::Percent(50.0), Val::Px(200.0)),
+						size: Size::new(Val::Percent(50.0), Val::Px(270.0)),
 						#[cfg(target_arch="wasm32")]
-						size: Size::new(Val::Percent(50.0), Val::Px(135.0)),
+						size: Size::new(Val::Percent(50.0), Val::Px(200.0)),
 						justify_content: JustifyContent::SpaceBetween,
 						flex_direction: FlexDirection::ColumnReverse,
 						align_items: AlignItems::Center,
@@ -111,6 +115,31 @@ fn load_ui(mut commands: Commands, paint_font: Res<PaintFont>, roboto_font: Res<
 							));
 						});
 
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(300.0), Val::Px(50.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("RandomLevelButton"))
+						.insert(ColoredButton::default())
+						.insert(RandomLevelButton)
+						.with_children(|parent| {
+							parent.spawn_bundle(TextBundle::from_section(
+								"Random Level",
+								TextStyle {
+									font: roboto_font.0.clone(),
+									font_size: 32.0,
+									color: Color::BLACK,
+								},
+							));
+						});
+
 					parent
 						.spawn_bundle(ButtonBundle {
 							style: Style {
@@ -221,10 +250,27 @@ fn drop_ui(mut commands: Commands, ui: Query<Entity, With<MainMenuUi>>) {
 fn play_button(
 	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<PlayButton>)>,
 	mut state: ResMut<State<GameState>>,
+	mut level_source: ResMut<LevelSource>,
 ) {
 	for interaction in &mut interaction_query {
-		#[allow(clippy::collapsible_if)]
 		if *interaction == Interaction::Clicked {
+			*level_source = LevelSource::Tiled;
+
+			if state.set(GameState::Game).is_err() {}
+		}
+	}
+}
+
+fn random_level_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<RandomLevelButton>)>,
+	mut state: ResMut<State<GameState>>,
+	mut level_source: ResMut<LevelSource>,
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			// Playing again keeps the seed, so the same level can be retried
+			*level_source = LevelSource::Generated { seed: rand::random() };
+
 			if state.set(GameState::Game).is_err() {}
 		}
 	}
@@ -1,29 +1,113 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
-use bevy_kira_audio::{AudioSource, Audio};
+use bevy::utils::HashMap;
+use bevy_kira_audio::{AudioApp, AudioChannel, AudioSource};
+
+use crate::{audio_player::{AudioPlayer, MUSIC_VOLUME}, settings::Settings, GameState};
 
-use crate::{GameState, audio_player::{AudioPlayer, MUSIC_VOLUME}, settings::Settings};
+pub const MUSIC_CROSSFADE: f32 = 1.5;
 
 pub struct MusicPlugin;
 
 impl Plugin for MusicPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_startup_system(load_music)
-			.add_system_set(SystemSet::on_enter(GameState::Game).with_system(play_music))
-			.add_system_set(SystemSet::on_exit(GameState::Game).with_system(stop_music));
+		app.add_audio_channel::<MusicChannel>()
+			.insert_resource(CurrentTrack(None))
+			.add_startup_system(load_music)
+			.add_system(switch_music)
+			.add_system(update_music_volume);
 	}
 }
 
-#[derive(Deref, DerefMut)]
-struct Music(Handle<AudioSource>);
+/// Audio channel the background music is played on, separate from the sound effects
+pub struct MusicChannel;
+
+#[derive(Clone, PartialEq)]
+pub struct MusicTrack {
+	pub source: Handle<AudioSource>,
+	/// Where the song jumps back to once it ends, in seconds
+	pub loop_start: f64,
+}
+
+/// Which track plays in which state. States without a track fade the music out.
+pub struct MusicTracks {
+	pub tracks: HashMap<GameState, MusicTrack>,
+	pub crossfade: Duration,
+}
+
+struct CurrentTrack(Option<MusicTrack>);
 
 fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
-	commands.insert_resource(Music(asset_server.load("./audio/song.wav")));
+	let song = MusicTrack {
+		source: asset_server.load("./audio/song.wav"),
+		loop_start: 9.54,
+	};
+
+	let mut tracks = HashMap::new();
+
+	for state in [
+		GameState::MainMenu,
+		GameState::Settings,
+		GameState::Game,
+		GameState::Paused,
+		GameState::Win,
+		GameState::Stats,
+	] {
+		tracks.insert(state, song.clone());
+	}
+
+	commands.insert_resource(MusicTracks {
+		tracks,
+		crossfade: Duration::from_secs_f32(MUSIC_CROSSFADE),
+	});
 }
 
-fn play_music(audio: Res<Audio>, music: Res<Music>, settings: Res<Settings>) {
-	AudioPlayer::play_music(audio.into_inner(), music.clone(), MUSIC_VOLUME, &settings, 9.54);
+fn switch_music(
+	state: Res<State<GameState>>,
+	tracks: Option<Res<MusicTracks>>,
+	mut current_track: ResMut<CurrentTrack>,
+	channel: Res<AudioChannel<MusicChannel>>,
+	settings: Res<Settings>,
+) {
+	let tracks = match tracks {
+		Some(tracks) => tracks,
+		None => return,
+	};
+
+	// Also runs on the first frame the tracks are there, to start the menu music
+	if !state.is_changed() && !tracks.is_added() {
+		return;
+	}
+
+	let track = tracks.tracks.get(state.current());
+
+	// Moving between states sharing the same song doesn't restart it
+	if track == current_track.0.as_ref() {
+		return;
+	}
+
+	if current_track.0.is_some() {
+		AudioPlayer::stop_music(channel.as_ref(), tracks.crossfade);
+	}
+
+	if let Some(track) = track {
+		AudioPlayer::play_music(
+			channel.as_ref(),
+			track.source.clone(),
+			MUSIC_VOLUME,
+			&settings,
+			track.loop_start,
+			tracks.crossfade,
+		);
+	}
+
+	current_track.0 = track.cloned();
 }
 
-fn stop_music(audio: Res<Audio>) {
-	AudioPlayer::stop(audio.into_inner());
+fn update_music_volume(channel: Res<AudioChannel<MusicChannel>>, settings: Res<Settings>) {
+	// Changing the channel volume keeps the song going instead of restarting it
+	if settings.is_changed() {
+		AudioPlayer::set_music_volume(channel.into_inner(), MUSIC_VOLUME, &settings);
+	}
 }
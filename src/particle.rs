@@ -0,0 +1,128 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::random;
+
+use crate::pool::Pool;
+use crate::settings::Settings;
+use crate::time::TimeCounter;
+use crate::GameState;
+
+pub const DEATH_BURST_PARTICLES: u32 = 16;
+/// Used instead with the reduce motion setting on
+pub const REDUCED_DEATH_BURST_PARTICLES: u32 = 4;
+pub const PARTICLE_SIZE: f32 = 6.0;
+pub const PARTICLE_LIFETIME: f32 = 0.6;
+pub const PARTICLE_MIN_SPEED: f32 = 100.0;
+pub const PARTICLE_MAX_SPEED: f32 = 350.0;
+/// How quickly the particles slow down
+pub const PARTICLE_DAMPING: f32 = 4.0;
+
+/// How many particles the effects spawn, lower it if there is a performance problem
+pub struct ParticleSettings {
+	pub death_burst_count: u32,
+}
+
+impl Default for ParticleSettings {
+	fn default() -> Self {
+		Self {
+			death_burst_count: DEATH_BURST_PARTICLES,
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct Particle {
+	pub velocity: Vec2,
+	pub lifetime: Timer,
+}
+
+#[derive(Bundle)]
+struct ParticleBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	particle: Particle,
+}
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(ParticleSettings::default())
+			.add_system(apply_reduce_motion)
+			.add_system_set(SystemSet::on_update(GameState::Game).with_system(particle_update));
+	}
+}
+
+/// Spawns particles flying out of the position in every direction.
+/// New particles are parented to `parent`, the level, so that they get despawned with it.
+pub fn spawn_burst(
+	commands: &mut Commands,
+	pool: &mut Pool<Particle>,
+	parent: Entity,
+	position: Vec3,
+	color: Color,
+	count: u32,
+) {
+	for _ in 0..count {
+		let angle = random::<f32>() * 2.0 * PI;
+		let direction = Vec2::new(angle.cos(), angle.sin());
+		let speed = PARTICLE_MIN_SPEED + random::<f32>() * (PARTICLE_MAX_SPEED - PARTICLE_MIN_SPEED);
+
+		pool.spawn(
+			commands,
+			parent,
+			ParticleBundle {
+				sprite_bundle: SpriteBundle {
+					sprite: Sprite {
+						color,
+						custom_size: Some(Vec2::splat(PARTICLE_SIZE)),
+						..Default::default()
+					},
+					transform: Transform::from_translation(position.truncate().extend(50.0)),
+					..Default::default()
+				},
+				particle: Particle {
+					velocity: direction * speed,
+					lifetime: Timer::from_seconds(PARTICLE_LIFETIME, false),
+				},
+			},
+		);
+	}
+}
+
+fn apply_reduce_motion(settings: Res<Settings>, mut particle_settings: ResMut<ParticleSettings>) {
+	if !settings.is_changed() {
+		return;
+	}
+
+	particle_settings.death_burst_count = if settings.reduce_motion {
+		REDUCED_DEATH_BURST_PARTICLES
+	} else {
+		DEATH_BURST_PARTICLES
+	};
+}
+
+fn particle_update(
+	mut commands: Commands,
+	mut particles: Query<(Entity, &mut Transform, &mut Sprite, &mut Particle)>,
+	mut pool: ResMut<Pool<Particle>>,
+	time: Res<TimeCounter>,
+) {
+	for (entity, mut transform, mut sprite, mut particle) in particles.iter_mut() {
+		particle.lifetime.tick(time.delta());
+
+		if particle.lifetime.finished() {
+			pool.release(&mut commands, entity);
+			continue;
+		}
+
+		transform.translation += (particle.velocity * time.delta_seconds()).extend(0.0);
+
+		// Framerate independent slowdown
+		let damping = (-PARTICLE_DAMPING * time.delta_seconds()).exp();
+		particle.velocity *= damping;
+
+		sprite.color.set_a(1.0 - particle.lifetime.percent());
+	}
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::{GameState, fonts::{PaintFont, RobotoFont}, button::ColoredButton};
+
+#[derive(Component)]
+struct PauseUi;
+
+#[derive(Component)]
+struct ResumeButton;
+
+#[derive(Component)]
+struct MainMenuButton;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+	fn build(&self, app: &mut App) {
+		// Paused is pushed on top of Game, so the level stays loaded
+		// and none of the gameplay systems (or their timers) run in the meantime
+		app
+			.add_system_set(
+				SystemSet::on_update(GameState::Game)
+					.with_system(pause_game)
+			)
+			.add_system_set(
+				SystemSet::on_enter(GameState::Paused)
+					.with_system(load_ui)
+					.with_system(show_cursor)
+			)
+			.add_system_set(
+				SystemSet::on_update(GameState::Paused)
+					.with_system(resume_button)
+					.with_system(main_menu_button)
+			)
+			.add_system_set(
+				SystemSet::on_exit(GameState::Paused)
+					.with_system(drop_ui)
+			)
+			.add_system_set(
+				SystemSet::on_resume(GameState::Game)
+					.with_system(hide_cursor)
+			);
+	}
+}
+
+fn pause_game(mut keyboard: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+	if keyboard.just_pressed(KeyCode::Escape) {
+		// The paused state is updated the same frame, don't let it unpause right away
+		keyboard.clear_just_pressed(KeyCode::Escape);
+
+		state.push(GameState::Paused).expect("Failed to change state!");
+	}
+}
+
+fn show_cursor(mut windows: ResMut<Windows>) {
+	if let Some(window) = windows.get_primary_mut() {
+		window.set_cursor_visibility(true);
+	}
+}
+
+fn hide_cursor(mut windows: ResMut<Windows>) {
+	if let Some(window) = windows.get_primary_mut() {
+		window.set_cursor_visibility(false);
+	}
+}
+
+fn load_ui(mut commands: Commands, paint_font: Res<PaintFont>, roboto_font: Res<RobotoFont>) {
+	commands
+		.spawn_bundle(NodeBundle {
+			style: Style {
+				size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				flex_direction: FlexDirection::ColumnReverse,
+				..Default::default()
+			},
+			// Keep the game visible under the menu
+			color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+			..Default::default()
+		})
+		.insert(PauseUi)
+		.insert(Name::new("Ui"))
+		.with_children(|parent| {
+			parent
+				.spawn_bundle(
+					TextBundle::from_section(
+						"Paused",
+						TextStyle {
+							font: paint_font.0.clone(),
+							font_size: 152.0,
+							color: Color::WHITE,
+						},
+					)
+					.with_style(Style {
+						margin: UiRect::all(Val::Px(5.0)),
+						..default()
+					}),
+				)
+				.insert(Name::new("Title"));
+
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(50.0), Val::Px(50.0)),
+						justify_content: JustifyContent::SpaceBetween,
+						margin: UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Px(100.0), Val::Px(0.0)),
+						..Default::default()
+					},
+					color: Color::NONE.into(),
+					..Default::default()
+				})
+				.insert(Name::new("ButtonsContainer"))
+				.with_children(|parent| {
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(300.0), Val::Percent(100.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							button: Button,
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("ResumeButton"))
+						.insert(ColoredButton::default())
+						.insert(ResumeButton)
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(TextBundle::from_section(
+									"Resume",
+									TextStyle {
+										font: roboto_font.0.clone(),
+										font_size: 32.0,
+										color: Color::BLACK
+									}
+								));
+						});
+
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(300.0), Val::Percent(100.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							button: Button,
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("MainMenuButton"))
+						.insert(MainMenuButton)
+						.insert(ColoredButton::default())
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(TextBundle::from_section(
+									"Quit to Menu",
+									TextStyle {
+										font: roboto_font.0.clone(),
+										font_size: 32.0,
+										color: Color::BLACK
+									}
+								));
+						});
+				});
+		});
+}
+
+fn drop_ui(mut commands: Commands, ui: Query<Entity, With<PauseUi>>) {
+	let ui = ui.single();
+	commands.entity(ui).despawn_recursive();
+}
+
+fn resume_button(
+	mut interaction_query: Query<
+		&Interaction,
+		(Changed<Interaction>, With<ResumeButton>)
+	>,
+	mut keyboard: ResMut<Input<KeyCode>>,
+	mut state: ResMut<State<GameState>>
+) {
+	let clicked = interaction_query.iter_mut().any(|interaction| *interaction == Interaction::Clicked);
+
+	if clicked || keyboard.just_pressed(KeyCode::Escape) {
+		// Same as when pausing, so Game doesn't pause again this frame
+		keyboard.clear_just_pressed(KeyCode::Escape);
+
+		state.pop().expect("Failed to change state!");
+	}
+}
+
+fn main_menu_button(
+	mut interaction_query: Query<
+		&Interaction,
+		(Changed<Interaction>, With<MainMenuButton>)
+	>,
+	mut state: ResMut<State<GameState>>
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			// Replacing the whole stack also exits Game, which cleans up the level
+			state.replace(GameState::MainMenu).expect("Failed to change state!");
+		}
+	}
+}
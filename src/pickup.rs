@@ -0,0 +1,334 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use rand::seq::SliceRandom;
+
+use crate::audio::{CraftingSound, SnortingSounds};
+use crate::audio_player::{AudioPlayer, PICKUP_VOLUME};
+use crate::damage_number::{DamageNumber, DAMAGE_NUMBER_FONT_SIZE};
+use crate::door::KeyColor;
+use crate::fonts::RobotoFont;
+use crate::player::{pistol, Player, PLAYER_RADIUS};
+use crate::settings::Settings;
+use crate::tilemap::{Tile, Tilemap};
+use crate::unit::{Health, Inventory, Shooting, Weapon};
+use crate::{GameState, TILE_SIZE};
+
+/// Size of the square the player has to touch to pick something up
+pub const PICKUP_SIZE: f32 = TILE_SIZE / 2.0;
+pub const HEALTH_PICKUP_AMOUNT: f32 = 25.0;
+/// Full magazines an ammo pickup adds to every weapon's reserve
+pub const AMMO_PICKUP_MAGAZINES: u32 = 2;
+/// Longer than a damage number stays up, to give time to read it
+pub const WEAPON_NAME_LIFETIME: f32 = 2.0;
+
+#[derive(Component)]
+pub struct HealthPickup {
+	pub amount: f32,
+}
+
+impl Default for HealthPickup {
+	fn default() -> Self {
+		Self {
+			amount: HEALTH_PICKUP_AMOUNT,
+		}
+	}
+}
+
+#[derive(Bundle, Default)]
+pub struct HealthPickupBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	health_pickup: HealthPickup,
+}
+
+impl Tile for HealthPickupBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_translation(position.extend(25.0)),
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				texture,
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct AmmoPickup {
+	pub magazines: u32,
+}
+
+impl Default for AmmoPickup {
+	fn default() -> Self {
+		Self {
+			magazines: AMMO_PICKUP_MAGAZINES,
+		}
+	}
+}
+
+#[derive(Bundle, Default)]
+pub struct AmmoPickupBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	ammo_pickup: AmmoPickup,
+}
+
+impl Tile for AmmoPickupBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_translation(position.extend(25.0)),
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				texture,
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+/// Opens one locked door of the same color
+#[derive(Component)]
+pub struct KeyPickup {
+	pub color: KeyColor,
+}
+
+impl Default for KeyPickup {
+	fn default() -> Self {
+		Self { color: KeyColor::Red }
+	}
+}
+
+#[derive(Bundle, Default)]
+pub struct KeyPickupBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	key_pickup: KeyPickup,
+}
+
+impl Tile for KeyPickupBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_translation(position.extend(25.0)),
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					color: KeyColor::Red.color(),
+					..Default::default()
+				},
+				texture,
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+impl KeyPickupBundle {
+	pub fn with_color(mut self, color: KeyColor) -> Self {
+		self.key_pickup.color = color;
+		self.sprite_bundle.sprite.color = color.color();
+		self
+	}
+}
+
+/// Gives the player its weapon, in a slot of its own
+#[derive(Component)]
+pub struct WeaponPickup {
+	pub weapon: Weapon,
+}
+
+impl Default for WeaponPickup {
+	fn default() -> Self {
+		Self { weapon: pistol() }
+	}
+}
+
+#[derive(Bundle, Default)]
+pub struct WeaponPickupBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	weapon_pickup: WeaponPickup,
+}
+
+impl Tile for WeaponPickupBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_translation(position.extend(25.0)),
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				texture,
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+impl WeaponPickupBundle {
+	pub fn with_weapon(mut self, weapon: Weapon) -> Self {
+		self.sprite_bundle.sprite.color = weapon.rarity.color();
+		self.weapon_pickup.weapon = weapon;
+		self
+	}
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_system_set(
+			SystemSet::on_update(GameState::Game)
+				.with_system(pickup_collection),
+		);
+	}
+}
+
+/// # Returns
+/// True if the player's bounding box overlaps the pickup's.
+fn touches_pickup(player_position: Vec2, pickup_position: Vec2) -> bool {
+	let distance = (player_position - pickup_position).abs();
+	let reach = PLAYER_RADIUS + PICKUP_SIZE / 2.0;
+
+	distance.x < reach && distance.y < reach
+}
+
+fn pickup_collection(
+	mut commands: Commands,
+	mut player_query: Query<(&mut Health, &mut Shooting, &mut Inventory, &Transform), With<Player>>,
+	health_pickups: Query<(Entity, &Transform, &HealthPickup)>,
+	ammo_pickups: Query<(Entity, &Transform, &AmmoPickup)>,
+	key_pickups: Query<(Entity, &Transform, &KeyPickup)>,
+	weapon_pickups: Query<(Entity, &Transform, &WeaponPickup)>,
+	audio: Res<Audio>,
+	settings: Res<Settings>,
+	snorting_sounds: Res<SnortingSounds>,
+	crafting_sound: Res<CraftingSound>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	font: Res<RobotoFont>,
+) {
+	let (mut health, mut shooting, mut inventory, player_transform) = player_query.single_mut();
+	let player_position = player_transform.translation.truncate();
+
+	for (entity, transform, pickup) in health_pickups.iter() {
+		// Leave it lying around for later if there's nothing to heal
+		if health.get_health() >= health.get_max_health() {
+			break;
+		}
+
+		if touches_pickup(player_position, transform.translation.truncate()) {
+			health.heal(pickup.amount);
+
+			AudioPlayer::play_sfx(
+				audio.as_ref(),
+				snorting_sounds
+					.0
+					.choose(&mut rand::thread_rng())
+					.expect("No snorting sounds!")
+					.clone(),
+				PICKUP_VOLUME,
+				settings.as_ref(),
+			);
+
+			commands.entity(entity).despawn_recursive();
+		}
+	}
+
+	for (entity, transform, pickup) in ammo_pickups.iter() {
+		if !touches_pickup(player_position, transform.translation.truncate()) {
+			continue;
+		}
+
+		let mut picked_up = false;
+
+		for weapon in shooting.weapons_mut() {
+			picked_up |= weapon.add_reserve(pickup.magazines * weapon.magazine_size);
+		}
+
+		// Every reserve is full, so it stays there for later
+		if picked_up {
+			AudioPlayer::play_sfx(
+				audio.as_ref(),
+				crafting_sound.0.clone(),
+				PICKUP_VOLUME,
+				settings.as_ref(),
+			);
+
+			commands.entity(entity).despawn_recursive();
+		}
+	}
+
+	for (entity, transform, pickup) in key_pickups.iter() {
+		if !touches_pickup(player_position, transform.translation.truncate()) {
+			continue;
+		}
+
+		inventory.add_key(pickup.color);
+
+		AudioPlayer::play_sfx(
+			audio.as_ref(),
+			crafting_sound.0.clone(),
+			PICKUP_VOLUME,
+			settings.as_ref(),
+		);
+
+		commands.entity(entity).despawn_recursive();
+	}
+
+	for (entity, transform, pickup) in weapon_pickups.iter() {
+		if !touches_pickup(player_position, transform.translation.truncate()) {
+			continue;
+		}
+
+		shooting.add_weapon(pickup.weapon.clone());
+
+		// Floats up like a damage number, in the color of the rarity
+		let name = commands
+			.spawn_bundle(Text2dBundle {
+				text: Text::from_section(
+					pickup.weapon.display_name(),
+					TextStyle {
+						font: font.0.clone(),
+						font_size: DAMAGE_NUMBER_FONT_SIZE,
+						color: pickup.weapon.rarity.color(),
+					},
+				)
+				.with_alignment(TextAlignment::CENTER),
+				transform: Transform::from_translation(
+					(player_position + Vec2::new(0.0, TILE_SIZE)).extend(80.0),
+				),
+				..Default::default()
+			})
+			.insert(Name::new("WeaponName"))
+			.insert(DamageNumber {
+				lifetime: Timer::from_seconds(WEAPON_NAME_LIFETIME, false),
+			})
+			.id();
+
+		commands.entity(tilemap.single()).add_child(name);
+
+		AudioPlayer::play_sfx(
+			audio.as_ref(),
+			crafting_sound.0.clone(),
+			PICKUP_VOLUME,
+			settings.as_ref(),
+		);
+
+		commands.entity(entity).despawn_recursive();
+	}
+}
@@ -1,5 +1,7 @@
-use std::time::Duration;
+use std::marker::PhantomData;
 
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 
 use bevy_rapier2d::prelude::*;
@@ -10,77 +12,260 @@ use rand::prelude::*;
 
 use rand::seq::SliceRandom;
 
-use crate::audio::{CraftingSound, FootstepSounds, ShotgunSound, SnortingSounds};
+use crate::audio::{
+	CraftingSound, FootstepSounds, OverheatSound, PitchedSfxChannel, PlayerDeathSound, PlayerHurtSounds,
+	ShieldBreakSound, ShotgunSound, SnortingSounds,
+};
 use crate::audio_player::{
-	AudioPlayer, PLAYER_FOOTSTEP_VOLUME, PLAYER_SHOT_VOLUME, PLAYER_SNORTING_VOLUME, PLAYER_CRAFTING_VOLUME,
+	AudioPlayer, OVERHEAT_VOLUME, PLAYER_DEATH_VOLUME, PLAYER_FOOTSTEP_VOLUME, PLAYER_HURT_VOLUME, PLAYER_SHOT_VOLUME,
+	PLAYER_SNORTING_VOLUME, PLAYER_CRAFTING_VOLUME, SHIELD_BREAK_VOLUME,
 };
-use crate::bullet::{Bullet, BulletBundle, BulletTexture, ShotEvent};
+use crate::bullet::{Bullet, BulletSpawner, ImpactBundle, KnockbackEvent, ShotEvent};
 use crate::cocaine::Cocaine;
+use crate::damage_number::DamageEvent;
 use crate::enemy::Enemy;
+use crate::key_bindings::KeyBindings;
+use crate::rng::GameRng;
 use crate::post_processing::{
 	update_post_processing_effects, CameraRenderImage, DefaultMaterial, MainCamera,
 	PostProcessingLayer, ScreenRes,
 };
 use crate::settings::Settings;
 use crate::stats::Stats;
-use crate::tilemap::{Tile, Tilemap};
+use crate::status_effect::StatusEffects;
+use crate::terrain::{ice_grip, slow_factor, IceTile, SlowTile};
+use crate::tilemap::{Tile, TileCollider, Tilemap};
 use crate::time::TimeCounter;
-use crate::unit::{Health, Inventory, Movement, ShootEvent, Shooting};
-use crate::win::Win;
-use crate::HEIGHT;
-use crate::WIDTH;
+use crate::unit::{Armor, FireMode, Health, Inventory, Movement, Overheat, ShootEvent, ShotMode, Shooting, Stamina, Weapon};
+use crate::wave::WaveSpawner;
+use crate::win::{Win, WinGoal};
 use crate::{GameState, TILE_SIZE};
 
+mod bullet_time;
+mod camera;
+mod dash;
 mod effect;
+mod melee;
+mod minimap;
+mod muzzle_flash;
 mod post_processing;
+mod regen;
+mod screen_shake;
+mod shield;
+mod sprint;
 mod ui;
 
-use ui::{drop_ui, ui_setup, update_ui};
+use ui::{
+	drop_ui, ui_setup, update_ability_ui, update_health_bar, update_key_ui, update_low_health_vignette, update_shield_ui, update_ui,
+	update_wave_ui,
+};
 
+pub use self::camera::{cursor_to_world, view_half_size};
+use self::bullet_time::{end_bullet_time, player_bullet_time, BulletTime};
+use self::camera::{camera_follow, camera_zoom, reset_camera, CameraFocus, CameraSettings};
+use self::dash::{player_dash, Dash};
 use self::effect::{BigPowerup, EffectData, SmallPowerup};
+use self::melee::{player_melee, Melee};
+use self::minimap::{build_minimap, spawn_minimap_dots, toggle_minimap, update_minimap_dots};
+use self::muzzle_flash::MuzzleFlashBundle;
 use self::post_processing::{
 	clean_post_processing, BigPowerupMaterial, PlayerPostProcessingPlugin, SmallPowerupMaterial,
 };
+use self::regen::{player_regen, Regen};
+use self::screen_shake::{
+	decay_screen_shake, reset_screen_shake, ScreenShake, DAMAGE_TRAUMA, SHOT_TRAUMA,
+};
+use self::shield::{shield_regen, Shield};
+use self::sprint::{player_sprint, regenerate_stamina, Sprint};
 
 pub const WEAPON_COOLDOWN: f32 = 0.5;
+pub const WEAPON_RANGE: f32 = 20.0 * TILE_SIZE;
+pub const WEAPON_DAMAGE: f32 = 30.0;
+pub const WEAPON_KNOCKBACK: f32 = 150.0;
+pub const WEAPON_PELLET_COUNT: u32 = 4;
+pub const WEAPON_SPREAD_ANGLE: f32 = 0.06;
+pub const WEAPON_CRIT_CHANCE: f32 = 0.1;
+/// Past this the shotgun pellets start losing damage
+pub const WEAPON_EFFECTIVE_RANGE: f32 = 6.0 * TILE_SIZE;
+/// Share of the damage left at `WEAPON_RANGE`
+pub const WEAPON_MIN_DAMAGE_FRACTION: f32 = 0.3;
+pub const MAGAZINE_SIZE: u32 = 6;
+pub const RELOAD_TIME: f32 = 1.5;
+pub const MAX_RESERVE: u32 = 4 * MAGAZINE_SIZE;
+pub const PISTOL_COOLDOWN: f32 = 0.25;
+pub const PISTOL_RANGE: f32 = 15.0 * TILE_SIZE;
+pub const PISTOL_DAMAGE: f32 = 20.0;
+pub const PISTOL_MAGAZINE_SIZE: u32 = 12;
+pub const PISTOL_RELOAD_TIME: f32 = 1.0;
+pub const PISTOL_MAX_RESERVE: u32 = 4 * PISTOL_MAGAZINE_SIZE;
+pub const PISTOL_KNOCKBACK: f32 = 100.0;
+pub const PISTOL_CRIT_CHANCE: f32 = 0.15;
+pub const PISTOL_EFFECTIVE_RANGE: f32 = 10.0 * TILE_SIZE;
+pub const PISTOL_MIN_DAMAGE_FRACTION: f32 = 0.6;
+/// Enemies a pistol shot goes through
+pub const PISTOL_PIERCE_COUNT: u32 = 2;
+pub const BURST_PISTOL_SHOTS: u32 = 3;
+/// Seconds between the shots of a burst
+pub const BURST_PISTOL_INTERVAL: f32 = 0.08;
+pub const BLASTER_COOLDOWN: f32 = 0.15;
+pub const BLASTER_RANGE: f32 = 18.0 * TILE_SIZE;
+pub const BLASTER_DAMAGE: f32 = 12.0;
+pub const BLASTER_KNOCKBACK: f32 = 40.0;
+/// Share of the heat limit every blaster shot adds, so it overheats on the eighth shot in a row
+pub const BLASTER_HEAT_PER_SHOT: f32 = 0.13;
+/// Heat lost every second once the blaster stops firing
+pub const BLASTER_DISSIPATION: f32 = 0.6;
+pub const BLASTER_DISSIPATION_DELAY: f32 = 0.3;
+/// Seconds an overheated blaster can't fire for
+pub const BLASTER_LOCKOUT: f32 = 2.0;
+pub const RAILGUN_COOLDOWN: f32 = 0.6;
+pub const RAILGUN_RANGE: f32 = 30.0 * TILE_SIZE;
+/// Damage of a full charge is this times `RAILGUN_MAX_CHARGE_MULTIPLIER`
+pub const RAILGUN_DAMAGE: f32 = 25.0;
+pub const RAILGUN_MAGAZINE_SIZE: u32 = 4;
+pub const RAILGUN_RELOAD_TIME: f32 = 2.0;
+pub const RAILGUN_MAX_RESERVE: u32 = 3 * RAILGUN_MAGAZINE_SIZE;
+pub const RAILGUN_KNOCKBACK: f32 = 250.0;
+pub const RAILGUN_PIERCE_COUNT: u32 = 5;
+/// Seconds the railgun takes to charge up fully
+pub const RAILGUN_CHARGE_TIME: f32 = 1.2;
+/// A shot let go right away still does this share of the damage
+pub const RAILGUN_MIN_CHARGE_MULTIPLIER: f32 = 0.3;
+pub const RAILGUN_MAX_CHARGE_MULTIPLIER: f32 = 3.0;
+pub const CRIT_MULTIPLIER: f32 = 2.0;
+/// Keys selecting the weapon slots, in order
+const WEAPON_SLOT_KEYS: [KeyCode; 9] = [
+	KeyCode::Key1,
+	KeyCode::Key2,
+	KeyCode::Key3,
+	KeyCode::Key4,
+	KeyCode::Key5,
+	KeyCode::Key6,
+	KeyCode::Key7,
+	KeyCode::Key8,
+	KeyCode::Key9,
+];
 pub const SMALL_POWERUP_DURATION: f32 = 5.0;
 pub const BIG_POWERUP_DURATION: f32 = 5.0 / 3.0;
+/// Distance kept between the player and the wall they're sliding along
+const COLLISION_SKIN: f32 = 0.1;
+/// Game time covered by one step of the player's movement, a fixed 240 steps a second.
+/// High enough that screens up to 240 Hz get at least one step every frame.
+pub const MOVEMENT_STEP: f32 = 1.0 / 240.0;
+pub const PLAYER_RADIUS: f32 = TILE_SIZE / 2.0;
+/// Below this a slide on the ice comes to a stop, in pixels per second
+pub const MIN_SLIDE_SPEED: f32 = 0.05 * TILE_SIZE;
+/// In tiles per second, before the speed setting and any effects
+pub const PLAYER_SPEED: f32 = 10.0;
+/// Stick deflection below which gamepad input is ignored
+pub const GAMEPAD_DEADZONE: f32 = 0.15;
+/// Time between footsteps when walking at the base speed
+pub const FOOTSTEP_INTERVAL: f32 = 0.4;
+/// How far each step's playback rate can stray from normal, either way
+pub const FOOTSTEP_PITCH_VARIATION: f64 = 0.08;
+/// Minimum time between two hurt sounds, so that a burst of hits doesn't stack them up
+pub const HURT_SOUND_COOLDOWN: f32 = 0.35;
+/// The player is voiced by the enemy screams, sped up when hurt and slowed down when dying
+pub const HURT_SOUND_RATE: f64 = 1.4;
+pub const DEATH_SOUND_RATE: f64 = 0.75;
+/// The overheat hiss is an enemy shot slowed down
+pub const OVERHEAT_SOUND_RATE: f64 = 0.5;
 
 #[derive(Component)]
 pub struct Player;
 
+/// How fast the player is actually moving, in pixels per second.
+/// Normally that's just the input, but it carries over between frames on the ice.
+#[derive(Component, Default)]
+pub struct Momentum {
+	pub velocity: Vec2,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AimDevice {
+	Mouse,
+	Gamepad,
+}
+
+/// Where the player is aiming, shared by aiming, shooting and the crosshair.
+/// The device that was used last decides the direction.
+pub struct Aim {
+	pub direction: Vec2,
+	pub device: AimDevice,
+}
+
+impl Default for Aim {
+	fn default() -> Self {
+		Self {
+			direction: Vec2::Y,
+			device: AimDevice::Mouse,
+		}
+	}
+}
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
 	fn build(&self, app: &mut App) {
 		app.add_plugin(PlayerPostProcessingPlugin)
 			.register_type::<Movement>()
+			.register_type::<Stamina>()
 			.insert_resource(ActiveMaterial(None))
+			.insert_resource(Aim::default())
+			.insert_resource(KeyBindings::default())
+			.insert_resource(ScreenShake::default())
+			.insert_resource(CameraSettings::default())
+			.insert_resource(CameraFocus::default())
+			.insert_resource(MovementClock::default())
 			.add_event::<ShootEvent>()
 			.add_system_set(
 				SystemSet::on_enter(GameState::Game)
 					.with_system(ui_setup)
-					.with_system(setup_footstep_timer),
+					.with_system(setup_footstep_timer)
+					.with_system(reset_screen_shake)
+					.with_system(reset_camera),
 			)
 			.add_system_set(
 				SystemSet::on_exit(GameState::Game)
 					.with_system(drop_ui)
-					.with_system(drop_footstep_timer),
+					.with_system(drop_footstep_timer)
+					.with_system(end_bullet_time),
 			)
 			.add_system_set(
 				SystemSet::on_update(GameState::Game)
+					.with_system(player_dash.before("player_movement"))
+					.with_system(player_bullet_time.before("player_movement"))
+					.with_system(player_sprint.label("player_sprint").before("player_movement"))
+					.with_system(regenerate_stamina.after("player_sprint"))
 					.with_system(player_movement.label("player_movement"))
+					.with_system(decay_screen_shake.before("camera_follow_player"))
+					.with_system(camera_zoom.before("camera_follow_player"))
 					.with_system(
 						camera_follow
 							.label("camera_follow_player")
 							.after("player_movement"),
 					)
-					.with_system(player_aim.label("player_aim").after("player_movement"))
-					.with_system(player_shoot.after("player_aim"))
+					.with_system(player_aim.label("player_aim").after("camera_follow_player"))
+					.with_system(player_switch_weapon.label("player_switch_weapon"))
+					.with_system(player_reload.label("player_reload").after("player_switch_weapon"))
+					.with_system(player_shoot.label("player_shoot").after("player_aim").after("player_reload"))
+					.with_system(player_melee.after("player_aim").after("rebuild_enemy_grid"))
 					.with_system(damage_yourself)
 					.with_system(get_shot)
+					.with_system(player_regen)
+					.with_system(shield_regen)
 					.with_system(win_condition)
 					.with_system(update_ui)
+					.with_system(update_health_bar)
+					.with_system(update_low_health_vignette)
+					.with_system(update_key_ui)
+					.with_system(update_wave_ui)
+					.with_system(update_shield_ui)
+					.with_system(update_ability_ui)
+					.with_system(build_minimap)
+					.with_system(spawn_minimap_dots)
+					.with_system(update_minimap_dots)
+					.with_system(toggle_minimap)
 					.with_system(pick_up_cocaine)
 					.with_system(craft_magic_dust)
 					.with_system(use_powerup)
@@ -96,11 +281,21 @@ pub struct PlayerBundle {
 	name: Name,
 	player: Player,
 	movement: Movement,
+	momentum: Momentum,
 	health: Health,
+	armor: Armor,
 	shooting: Shooting,
 	rapier_collider: Collider,
 	inventory: Inventory,
 	effect: EffectData,
+	dash: Dash,
+	bullet_time: BulletTime,
+	stamina: Stamina,
+	sprint: Sprint,
+	melee: Melee,
+	regen: Regen,
+	shield: Shield,
+	status_effects: StatusEffects,
 }
 
 impl Default for PlayerBundle {
@@ -116,17 +311,25 @@ impl Default for PlayerBundle {
 			},
 			name: Name::new("Player"),
 			player: Player,
-			movement: Movement { speed: 10.0 },
+			movement: Movement { speed: PLAYER_SPEED },
+			momentum: Momentum::default(),
 			health: Health::new(100.0),
-			shooting: Shooting {
-				cooldown: Timer::new(Duration::from_secs_f32(WEAPON_COOLDOWN), false),
-			},
+			armor: Armor::default(),
+			shooting: Shooting::new(vec![shotgun(), pistol(), blaster(), railgun()]),
 			inventory: Inventory::new(),
 			effect: EffectData {
 				effect: None,
 				duration: Timer::from_seconds(0.0, false),
 			},
-			rapier_collider: Collider::ball(TILE_SIZE / 2.0),
+			rapier_collider: Collider::ball(PLAYER_RADIUS),
+			dash: Dash::default(),
+			bullet_time: BulletTime::default(),
+			stamina: Stamina::new(100.0, 20.0),
+			sprint: Sprint::default(),
+			melee: Melee::default(),
+			regen: Regen::default(),
+			shield: Shield::default(),
+			status_effects: StatusEffects::default(),
 		}
 	}
 }
@@ -150,11 +353,99 @@ impl Tile for PlayerBundle {
 	}
 }
 
+/// The weapon the player starts out holding
+pub fn shotgun() -> Weapon {
+	Weapon::new(WEAPON_COOLDOWN, WEAPON_RANGE, WEAPON_DAMAGE, MAGAZINE_SIZE, RELOAD_TIME)
+		.with_name("Shotgun")
+		.with_spread(WEAPON_PELLET_COUNT, WEAPON_SPREAD_ANGLE)
+		.with_knockback(WEAPON_KNOCKBACK)
+		.with_reserve(MAX_RESERVE)
+		.with_crit(WEAPON_CRIT_CHANCE, CRIT_MULTIPLIER)
+		.with_falloff(WEAPON_EFFECTIVE_RANGE, WEAPON_MIN_DAMAGE_FRACTION)
+}
+
+pub fn pistol() -> Weapon {
+	Weapon::new(
+		PISTOL_COOLDOWN,
+		PISTOL_RANGE,
+		PISTOL_DAMAGE,
+		PISTOL_MAGAZINE_SIZE,
+		PISTOL_RELOAD_TIME,
+	)
+	.with_name("Pistol")
+	.with_mode(ShotMode::Hitscan)
+	.with_knockback(PISTOL_KNOCKBACK)
+	.with_reserve(PISTOL_MAX_RESERVE)
+	.with_crit(PISTOL_CRIT_CHANCE, CRIT_MULTIPLIER)
+	.with_falloff(PISTOL_EFFECTIVE_RANGE, PISTOL_MIN_DAMAGE_FRACTION)
+	.with_pierce(PISTOL_PIERCE_COUNT)
+}
+
+/// Charges up while the trigger is held, going through every enemy in a line
+pub fn railgun() -> Weapon {
+	Weapon::new(
+		RAILGUN_COOLDOWN,
+		RAILGUN_RANGE,
+		RAILGUN_DAMAGE,
+		RAILGUN_MAGAZINE_SIZE,
+		RAILGUN_RELOAD_TIME,
+	)
+	.with_name("Railgun")
+	.with_mode(ShotMode::Hitscan)
+	.with_fire_mode(FireMode::Charge {
+		max_time: RAILGUN_CHARGE_TIME,
+		min_multiplier: RAILGUN_MIN_CHARGE_MULTIPLIER,
+		max_multiplier: RAILGUN_MAX_CHARGE_MULTIPLIER,
+	})
+	.with_knockback(RAILGUN_KNOCKBACK)
+	.with_reserve(RAILGUN_MAX_RESERVE)
+	.with_pierce(RAILGUN_PIERCE_COUNT)
+}
+
+/// A pistol firing a few shots for every pull of the trigger, only found as loot
+pub fn burst_pistol() -> Weapon {
+	pistol().with_name("Burst Pistol").with_fire_mode(FireMode::Burst {
+		shots: BURST_PISTOL_SHOTS,
+		interval: BURST_PISTOL_INTERVAL,
+	})
+}
+
+/// Fires quickly without ever reloading, as long as it's given time to cool down
+pub fn blaster() -> Weapon {
+	// No magazine, the heat is what limits it
+	Weapon::new(BLASTER_COOLDOWN, BLASTER_RANGE, BLASTER_DAMAGE, 0, 0.0)
+		.with_name("Blaster")
+		.with_fire_mode(FireMode::Auto)
+		.with_knockback(BLASTER_KNOCKBACK)
+		.with_overheat(Overheat::new(
+			BLASTER_HEAT_PER_SHOT,
+			BLASTER_DISSIPATION,
+			BLASTER_DISSIPATION_DELAY,
+			BLASTER_LOCKOUT,
+		))
+}
+
 #[derive(Deref, DerefMut)]
 struct FootstepTimer(pub Timer);
 
+/// The footstep sounds, the channel they play on with a varying pitch and the timer spacing them out
+#[derive(SystemParam)]
+struct Footsteps<'w, 's> {
+	channel: Res<'w, AudioChannel<PitchedSfxChannel>>,
+	sounds: Res<'w, FootstepSounds>,
+	timer: ResMut<'w, FootstepTimer>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
+/// Game time that hasn't been turned into movement steps yet, carried over to the next frame
+#[derive(Default)]
+pub struct MovementClock {
+	accumulator: f32,
+}
+
 fn setup_footstep_timer(mut commands: Commands) {
-	commands.insert_resource(FootstepTimer(Timer::from_seconds(0.4, false)));
+	commands.insert_resource(FootstepTimer(Timer::from_seconds(FOOTSTEP_INTERVAL, false)));
 }
 
 fn drop_footstep_timer(mut commands: Commands) {
@@ -162,124 +453,284 @@ fn drop_footstep_timer(mut commands: Commands) {
 }
 
 fn player_movement(
-	mut player_query: Query<(Entity, &Movement, &mut Transform, &Collider), With<Player>>,
+	mut player_query: Query<
+		(Entity, &Movement, &mut Momentum, &mut Transform, &Collider, &mut Dash, &Sprint),
+		With<Player>,
+	>,
 	enemy_query: Query<Entity, (With<Enemy>, Without<Player>)>,
+	wall_query: Query<(&Transform, &TileCollider), Without<Player>>,
+	slow_tile_query: Query<(&Transform, &SlowTile), Without<Player>>,
+	ice_tile_query: Query<(&Transform, &IceTile), Without<Player>>,
 	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	gamepads: Res<Gamepads>,
+	gamepad_axes: Res<Axis<GamepadAxis>>,
 	time: Res<TimeCounter>,
 	settings: Res<Settings>,
-	audio: Res<Audio>,
 	rapier_context: Res<RapierContext>,
-	footstep_sounds: Res<FootstepSounds>,
-	mut footstep_timer: ResMut<FootstepTimer>,
+	mut clock: ResMut<MovementClock>,
+	mut footsteps: Footsteps,
 ) {
-	let (player_entity, movement, mut transform, rapier_collider) = player_query
+	let (player_entity, movement, mut momentum, mut transform, rapier_collider, mut dash, sprint) = player_query
 		.iter_mut()
 		.next()
 		.expect("Player not found in the scene!");
 
+	// A lag spike can carry the player into a wall, where the shape casts can't get them out
+	for (wall_transform, wall) in wall_query.iter() {
+		let ejection = eject_from_wall(
+			transform.translation.truncate(),
+			wall_transform.translation.truncate(),
+			wall.half_size,
+			PLAYER_RADIUS,
+		);
+
+		transform.translation += ejection.extend(0.0);
+	}
+
 	let enemies: Vec<Entity> = enemy_query.iter().collect();
 
-	let mut direction = Vec3::new(0.0, 0.0, 0.0);
+	let mut direction = Vec2::new(0.0, 0.0);
 
-	if keyboard.pressed(KeyCode::W) {
+	if keyboard.pressed(key_bindings.up) {
 		direction.y += 1.0;
 	}
 
-	if keyboard.pressed(KeyCode::S) {
+	if keyboard.pressed(key_bindings.down) {
 		direction.y -= 1.0;
 	}
 
-	if keyboard.pressed(KeyCode::D) {
+	if keyboard.pressed(key_bindings.right) {
 		direction.x += 1.0;
 	}
 
-	if keyboard.pressed(KeyCode::A) {
+	if keyboard.pressed(key_bindings.left) {
 		direction.x -= 1.0;
 	}
 
-	if direction.length() != 0.0 {
-		let shape = rapier_collider;
-		let rotation = transform.rotation.z;
-		let direction = direction.normalize().truncate();
-		let max_time_of_impact = movement.speed * TILE_SIZE * time.delta_seconds();
+	// Analog input keeps its magnitude, so a half-tilted stick walks at half speed
+	let direction = (direction.normalize_or_zero()
+		+ read_stick(
+			gamepads.as_ref(),
+			gamepad_axes.as_ref(),
+			GamepadAxisType::LeftStickX,
+			GamepadAxisType::LeftStickY,
+		))
+	.clamp_length_max(1.0);
+
+	// The setting is applied here instead of on the Movement, so that changing it
+	// takes effect right away and doesn't get mixed up with the powerups and slows
+	let mut speed = movement.speed * settings.player_speed * sprint.speed_multiplier();
+
+	// Checked every frame, so full speed is back as soon as the player is off the tile
+	speed *= slow_factor(transform.translation.truncate(), PLAYER_RADIUS, slow_tile_query.iter());
+
+	// A dash keeps its direction, falling back to where the player faces when standing still
+	let direction = if dash.is_dashing() {
+		speed *= dash.speed_multiplier;
+
+		let facing = transform.up().truncate();
+
+		*dash
+			.direction
+			.get_or_insert_with(|| direction.try_normalize().unwrap_or(facing))
+	} else {
+		direction
+	};
+
+	let target_velocity = direction * speed * TILE_SIZE;
+
+	// The movement runs in fixed steps, so the collision and the ice behave the same at any frame rate.
+	// The input is read every frame and applies to all the steps the frame runs, the leftover time
+	// waits for the next frame. A hair of slack keeps float rounding from costing a step.
+	clock.accumulator += time.delta_seconds();
+	let steps = (clock.accumulator / MOVEMENT_STEP + 1e-3).floor();
+	clock.accumulator = (clock.accumulator - steps * MOVEMENT_STEP).max(0.0);
+
+	let predicate = |entity| !enemies.contains(&entity);
+
+	let filter = QueryFilter::default()
+		.exclude_collider(player_entity)
+		.exclude_sensors()
+		.predicate(&predicate);
+
+	let mut movement_vector = Vec2::ZERO;
+
+	for _ in 0..steps as u32 {
+		// On ice the velocity only slowly follows the input, so the player keeps sliding after letting go.
+		// Dashes keep full control, so that they can still be used to get out.
+		momentum.velocity = match ice_grip(transform.translation.truncate(), PLAYER_RADIUS, ice_tile_query.iter()) {
+			Some(grip) if !dash.is_dashing() => {
+				let t = 1.0 - (-grip * MOVEMENT_STEP).exp();
+				momentum.velocity.lerp(target_velocity, t)
+			}
+			_ => target_velocity,
+		};
 
-		let predicate = |entity| !enemies.contains(&entity);
+		// The easing never quite reaches zero by itself
+		if momentum.velocity.length() < MIN_SLIDE_SPEED {
+			momentum.velocity = Vec2::ZERO;
+			continue;
+		}
 
-		let filter = QueryFilter::default()
-			.exclude_collider(player_entity)
-			.predicate(&predicate);
+		let step_vector = momentum.velocity * MOVEMENT_STEP;
+		let rotation = transform.rotation.z;
 
-		let x_movement = if let Some((_, hit)) = rapier_context.cast_shape(
+		// Resolve the axes one after another, so that the Y cast already starts from the
+		// corrected X position and can't push the player back into a neighbouring tile
+		let x_movement = cast_axis_movement(
+			rapier_context.as_ref(),
 			transform.translation.truncate(),
 			rotation,
-			direction * Vec2::new(1.0, 0.0),
-			shape,
-			max_time_of_impact,
+			rapier_collider,
+			Vec2::X * step_vector.x,
 			filter,
-		) {
-			direction * (hit.toi - 0.1)
-		} else {
-			direction * (max_time_of_impact - 0.1)
-		}
+		)
 		.x;
 
 		transform.translation.x += x_movement;
 
-		let y_movement = if let Some((_, hit)) = rapier_context.cast_shape(
+		let y_movement = cast_axis_movement(
+			rapier_context.as_ref(),
 			transform.translation.truncate(),
 			rotation,
-			direction * Vec2::new(0.0, 1.0),
-			shape,
-			max_time_of_impact,
+			rapier_collider,
+			Vec2::Y * step_vector.y,
 			filter,
-		) {
-			direction * (hit.toi - 0.1)
-		} else {
-			direction * (max_time_of_impact - 0.1)
-		}
+		)
 		.y;
 
 		transform.translation.y += y_movement;
 
-		let movement_vector = Vec2::new(x_movement, y_movement);
+		// Sliding into a wall stops the slide along it
+		let step_movement = Vec2::new(x_movement, y_movement);
+		momentum.velocity = step_movement / MOVEMENT_STEP;
+		movement_vector += step_movement;
+	}
 
-		footstep_timer.tick(time.delta());
-		if movement_vector != Vec2::ZERO && footstep_timer.finished() {
-			AudioPlayer::play_sfx(
-				audio.as_ref(),
-				footstep_sounds
-					.choose(&mut rand::thread_rng())
+	// Only walking makes footsteps, sliding along the ice is silent
+	if direction.length() != 0.0 {
+		// Steps come quicker the faster the player actually moves, so sprinting and dashing
+		// speed them up and sliding along a wall slows them down
+		let base_distance = movement.speed * TILE_SIZE * time.delta_seconds();
+		if base_distance > 0.0 {
+			let speed_ratio = movement_vector.length() / base_distance;
+			footsteps.timer.tick(time.delta().mul_f32(speed_ratio));
+		}
+
+		if movement_vector != Vec2::ZERO && footsteps.timer.finished() {
+			let mut rng = rand::thread_rng();
+			let pitch = 1.0 + rng.gen_range(-FOOTSTEP_PITCH_VARIATION..=FOOTSTEP_PITCH_VARIATION);
+
+			AudioPlayer::play_sfx_with_rate(
+				footsteps.channel.as_ref(),
+				footsteps
+					.sounds
+					.choose(&mut rng)
 					.expect("No footstep sounds found.")
 					.clone(),
 				PLAYER_FOOTSTEP_VOLUME,
 				settings.as_ref(),
+				pitch * time.scale() as f64,
 			);
-			footstep_timer.reset();
+			footsteps.timer.reset();
 		}
+	} else {
+		// Standing still lets the next step play as soon as the player starts walking again
+		let duration = footsteps.timer.duration();
+		footsteps.timer.set_elapsed(duration);
 	}
 }
 
-fn camera_follow(
-	player_query: Query<&Transform, With<Player>>,
-	mut camera_query: Query<&mut Transform, (Without<Player>, With<MainCamera>)>,
-) {
-	let mut camera_transform = camera_query.single_mut();
-	let player_transform = player_query.single();
+/// Returns the deflection of the first gamepad stick pushed past the deadzone,
+/// rescaled so that its length grows from zero at the deadzone to one at full tilt.
+pub fn read_stick(
+	gamepads: &Gamepads,
+	gamepad_axes: &Axis<GamepadAxis>,
+	x_axis: GamepadAxisType,
+	y_axis: GamepadAxisType,
+) -> Vec2 {
+	for gamepad in gamepads.iter() {
+		let stick = Vec2::new(
+			gamepad_axes.get(GamepadAxis(*gamepad, x_axis)).unwrap_or(0.0),
+			gamepad_axes.get(GamepadAxis(*gamepad, y_axis)).unwrap_or(0.0),
+		);
+
+		let deflection = stick.length();
+
+		if deflection > GAMEPAD_DEADZONE {
+			let scaled = ((deflection - GAMEPAD_DEADZONE) / (1.0 - GAMEPAD_DEADZONE)).min(1.0);
 
-	camera_transform.translation.x = player_transform.translation.x;
-	camera_transform.translation.y = player_transform.translation.y;
+			return stick / deflection * scaled;
+		}
+	}
+
+	Vec2::ZERO
+}
+
+/// Casts the player's collider along a single axis and returns the part of `movement`
+/// that can be travelled before touching solid geometry.
+fn cast_axis_movement(
+	rapier_context: &RapierContext,
+	position: Vec2,
+	rotation: f32,
+	shape: &Collider,
+	movement: Vec2,
+	filter: QueryFilter,
+) -> Vec2 {
+	let distance = movement.length();
+
+	if distance == 0.0 {
+		return Vec2::ZERO;
+	}
+
+	let direction = movement / distance;
+
+	if let Some((_, hit)) =
+		rapier_context.cast_shape(position, rotation, direction, shape, distance, filter)
+	{
+		// Never move backwards, otherwise touching two tiles at once makes the player stutter
+		direction * (hit.toi - COLLISION_SKIN).clamp(0.0, distance)
+	} else {
+		movement
+	}
+}
+
+/// If `position` lies inside the tile, returns the offset that moves it out through
+/// the nearest edge, so that a collider of the given `radius` no longer overlaps the tile.
+fn eject_from_wall(position: Vec2, wall_position: Vec2, half_size: Vec2, radius: f32) -> Vec2 {
+	let offset = position - wall_position;
+
+	if offset.x.abs() >= half_size.x || offset.y.abs() >= half_size.y {
+		return Vec2::ZERO;
+	}
+
+	let penetration = half_size + Vec2::splat(radius + COLLISION_SKIN) - offset.abs();
+
+	if penetration.x < penetration.y {
+		Vec2::new(penetration.x * offset.x.signum(), 0.0)
+	} else {
+		Vec2::new(0.0, penetration.y * offset.y.signum())
+	}
 }
 
 fn damage_yourself(
 	mut player_query: Query<&mut Health, With<Player>>,
 	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
 	mut state: ResMut<State<GameState>>,
+	channel: Res<AudioChannel<PitchedSfxChannel>>,
+	death_sound: Res<PlayerDeathSound>,
+	settings: Res<Settings>,
+	mut rng: ResMut<GameRng>,
 ) {
 	let mut player_health = player_query.single_mut();
 
 	#[allow(clippy::collapsible_if)]
-	if cfg!(debug_assertions) && keyboard.just_pressed(KeyCode::Space) {
-		if player_health.take_damage(rand::thread_rng().gen::<f32>() * 10.0 + 10.0) {
+	if cfg!(debug_assertions) && keyboard.just_pressed(key_bindings.damage_yourself) {
+		if player_health.take_damage(rng.gen::<f32>() * 10.0 + 10.0) {
+			play_death_sound(channel.as_ref(), &death_sound, settings.as_ref());
+
 			state
 				.set(GameState::GameOver)
 				.expect("Failed to change states");
@@ -287,85 +738,381 @@ fn damage_yourself(
 	}
 }
 
-fn player_aim(mut player_query: Query<&mut Transform, With<Player>>, window: Res<Windows>) {
+fn play_death_sound(channel: &AudioChannel<PitchedSfxChannel>, death_sound: &PlayerDeathSound, settings: &Settings) {
+	AudioPlayer::play_sfx_with_rate(
+		channel,
+		death_sound.0.clone(),
+		PLAYER_DEATH_VOLUME,
+		settings,
+		DEATH_SOUND_RATE,
+	);
+}
+
+fn player_aim(
+	mut player_query: Query<&mut Transform, With<Player>>,
+	camera_query: Query<(&Transform, &OrthographicProjection), (With<MainCamera>, Without<Player>)>,
+	mut cursor_moved: EventReader<CursorMoved>,
+	window: Res<Windows>,
+	gamepads: Res<Gamepads>,
+	gamepad_axes: Res<Axis<GamepadAxis>>,
+	mut aim: ResMut<Aim>,
+) {
 	let mut player_transform = player_query.single_mut();
 
-	if let Some(target) = window.iter().next().unwrap().cursor_position() {
-		let window_size = Vec2::new(WIDTH as f32, HEIGHT as f32);
+	if cursor_moved.iter().last().is_some() {
+		aim.device = AimDevice::Mouse;
+	}
 
-		let target = target - window_size / 2.0;
+	let stick = read_stick(
+		gamepads.as_ref(),
+		gamepad_axes.as_ref(),
+		GamepadAxisType::RightStickX,
+		GamepadAxisType::RightStickY,
+	);
 
-		let angle = Vec2::Y.angle_between(target);
-		player_transform.rotation = Quat::from_rotation_z(angle);
+	if stick != Vec2::ZERO {
+		aim.device = AimDevice::Gamepad;
+		aim.direction = stick.normalize();
+	}
+
+	if aim.device == AimDevice::Mouse {
+		// Nothing to aim at without a window, or with the cursor outside of it
+		if let Some(cursor_position) = window.get_primary().and_then(|window| window.cursor_position()) {
+			let (camera_transform, projection) = camera_query.single();
+
+			// The camera trails behind the player, so aim from the player and not from the screen center
+			let target = cursor_to_world(cursor_position, camera_transform, projection)
+				- player_transform.translation.truncate();
+
+			if target != Vec2::ZERO {
+				aim.direction = target.normalize();
+			}
+		}
+	}
+
+	let angle = Vec2::Y.angle_between(aim.direction);
+	player_transform.rotation = Quat::from_rotation_z(angle);
+}
+
+/// The fire buttons, the left mouse button and the right trigger of every gamepad
+#[derive(SystemParam)]
+struct Trigger<'w, 's> {
+	buttons: Res<'w, Input<MouseButton>>,
+	gamepads: Res<'w, Gamepads>,
+	gamepad_buttons: Res<'w, Input<GamepadButton>>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
+impl Trigger<'_, '_> {
+	const GAMEPAD_BUTTON: GamepadButtonType = GamepadButtonType::RightTrigger2;
+
+	/// Pressed down this frame
+	fn pulled(&self) -> bool {
+		self.buttons.just_pressed(MouseButton::Left)
+			|| self.gamepads.iter().any(|gamepad| {
+				self.gamepad_buttons.just_pressed(GamepadButton(*gamepad, Self::GAMEPAD_BUTTON))
+			})
+	}
+
+	fn held(&self) -> bool {
+		self.buttons.pressed(MouseButton::Left)
+			|| self.gamepads.iter().any(|gamepad| {
+				self.gamepad_buttons.pressed(GamepadButton(*gamepad, Self::GAMEPAD_BUTTON))
+			})
 	}
 }
 
+/// The sounds of firing, the overheat plays on the pitched channel so that it follows the timescale
+#[derive(SystemParam)]
+struct ShotSounds<'w, 's> {
+	audio: Res<'w, Audio>,
+	shot: Res<'w, ShotgunSound>,
+	overheat: Res<'w, OverheatSound>,
+	pitched_channel: Res<'w, AudioChannel<PitchedSfxChannel>>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
 fn player_shoot(
 	mut commands: Commands,
 	mut player_query: Query<(Entity, &Transform, &mut Shooting), With<Player>>,
 	world_query: Query<Entity, With<Tilemap>>,
+	wall_query: Query<(), With<TileCollider>>,
 	mut event_shot: EventWriter<ShootEvent>,
-	buttons: Res<Input<MouseButton>>,
+	mut hit_event: EventWriter<ShotEvent>,
+	mut knockback_event: EventWriter<KnockbackEvent>,
+	trigger: Trigger,
+	aim: Res<Aim>,
+	mut screen_shake: ResMut<ScreenShake>,
 	time: Res<TimeCounter>,
-	_rapier_context: Res<RapierContext>,
+	rapier_context: Res<RapierContext>,
 	settings: Res<Settings>,
-	audio: Res<Audio>,
-	shot_sound: Res<ShotgunSound>,
-	bullet_texture: Res<BulletTexture>,
+	sounds: ShotSounds,
+	mut bullets: BulletSpawner,
 	mut stats: ResMut<Stats>,
 ) {
-	let (_player, player_transform, mut shooting) = player_query.single_mut();
+	let (player, player_transform, mut shooting) = player_query.single_mut();
 	let world = world_query.single();
 
-	shooting.cooldown.tick(time.delta());
+	shooting.tick(time.delta());
 
-	if !shooting.cooldown.finished() {
+	// Mid burst the shots come on the burst's own cadence, the cooldown only starts after it
+	let ready = if shooting.burst_left > 0 {
+		shooting.burst_timer.finished()
+	} else {
+		shooting.cooldown.finished()
+	};
+
+	if !ready {
 		return;
 	}
 
-	if buttons.just_pressed(MouseButton::Left) {
-		// Spawn the bullets
-		let mut bullets = Vec::new();
+	// An empty gun doesn't fire
+	if shooting.is_out_of_ammo() || shooting.is_overheated() {
+		shooting.burst_left = 0;
+		shooting.charge = None;
+		return;
+	}
 
-		for i in 1..5 {
-			let mut bullet_transform = player_transform
-				.with_translation(player_transform.translation + player_transform.up() * TILE_SIZE);
+	let trigger_pulled = trigger.pulled();
+	let trigger_held = trigger.held();
+
+	let fire_mode = shooting.fire_mode;
+
+	let fires = match fire_mode {
+		FireMode::Auto => trigger_held,
+		// The rest of the burst goes off without the trigger
+		FireMode::Burst { .. } if shooting.burst_left > 0 => true,
+		FireMode::Single | FireMode::Burst { .. } => trigger_pulled || (settings.hold_to_fire && trigger_held),
+		FireMode::Charge { max_time, .. } => {
+			if trigger_held {
+				let charge = shooting.charge.unwrap_or(0.0) + time.delta_seconds();
+				shooting.charge = Some(charge.min(max_time));
+				false
+			} else {
+				// Goes off once the trigger is let go, however little it was charged
+				shooting.charge.is_some()
+			}
+		}
+	};
+
+	if fires {
+		if let FireMode::Burst { shots, .. } = fire_mode {
+			if shooting.burst_left == 0 {
+				shooting.burst_left = shots;
+			}
+		}
 
-			bullet_transform.rotate_z((i - 2) as f32 * (0.02 + random::<f32>() * 0.01));
+		let charge_multiplier = shooting.release_charge();
 
-			bullets.push(
-				commands
-					.spawn_bundle(BulletBundle {
-						sprite_bundle: SpriteBundle {
-							transform: bullet_transform,
-							texture: bullet_texture.clone(),
-							..Default::default()
+		// The hitscan impacts, the bullets come from the pool
+		let mut impacts = Vec::new();
+
+		// One roll for the whole shot, so that a crit hits with every pellet
+		let (damage, critical) = shooting.roll_damage(&mut bullets.rng.0);
+		let damage = damage * charge_multiplier;
+
+		for pellet in 0..shooting.pellet_count {
+			let mut bullet_transform = player_transform
+				.with_translation(player_transform.translation + aim.direction.extend(0.0) * TILE_SIZE)
+				.with_rotation(Quat::from_rotation_z(Vec2::Y.angle_between(aim.direction)));
+
+			// Jitter the spread a bit, so that no two shots look the same
+			bullet_transform.rotate_z(shooting.pellet_angle(pellet) * (1.0 + bullets.rng.gen::<f32>() * 0.5));
+
+			match shooting.mode {
+				ShotMode::Projectile => {
+					bullets.fire(
+						&mut commands,
+						world,
+						bullet_transform,
+						Bullet {
+							speed: 2000.0,
+							range: shooting.range,
+							knockback: shooting.knockback,
+							damage,
+							status_effect: shooting.status_effect,
+							critical,
+							falloff: shooting.falloff,
+							traveled: 0.0,
+							pierce_left: shooting.pierce_count,
+							pierced: Vec::new(),
 						},
-						bullet: Bullet { speed: 2000.0 },
-						..Default::default()
-					})
-					.id(),
-			);
+					);
+				}
+				ShotMode::Hitscan => {
+					let filter = QueryFilter::default()
+						.exclude_collider(player)
+						.exclude_sensors();
+
+					let hits = hitscan_hits(
+						rapier_context.as_ref(),
+						player_transform.translation.truncate(),
+						bullet_transform.up().truncate(),
+						shooting.range,
+						shooting.pierce_count,
+						filter,
+						|entity| wall_query.contains(entity),
+					);
+
+					for (hit_entity, distance) in hits {
+						// The ray direction is normalized, so the time of impact is the distance
+						let damage = match shooting.falloff {
+							Some(falloff) => falloff.damage_at(damage, distance),
+							None => damage,
+						};
+
+						hit_event.send(ShotEvent(hit_entity, damage, shooting.status_effect, critical));
+
+						let impact_point = player_transform.translation.truncate()
+							+ bullet_transform.up().truncate() * distance;
+
+						impacts.push(commands.spawn_bundle(ImpactBundle::new(impact_point)).id());
+
+						knockback_event.send(KnockbackEvent(
+							hit_entity,
+							bullet_transform.up().truncate() * shooting.knockback,
+						));
+					}
+				}
+			}
 		}
 
-		commands.entity(world).push_children(&bullets);
+		commands.entity(world).push_children(&impacts);
+
+		let muzzle_flash = commands
+			.spawn_bundle(MuzzleFlashBundle::new(player_transform.translation, aim.direction))
+			.id();
+
+		commands.entity(world).add_child(muzzle_flash);
 
 		AudioPlayer::play_sfx(
-			audio.into_inner(),
-			shot_sound.clone(),
+			sounds.audio.as_ref(),
+			sounds.shot.clone(),
 			PLAYER_SHOT_VOLUME,
-			settings.into_inner(),
+			settings.as_ref(),
 		);
 
 		stats.shot_fired += 1;
 
+		screen_shake.add_trauma(SHOT_TRAUMA);
+
 		event_shot.send(ShootEvent(player_transform.translation.truncate()));
 
+		match shooting.overheat.as_mut() {
+			Some(overheat) => {
+				if overheat.add_heat() {
+					AudioPlayer::play_sfx_with_rate(
+						sounds.pitched_channel.as_ref(),
+						sounds.overheat.0.clone(),
+						OVERHEAT_VOLUME,
+						settings.as_ref(),
+						OVERHEAT_SOUND_RATE * time.scale() as f64,
+					);
+				}
+			}
+			None => shooting.use_round(),
+		}
+
+		if let FireMode::Burst { interval, .. } = fire_mode {
+			shooting.burst_left -= 1;
+
+			if shooting.burst_left > 0 {
+				shooting.burst_timer = Timer::from_seconds(interval, false);
+				return;
+			}
+		}
+
 		// Reset the cooldown timer
 		shooting.cooldown.reset();
 	}
 }
 
+/// What a hitscan shot hits, closest first, together with how far along the ray each hit is.
+/// The shot goes through `pierce_count` enemies and stops at the one after them or at a wall,
+/// which is the last hit then.
+fn hitscan_hits(
+	rapier_context: &RapierContext,
+	origin: Vec2,
+	direction: Vec2,
+	range: f32,
+	pierce_count: u32,
+	filter: QueryFilter,
+	is_wall: impl Fn(Entity) -> bool,
+) -> Vec<(Entity, f32)> {
+	let mut hits = Vec::new();
+
+	rapier_context.intersections_with_ray(origin, direction, range, true, filter, |hit_entity, intersection| {
+		hits.push((hit_entity, intersection.toi));
+		true
+	});
+
+	// The hits don't come in order
+	hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+	let mut enemies_hit = 0;
+
+	// Goes through enemies until the pierce runs out, but never through walls
+	let last = hits.iter().position(|(hit_entity, _)| {
+		if is_wall(*hit_entity) {
+			return true;
+		}
+
+		enemies_hit += 1;
+		enemies_hit > pierce_count
+	});
+
+	if let Some(last) = last {
+		hits.truncate(last + 1);
+	}
+
+	hits
+}
+
+fn player_switch_weapon(
+	mut player_query: Query<&mut Shooting, With<Player>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	mut mouse_wheel: EventReader<MouseWheel>,
+) {
+	let mut shooting = player_query.single_mut();
+
+	for (slot, key) in WEAPON_SLOT_KEYS.iter().enumerate() {
+		if keyboard.just_pressed(*key) {
+			shooting.switch_to(slot);
+		}
+	}
+
+	let scroll: f32 = mouse_wheel.iter().map(|event| event.y).sum();
+
+	// The wheel zooms the camera instead
+	if keyboard.pressed(key_bindings.zoom) {
+		return;
+	}
+
+	if scroll > 0.0 {
+		shooting.cycle(1);
+	} else if scroll < 0.0 {
+		shooting.cycle(-1);
+	}
+}
+
+fn player_reload(
+	mut player_query: Query<&mut Shooting, With<Player>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+) {
+	let mut shooting = player_query.single_mut();
+
+	if keyboard.just_pressed(key_bindings.reload)
+		&& !shooting.is_reloading()
+		&& shooting.can_reload()
+	{
+		shooting.start_reload();
+	}
+}
+
 // It actually doesn't make any sense but it's the fastest (and also hackiest) way to do this
 enum PowerupMaterial {
 	SmallPowerup(Handle<SmallPowerupMaterial>),
@@ -382,6 +1129,7 @@ fn use_powerup(
 		With<Player>,
 	>,
 	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
 	mut time: ResMut<TimeCounter>,
 	settings: Res<Settings>,
 	audio: Res<Audio>,
@@ -420,8 +1168,8 @@ fn use_powerup(
 		return;
 	}
 
-	// Small powerup is under E
-	if keyboard.just_pressed(KeyCode::E) && inventory.subtract_small_powerup(1) {
+	// Small powerup is under E by default
+	if keyboard.just_pressed(key_bindings.small_powerup) && inventory.subtract_small_powerup(1) {
 		effect_data.apply(
 			Some(Box::new(SmallPowerup)),
 			movement.as_mut(),
@@ -457,8 +1205,8 @@ fn use_powerup(
 
 		stats.small_powerup_used += 1;
 	}
-	// Big powerup is under R
-	else if keyboard.just_pressed(KeyCode::R) && inventory.subtract_big_powerup(1) {
+	// Big powerup is under Q by default
+	else if keyboard.just_pressed(key_bindings.big_powerup) && inventory.subtract_big_powerup(1) {
 		effect_data.apply(
 			Some(Box::new(BigPowerup)),
 			movement.as_mut(),
@@ -540,6 +1288,7 @@ fn pick_up_cocaine(
 fn craft_magic_dust(
 	mut player_query: Query<&mut Inventory, With<Player>>,
 	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
 	audio: Res<Audio>,
 	settings: Res<Settings>,
 	crafting_sound: Res<CraftingSound>,
@@ -547,9 +1296,9 @@ fn craft_magic_dust(
 ) {
 	let mut inventory = player_query.single_mut();
 
-	// Press T to craft magic dust
+	// Press T (by default) to craft magic dust
 	#[allow(clippy::collapsible_if)]
-	if keyboard.just_pressed(KeyCode::T) {
+	if keyboard.just_pressed(key_bindings.craft) {
 		if inventory.subtract_small_powerup(3) {
 			inventory.add_big_powerup(1);
 			AudioPlayer::play_sfx(
@@ -563,13 +1312,32 @@ fn craft_magic_dust(
 	}
 }
 
+/// The sounds of the player getting hit, the pitched ones follow the timescale
+#[derive(SystemParam)]
+struct HurtSounds<'w, 's> {
+	audio: Res<'w, Audio>,
+	pitched_channel: Res<'w, AudioChannel<PitchedSfxChannel>>,
+	shield_break: Res<'w, ShieldBreakSound>,
+	hurt: Res<'w, PlayerHurtSounds>,
+	death: Res<'w, PlayerDeathSound>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
 fn get_shot(
-	mut player_query: Query<(Entity, &mut Health), With<Player>>,
+	mut player_query: Query<(Entity, &Transform, &mut Health, &Armor, &mut Shield), With<Player>>,
 	mut shot_events: EventReader<ShotEvent>,
 	mut state: ResMut<State<GameState>>,
-	mut stats: ResMut<Stats>
+	mut stats: ResMut<Stats>,
+	mut screen_shake: ResMut<ScreenShake>,
+	mut damage_events: EventWriter<DamageEvent>,
+	sounds: HurtSounds,
+	settings: Res<Settings>,
+	time: Res<TimeCounter>,
+	mut rng: ResMut<GameRng>,
+	mut last_hurt_sound: Local<Option<f32>>,
 ) {
-	let (player, mut health) = player_query.single_mut();
+	let (player, transform, mut health, armor, mut shield) = player_query.single_mut();
 
 	for shot in shot_events.iter() {
 		let entity = shot.0;
@@ -578,13 +1346,60 @@ fn get_shot(
 			continue;
 		}
 
-		let damage = 25.0 + random::<f32>() * 10.0;
+		// Some variety to the damage, relative so that small status effect ticks can't heal
+		let damage = shot.1 * (1.0 + (rng.gen::<f32>() - 0.5) / 3.0);
+
+		// The shield takes the hit first, the armor only protects the health
+		let (unabsorbed, shield_broke) = shield.absorb(damage);
+		let health_damage = armor.reduce(unabsorbed);
+
+		if shield_broke {
+			AudioPlayer::play_sfx(
+				sounds.audio.as_ref(),
+				sounds.shield_break.clone(),
+				SHIELD_BREAK_VOLUME,
+				settings.as_ref(),
+			);
+		}
+
+		stats.damage_taken += health_damage.min(health.get_health());
+
+		screen_shake.add_trauma(DAMAGE_TRAUMA);
 
-		stats.damage_taken += damage.min(health.get_health());
+		damage_events.send(DamageEvent {
+			position: transform.translation.truncate(),
+			amount: damage - unabsorbed + health_damage,
+			to_player: true,
+			critical: shot.3,
+		});
+
+		if health.take_damage(health_damage) {
+			play_death_sound(sounds.pitched_channel.as_ref(), &sounds.death, settings.as_ref());
 
-		#[allow(clippy::collapsible_if)]
-		if health.take_damage(damage) {
 			if state.set(GameState::GameOver).is_err() {}
+
+			// The rest of the shots can't kill the player a second time
+			break;
+		}
+
+		let now = time.seconds_since_startup();
+		let cooled_down = last_hurt_sound.map_or(true, |last| now - last >= HURT_SOUND_COOLDOWN);
+
+		// Hits taken entirely by the shield don't hurt
+		if health_damage > 0.0 && cooled_down {
+			AudioPlayer::play_sfx_with_rate(
+				sounds.pitched_channel.as_ref(),
+				sounds
+					.hurt
+					.choose(&mut rand::thread_rng())
+					.expect("No hurt sounds found.")
+					.clone(),
+				PLAYER_HURT_VOLUME,
+				settings.as_ref(),
+				HURT_SOUND_RATE * time.scale() as f64,
+			);
+
+			*last_hurt_sound = Some(now);
 		}
 	}
 }
@@ -592,16 +1407,289 @@ fn get_shot(
 fn win_condition(
 	player: Query<&Transform, With<Player>>,
 	win: Query<&Transform, With<Win>>,
+	enemies: Query<(), With<Enemy>>,
+	goal: Res<WinGoal>,
+	stats: Res<Stats>,
+	spawner: Res<WaveSpawner>,
 	mut state: ResMut<State<GameState>>,
 ) {
-	let player_position = player.single().translation;
+	let won = match *goal {
+		WinGoal::ReachExit => {
+			let player_position = player.single().translation;
+
+			win.iter().any(|win| (player_position - win.translation).length() < TILE_SIZE)
+		}
+		// The enemies aren't spawned yet on the first frame, so make sure some actually died
+		WinGoal::KillAll => stats.enemies_killed > 0 && enemies.is_empty(),
+		WinGoal::ClearWaves => spawner.is_finished(),
+	};
+
+	#[allow(clippy::collapsible_if)]
+	if won {
+		if state.set(GameState::Win).is_err() {}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_app::{spawn_wall, step, test_app};
+
+	use super::*;
+
+	const FRAME: f32 = 1.0 / 60.0;
+
+	/// Headless app running only the player's movement
+	fn movement_app() -> App {
+		let mut app = test_app();
+
+		app.insert_resource(FootstepSounds(vec![Handle::default()]))
+			.insert_resource(FootstepTimer(Timer::from_seconds(FOOTSTEP_INTERVAL, false)))
+			.insert_resource(MovementClock::default())
+			.init_resource::<AudioChannel<PitchedSfxChannel>>()
+			.add_system_set(SystemSet::on_update(GameState::Game).with_system(player_movement));
+
+		app
+	}
+
+	fn spawn_player(app: &mut App, position: Vec2) -> Entity {
+		app.world
+			.spawn()
+			.insert_bundle(PlayerBundle::spawn(position, Handle::default(), false, false))
+			.id()
+	}
+
+	fn hold(app: &mut App, key: KeyCode) {
+		app.world.resource_mut::<Input<KeyCode>>().press(key);
+	}
+
+	fn position(app: &App, entity: Entity) -> Vec2 {
+		app.world.get::<Transform>(entity).unwrap().translation.truncate()
+	}
+
+	#[test]
+	fn player_walks_up_to_a_wall_and_stops() {
+		let mut app = movement_app();
+
+		let player = spawn_player(&mut app, Vec2::ZERO);
+		// Three tiles to the right
+		spawn_wall(&mut app, Vec2::new(3.0 * TILE_SIZE, 0.0), Vec2::splat(TILE_SIZE / 2.0));
+
+		step(&mut app, 0.0);
+
+		let right = app.world.resource::<KeyBindings>().right;
+		hold(&mut app, right);
+
+		for _ in 0..60 {
+			step(&mut app, FRAME);
+		}
 
-	for win in win.iter() {
-		let distance = (player_position - win.translation).length();
+		let position = position(&app, player);
+		let wall_edge = 3.0 * TILE_SIZE - TILE_SIZE / 2.0;
 
-		#[allow(clippy::collapsible_if)]
-		if distance < TILE_SIZE {
-			if state.set(GameState::Win).is_err() {}
+		assert!(position.x > TILE_SIZE, "The player didn't move, they're at {}", position);
+		assert!(position.x + PLAYER_RADIUS <= wall_edge + 0.01, "The player went into the wall, they're at {}", position);
+		assert!(position.y.abs() < 0.01, "The player moved off their line, they're at {}", position);
+	}
+
+	#[test]
+	fn player_stops_in_an_l_shaped_corner_outside_both_walls() {
+		let mut app = movement_app();
+		let half_tile = Vec2::splat(TILE_SIZE / 2.0);
+
+		let player = spawn_player(&mut app, Vec2::ZERO);
+
+		// A column of tiles to the right and a row above, meeting in the top right corner
+		for y in -2..=2 {
+			spawn_wall(&mut app, Vec2::new(2.0, y as f32) * TILE_SIZE, half_tile);
+		}
+
+		for x in -2..=1 {
+			spawn_wall(&mut app, Vec2::new(x as f32, 2.0) * TILE_SIZE, half_tile);
+		}
+
+		step(&mut app, 0.0);
+
+		let key_bindings = app.world.resource::<KeyBindings>();
+		let (up, right) = (key_bindings.up, key_bindings.right);
+		hold(&mut app, up);
+		hold(&mut app, right);
+
+		for _ in 0..60 {
+			step(&mut app, FRAME);
+		}
+
+		let position = position(&app, player);
+		let wall_edge = 2.0 * TILE_SIZE - TILE_SIZE / 2.0;
+
+		assert!(position.x + PLAYER_RADIUS <= wall_edge + 0.01, "The player went into the column, they're at {}", position);
+		assert!(position.y + PLAYER_RADIUS <= wall_edge + 0.01, "The player went into the row, they're at {}", position);
+		// Pushed all the way into the corner
+		assert!(position.x + PLAYER_RADIUS > wall_edge - 1.0, "The player stopped early, they're at {}", position);
+		assert!(position.y + PLAYER_RADIUS > wall_edge - 1.0, "The player stopped early, they're at {}", position);
+	}
+
+	#[test]
+	fn player_slides_along_a_wall_of_tiles_without_stopping() {
+		let mut app = movement_app();
+		let half_tile = Vec2::splat(TILE_SIZE / 2.0);
+
+		// Right up against the row of tiles above, as close as the movement ever gets
+		let player = spawn_player(&mut app, Vec2::new(0.0, -COLLISION_SKIN));
+
+		for x in -2..=20 {
+			spawn_wall(&mut app, Vec2::new(x as f32, 1.0) * TILE_SIZE, half_tile);
 		}
+
+		step(&mut app, 0.0);
+
+		let key_bindings = app.world.resource::<KeyBindings>();
+		let (up, right) = (key_bindings.up, key_bindings.right);
+		hold(&mut app, up);
+		hold(&mut app, right);
+
+		let mut last_position = position(&app, player);
+
+		for _ in 0..30 {
+			step(&mut app, FRAME);
+
+			let position = position(&app, player);
+
+			// Every tile edge it passes is a chance for the two tiles to disagree
+			assert!(position.x > last_position.x + 1.0, "The player got stuck at {}", position);
+			assert!(position.y + PLAYER_RADIUS <= TILE_SIZE / 2.0 + 0.01, "The player went into the wall, they're at {}", position);
+
+			last_position = position;
+		}
+	}
+
+	/// How far the player walks right in one second of game time, split into frames of `frame` seconds
+	fn distance_walked_in_a_second(frame: f32) -> f32 {
+		let mut app = movement_app();
+
+		let player = spawn_player(&mut app, Vec2::ZERO);
+		step(&mut app, 0.0);
+
+		let right = app.world.resource::<KeyBindings>().right;
+		hold(&mut app, right);
+
+		for _ in 0..(1.0 / frame).round() as u32 {
+			step(&mut app, frame);
+		}
+
+		position(&app, player).x
+	}
+
+	#[test]
+	fn walking_distance_does_not_depend_on_the_frame_rate() {
+		let slow = distance_walked_in_a_second(1.0 / 30.0);
+		// Shorter frames than a movement step, so some of them don't move at all
+		let fast = distance_walked_in_a_second(1.0 / 300.0);
+
+		assert!(slow > TILE_SIZE, "The player didn't move, they walked {}", slow);
+		// At most one step can still be waiting in the clock
+		assert!(
+			(slow - fast).abs() <= slow * MOVEMENT_STEP + 0.01,
+			"Walked {} at 30 fps but {} at 300 fps",
+			slow,
+			fast
+		);
+	}
+
+	#[test]
+	fn eject_pushes_out_through_the_nearest_edge() {
+		let half_size = Vec2::splat(TILE_SIZE / 2.0);
+
+		// Just right of the center, so the right edge is the closest
+		let ejection = eject_from_wall(Vec2::new(20.0, 5.0), Vec2::ZERO, half_size, PLAYER_RADIUS);
+
+		assert_eq!(ejection.y, 0.0);
+		assert!((20.0 + ejection.x - (TILE_SIZE / 2.0 + PLAYER_RADIUS + COLLISION_SKIN)).abs() < 0.01);
+	}
+
+	#[test]
+	fn eject_leaves_positions_outside_the_tile_alone() {
+		let half_size = Vec2::splat(TILE_SIZE / 2.0);
+
+		assert_eq!(eject_from_wall(Vec2::new(40.0, 0.0), Vec2::ZERO, half_size, PLAYER_RADIUS), Vec2::ZERO);
+	}
+
+	#[test]
+	fn player_centered_on_a_wall_is_pushed_out_in_one_update() {
+		let mut app = movement_app();
+		let half_size = Vec2::splat(TILE_SIZE / 2.0);
+
+		let wall_position = Vec2::new(4.0, -3.0) * TILE_SIZE;
+		spawn_wall(&mut app, wall_position, half_size);
+		let player = spawn_player(&mut app, wall_position);
+
+		step(&mut app, FRAME);
+
+		let offset = (position(&app, player) - wall_position).abs();
+		let clearance = half_size + Vec2::splat(PLAYER_RADIUS);
+
+		assert!(
+			offset.x >= clearance.x || offset.y >= clearance.y,
+			"The player is still {} off the wall's center",
+			offset
+		);
+	}
+
+	/// Three enemies in a row to the right, two tiles apart
+	fn spawn_enemy_line(app: &mut App) -> [Entity; 3] {
+		[2.0, 4.0, 6.0].map(|x| {
+			app.world
+				.spawn()
+				.insert_bundle(TransformBundle::from_transform(Transform::from_xyz(x * TILE_SIZE, 0.0, 0.0)))
+				.insert(Collider::ball(TILE_SIZE / 2.0))
+				.id()
+		})
+	}
+
+	fn shoot_right(app: &App, pierce_count: u32, walls: &[Entity]) -> Vec<Entity> {
+		hitscan_hits(
+			app.world.resource::<RapierContext>(),
+			Vec2::ZERO,
+			Vec2::X,
+			20.0 * TILE_SIZE,
+			pierce_count,
+			QueryFilter::default(),
+			|entity| walls.contains(&entity),
+		)
+		.into_iter()
+		.map(|(entity, _)| entity)
+		.collect()
+	}
+
+	#[test]
+	fn shot_pierces_three_enemies_in_a_line() {
+		let mut app = test_app();
+		let enemies = spawn_enemy_line(&mut app);
+
+		step(&mut app, 0.0);
+
+		assert_eq!(shoot_right(&app, 2, &[]), enemies.to_vec());
+	}
+
+	#[test]
+	fn shot_stops_once_the_pierce_runs_out() {
+		let mut app = test_app();
+		let enemies = spawn_enemy_line(&mut app);
+
+		step(&mut app, 0.0);
+
+		assert_eq!(shoot_right(&app, 0, &[]), vec![enemies[0]]);
+		assert_eq!(shoot_right(&app, 1, &[]), vec![enemies[0], enemies[1]]);
+	}
+
+	#[test]
+	fn shot_stops_at_a_wall_whatever_the_pierce() {
+		let mut app = test_app();
+		let enemies = spawn_enemy_line(&mut app);
+		let wall = spawn_wall(&mut app, Vec2::new(5.0 * TILE_SIZE, 0.0), Vec2::new(TILE_SIZE / 4.0, TILE_SIZE / 2.0));
+
+		step(&mut app, 0.0);
+
+		assert_eq!(shoot_right(&app, 10, &[wall]), vec![enemies[0], enemies[1], wall]);
 	}
 }
+
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+use crate::{key_bindings::KeyBindings, time::TimeCounter};
+
+use super::Player;
+
+/// How fast the game runs during bullet time
+pub const BULLET_TIME_SCALE: f32 = 0.3;
+/// In real seconds, so that slowing the game down doesn't make it last longer
+pub const BULLET_TIME_DURATION: f32 = 2.0;
+pub const BULLET_TIME_COOLDOWN: f32 = 12.0;
+
+/// Slows the whole game down for a moment, to line up shots
+#[derive(Component)]
+pub struct BulletTime {
+	pub scale: f32,
+	pub duration: Timer,
+	pub cooldown: Timer,
+	active: bool,
+}
+
+impl BulletTime {
+	pub fn new(scale: f32, duration: f32, cooldown: f32) -> Self {
+		let mut cooldown = Timer::from_seconds(cooldown, false);
+
+		// Ready to use right away
+		let elapsed = cooldown.duration();
+		cooldown.tick(elapsed);
+
+		Self {
+			scale,
+			duration: Timer::from_seconds(duration, false),
+			cooldown,
+			active: false,
+		}
+	}
+
+	pub fn is_active(&self) -> bool {
+		self.active
+	}
+
+	/// Seconds left until bullet time can be used again, in real time
+	pub fn cooldown_remaining(&self) -> f32 {
+		self.cooldown.duration().as_secs_f32() - self.cooldown.elapsed_secs()
+	}
+
+	fn start(&mut self, time: &mut TimeCounter) {
+		self.duration.reset();
+		self.cooldown.reset();
+		self.active = true;
+
+		// Multiplied in and divided back out, so that it stacks with the powerups
+		time.timescale *= self.scale;
+	}
+
+	fn stop(&mut self, time: &mut TimeCounter) {
+		self.active = false;
+		time.timescale /= self.scale;
+	}
+}
+
+impl Default for BulletTime {
+	fn default() -> Self {
+		Self::new(BULLET_TIME_SCALE, BULLET_TIME_DURATION, BULLET_TIME_COOLDOWN)
+	}
+}
+
+pub fn player_bullet_time(
+	mut player_query: Query<&mut BulletTime, With<Player>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	mut time: ResMut<TimeCounter>,
+) {
+	let mut bullet_time = player_query.single_mut();
+
+	let real_delta = time.real_delta();
+	bullet_time.duration.tick(real_delta);
+	bullet_time.cooldown.tick(real_delta);
+
+	if bullet_time.is_active() && bullet_time.duration.finished() {
+		bullet_time.stop(&mut time);
+	}
+
+	if keyboard.just_pressed(key_bindings.bullet_time)
+		&& !bullet_time.is_active()
+		&& bullet_time.cooldown.finished()
+	{
+		bullet_time.start(&mut time);
+	}
+}
+
+/// The timescale outlives the player, so leaving the game mid bullet time mustn't keep it slowed down
+pub fn end_bullet_time(mut player_query: Query<&mut BulletTime, With<Player>>, mut time: ResMut<TimeCounter>) {
+	for mut bullet_time in player_query.iter_mut() {
+		if bullet_time.is_active() {
+			bullet_time.stop(&mut time);
+		}
+	}
+}
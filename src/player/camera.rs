@@ -0,0 +1,218 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::{
+	key_bindings::KeyBindings, post_processing::MainCamera, settings::Settings, tilemap::MapBounds,
+	time::TimeCounter, HEIGHT, WIDTH,
+};
+
+use super::screen_shake::ScreenShake;
+use super::Player;
+
+/// How fast the camera catches up with the player, higher is snappier
+pub const CAMERA_SMOOTHING: f32 = 10.0;
+/// Furthest the camera can fall behind the player, in pixels
+pub const CAMERA_MAX_LAG: f32 = 150.0;
+/// Projection scales, below 1 zooms in and above zooms out
+pub const DEFAULT_ZOOM: f32 = 1.0;
+pub const MIN_ZOOM: f32 = 0.6;
+pub const MAX_ZOOM: f32 = 1.5;
+/// Zoom change for every line scrolled
+pub const ZOOM_STEP: f32 = 0.1;
+
+pub struct CameraSettings {
+	pub smoothing: f32,
+	pub max_lag: f32,
+	pub default_zoom: f32,
+	pub min_zoom: f32,
+	pub max_zoom: f32,
+}
+
+impl Default for CameraSettings {
+	fn default() -> Self {
+		Self {
+			smoothing: CAMERA_SMOOTHING,
+			max_lag: CAMERA_MAX_LAG,
+			default_zoom: DEFAULT_ZOOM,
+			min_zoom: MIN_ZOOM,
+			max_zoom: MAX_ZOOM,
+		}
+	}
+}
+
+/// Converts a cursor position in the window to the point in the world under it.
+pub fn cursor_to_world(cursor_position: Vec2, camera_transform: &Transform, projection: &OrthographicProjection) -> Vec2 {
+	let window_size = Vec2::new(WIDTH, HEIGHT);
+
+	camera_transform.translation.truncate() + (cursor_position - window_size / 2.0) * projection.scale
+}
+
+/// Where the camera is looking, before any screen shake is added
+#[derive(Default)]
+pub struct CameraFocus(Option<Vec2>);
+
+impl CameraFocus {
+	/// Makes the camera jump straight to the player instead of easing over, for when they get moved far at once
+	pub fn cut(&mut self) {
+		self.0 = None;
+	}
+}
+
+/// Half of the area the camera sees, in world units
+pub fn view_half_size(projection: &OrthographicProjection) -> Vec2 {
+	Vec2::new(WIDTH, HEIGHT) / 2.0 * projection.scale
+}
+
+/// Keeps a camera with the given half size from looking past the edges of the map.
+/// Centers it on the axes where the map is smaller than the screen.
+fn clamp_to_bounds(position: Vec2, half_size: Vec2, bounds: &MapBounds) -> Vec2 {
+	let clamp_axis = |position: f32, half_size: f32, min: f32, max: f32| {
+		if max - min < half_size * 2.0 {
+			(min + max) / 2.0
+		} else {
+			position.clamp(min + half_size, max - half_size)
+		}
+	};
+
+	Vec2::new(
+		clamp_axis(position.x, half_size.x, bounds.min.x, bounds.max.x),
+		clamp_axis(position.y, half_size.y, bounds.min.y, bounds.max.y),
+	)
+}
+
+pub fn reset_camera(
+	mut focus: ResMut<CameraFocus>,
+	mut projection_query: Query<&mut OrthographicProjection, With<MainCamera>>,
+	camera_settings: Res<CameraSettings>,
+) {
+	// Snap straight to the player when a level starts
+	focus.0 = None;
+
+	projection_query.single_mut().scale = camera_settings.default_zoom;
+}
+
+pub fn camera_zoom(
+	mut projection_query: Query<&mut OrthographicProjection, With<MainCamera>>,
+	mut mouse_wheel: EventReader<MouseWheel>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	camera_settings: Res<CameraSettings>,
+) {
+	let mut projection = projection_query.single_mut();
+
+	if keyboard.just_pressed(key_bindings.reset_zoom) {
+		projection.scale = camera_settings.default_zoom;
+	}
+
+	let scroll: f32 = mouse_wheel.iter().map(|event| event.y).sum();
+
+	// Without the zoom key the wheel switches weapons
+	if !keyboard.pressed(key_bindings.zoom) {
+		return;
+	}
+
+	if scroll != 0.0 {
+		projection.scale = (projection.scale - scroll * ZOOM_STEP)
+			.clamp(camera_settings.min_zoom, camera_settings.max_zoom);
+	}
+}
+
+pub fn camera_follow(
+	player_query: Query<&Transform, With<Player>>,
+	mut camera_query: Query<(&mut Transform, &OrthographicProjection), (Without<Player>, With<MainCamera>)>,
+	camera_settings: Res<CameraSettings>,
+	mut focus: ResMut<CameraFocus>,
+	screen_shake: Res<ScreenShake>,
+	settings: Res<Settings>,
+	map_bounds: Res<MapBounds>,
+	time: Res<TimeCounter>,
+) {
+	let (mut camera_transform, projection) = camera_query.single_mut();
+	let player_position = player_query.single().translation.truncate();
+
+	let current = focus.0.unwrap_or(player_position);
+
+	// Exponential smoothing, so it eases in the same way at any frame rate.
+	// On real time, so that the camera keeps up with the player during slow motion.
+	// With reduced motion the camera sticks to the player instead of swaying after them.
+	let t = if settings.reduce_motion {
+		1.0
+	} else {
+		1.0 - (-camera_settings.smoothing * time.real_delta_seconds()).exp()
+	};
+	let target = current.lerp(player_position, t);
+
+	// Never let a fast player leave the camera behind for good
+	let lag = (target - player_position).clamp_length_max(camera_settings.max_lag);
+	let target = player_position + lag;
+
+	focus.0 = Some(target);
+
+	let shake = if settings.screen_shake && !settings.reduce_motion {
+		screen_shake.offset(time.seconds_since_startup())
+	} else {
+		Vec2::ZERO
+	};
+
+	// Clamp after shaking too, so the shake doesn't reveal the void either
+	let half_size = view_half_size(&projection);
+	let position = clamp_to_bounds(target + shake, half_size, map_bounds.as_ref());
+
+	camera_transform.translation.x = position.x;
+	camera_transform.translation.y = position.y;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(actual: Vec2, expected: Vec2) {
+		assert!(
+			(actual - expected).length() < 1e-3,
+			"expected {:?}, got {:?}",
+			expected,
+			actual
+		);
+	}
+
+	fn window_center() -> Vec2 {
+		Vec2::new(WIDTH, HEIGHT) / 2.0
+	}
+
+	#[test]
+	fn screen_center_is_under_an_offset_camera() {
+		let camera_transform = Transform::from_xyz(300.0, -200.0, 999.0);
+		let projection = OrthographicProjection::default();
+
+		let world = cursor_to_world(window_center(), &camera_transform, &projection);
+
+		assert_close(world, Vec2::new(300.0, -200.0));
+	}
+
+	#[test]
+	fn zoom_scales_the_distance_from_the_camera() {
+		let camera_transform = Transform::from_xyz(300.0, -200.0, 999.0);
+		let projection = OrthographicProjection {
+			scale: 2.0,
+			..default()
+		};
+
+		let world = cursor_to_world(window_center() + Vec2::new(100.0, -50.0), &camera_transform, &projection);
+
+		assert_close(world, Vec2::new(500.0, -300.0));
+	}
+
+	#[test]
+	fn aim_points_from_the_player_to_the_cursor() {
+		// The camera lags behind a player running left, so the player is off the screen center
+		let camera_transform = Transform::from_xyz(300.0, -200.0, 999.0);
+		let projection = OrthographicProjection::default();
+		let player_position = Vec2::new(250.0, -200.0);
+
+		// Right on the screen center, which aiming from the center would have no direction for
+		let target = cursor_to_world(window_center(), &camera_transform, &projection);
+		let direction = (target - player_position).normalize();
+
+		assert_close(direction, Vec2::X);
+	}
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::{key_bindings::KeyBindings, time::TimeCounter};
+
+use super::Player;
+
+pub const DASH_SPEED_MULTIPLIER: f32 = 4.0;
+pub const DASH_DURATION: f32 = 0.15;
+pub const DASH_COOLDOWN: f32 = 1.5;
+
+#[derive(Component)]
+pub struct Dash {
+	pub speed_multiplier: f32,
+	pub duration: Timer,
+	pub cooldown: Timer,
+	/// Set by the movement system on the first frame of the dash
+	pub direction: Option<Vec2>,
+}
+
+impl Dash {
+	pub fn new(speed_multiplier: f32, duration: f32, cooldown: f32) -> Self {
+		let mut duration = Timer::from_seconds(duration, false);
+		let mut cooldown = Timer::from_seconds(cooldown, false);
+
+		// Both timers start finished, so the player can dash right away
+		let elapsed = duration.duration();
+		duration.tick(elapsed);
+		let elapsed = cooldown.duration();
+		cooldown.tick(elapsed);
+
+		Self {
+			speed_multiplier,
+			duration,
+			cooldown,
+			direction: None,
+		}
+	}
+
+	pub fn is_dashing(&self) -> bool {
+		!self.duration.finished()
+	}
+
+	/// Seconds left until the dash can be used again
+	pub fn cooldown_remaining(&self) -> f32 {
+		self.cooldown.duration().as_secs_f32() - self.cooldown.elapsed_secs()
+	}
+}
+
+impl Default for Dash {
+	fn default() -> Self {
+		Self::new(DASH_SPEED_MULTIPLIER, DASH_DURATION, DASH_COOLDOWN)
+	}
+}
+
+pub fn player_dash(
+	mut player_query: Query<&mut Dash, With<Player>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	time: Res<TimeCounter>,
+) {
+	let mut dash = player_query.single_mut();
+
+	dash.duration.tick(time.delta());
+	dash.cooldown.tick(time.delta());
+
+	if !dash.is_dashing() {
+		dash.direction = None;
+	}
+
+	if keyboard.just_pressed(key_bindings.dash) && dash.cooldown.finished() {
+		dash.duration.reset();
+		dash.cooldown.reset();
+		dash.direction = None;
+	}
+}
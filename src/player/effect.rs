@@ -63,13 +63,17 @@ pub struct BigPowerup;
 impl Effect for BigPowerup {
 	fn apply(&self, movement: &mut Movement, _: &mut Health, shooting: &mut Shooting, time: &mut TimeCounter) {
 		movement.speed *= 3.0;
-		shooting.cooldown.set_duration(shooting.cooldown.duration() / 5);
+		for weapon in shooting.weapons_mut() {
+			weapon.cooldown.set_duration(weapon.cooldown.duration() / 5);
+		}
 		time.timescale /= 3.0;
 	}
 
 	fn finish(&self, movement: &mut Movement, _: &mut Health, shooting: &mut Shooting, time: &mut TimeCounter) {
 		movement.speed /= 3.0;
-		shooting.cooldown.set_duration(shooting.cooldown.duration() * 5);
+		for weapon in shooting.weapons_mut() {
+			weapon.cooldown.set_duration(weapon.cooldown.duration() * 5);
+		}
 		time.timescale *= 3.0;
 	}
 }
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+	audio::FootstepSounds,
+	audio_player::{AudioPlayer, PLAYER_MELEE_VOLUME},
+	bullet::ShotEvent,
+	key_bindings::KeyBindings,
+	line_of_sight::has_line_of_sight,
+	settings::Settings,
+	spatial_grid::EnemyGrid,
+	tilemap::TileCollider,
+	time::TimeCounter,
+	TILE_SIZE,
+};
+
+use super::Player;
+
+pub const MELEE_RANGE: f32 = 1.5 * TILE_SIZE;
+pub const MELEE_DAMAGE: f32 = 50.0;
+pub const MELEE_ARC_DEGREES: f32 = 120.0;
+pub const MELEE_COOLDOWN: f32 = 0.6;
+
+#[derive(Component)]
+pub struct Melee {
+	pub range: f32,
+	pub damage: f32,
+	/// Width of the swing, centered on the direction the player faces
+	pub arc_degrees: f32,
+	pub cooldown: Timer,
+}
+
+impl Default for Melee {
+	fn default() -> Self {
+		let mut cooldown = Timer::from_seconds(MELEE_COOLDOWN, false);
+
+		// Ready to swing right away
+		let elapsed = cooldown.duration();
+		cooldown.tick(elapsed);
+
+		Self {
+			range: MELEE_RANGE,
+			damage: MELEE_DAMAGE,
+			arc_degrees: MELEE_ARC_DEGREES,
+			cooldown,
+		}
+	}
+}
+
+pub fn player_melee(
+	mut player_query: Query<(&Transform, &mut Melee), With<Player>>,
+	enemy_grid: Res<EnemyGrid>,
+	walls: Query<(), With<TileCollider>>,
+	mut hit_event: EventWriter<ShotEvent>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	time: Res<TimeCounter>,
+	rapier_context: Res<RapierContext>,
+	audio: Res<Audio>,
+	settings: Res<Settings>,
+	footstep_sounds: Res<FootstepSounds>,
+) {
+	let (player_transform, mut melee) = player_query.single_mut();
+
+	melee.cooldown.tick(time.delta());
+
+	if !keyboard.just_pressed(key_bindings.melee) || !melee.cooldown.finished() {
+		return;
+	}
+
+	melee.cooldown.reset();
+
+	let position = player_transform.translation.truncate();
+	let facing = player_transform.up().truncate();
+
+	for (enemy, enemy_position) in enemy_grid.query_radius(position, melee.range) {
+		let offset = enemy_position - position;
+
+		if offset == Vec2::ZERO {
+			continue;
+		}
+
+		if facing.angle_between(offset).abs().to_degrees() > melee.arc_degrees / 2.0 {
+			continue;
+		}
+
+		// Only walls can block the swing, other enemies in the way get hit as well
+		if has_line_of_sight(&rapier_context, &walls, position, enemy_position) {
+			hit_event.send(ShotEvent(enemy, melee.damage, None, false));
+		}
+	}
+
+	// There is no dedicated swing sample, a footstep makes for a decent thud
+	AudioPlayer::play_sfx(
+		audio.as_ref(),
+		footstep_sounds
+			.choose(&mut rand::thread_rng())
+			.expect("No footstep sounds found.")
+			.clone(),
+		PLAYER_MELEE_VOLUME,
+		settings.as_ref(),
+	);
+}
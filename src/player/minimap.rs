@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::boss::Boss;
+use crate::enemy::Enemy;
+use crate::key_bindings::KeyBindings;
+use crate::tilemap::{Floor, MapBounds, Wall};
+use crate::TILE_SIZE;
+
+use super::Player;
+
+/// The longer side of the minimap, in pixels
+pub const MINIMAP_MAX_SIZE: f32 = 200.0;
+pub const MINIMAP_DOT_SIZE: f32 = 6.0;
+const MINIMAP_FLOOR_COLOR: [u8; 4] = [90, 90, 90, 180];
+const MINIMAP_WALL_COLOR: [u8; 4] = [230, 230, 230, 220];
+const MINIMAP_PLAYER_COLOR: Color = Color::rgb(0.2, 0.9, 0.3);
+const MINIMAP_ENEMY_COLOR: Color = Color::rgb(0.95, 0.04, 0.07);
+
+#[derive(Component, Default)]
+pub struct Minimap {
+	/// Size of the minimap on the screen, known once the level texture is drawn
+	size: Vec2,
+}
+
+/// Dot following a unit on the minimap
+#[derive(Component)]
+pub struct MinimapDot {
+	target: Entity,
+}
+
+/// Draws the level into a texture, one pixel per tile, once the tiles are there
+pub fn build_minimap(
+	mut minimap_query: Query<(&mut Minimap, &mut UiImage, &mut Style), Added<Minimap>>,
+	floors: Query<&Transform, With<Floor>>,
+	walls: Query<&Transform, With<Wall>>,
+	map_bounds: Res<MapBounds>,
+	mut images: ResMut<Assets<Image>>,
+) {
+	let (mut minimap, mut image, mut style) = match minimap_query.get_single_mut() {
+		Ok(minimap) => minimap,
+		Err(_) => return,
+	};
+
+	let map_size = map_bounds.max - map_bounds.min;
+	let width = (map_size.x / TILE_SIZE).round().max(1.0) as u32;
+	let height = (map_size.y / TILE_SIZE).round().max(1.0) as u32;
+
+	let mut data = vec![0; (width * height * 4) as usize];
+
+	let mut paint = |position: Vec3, color: [u8; 4]| {
+		let x = ((position.x - map_bounds.min.x) / TILE_SIZE) as u32;
+		// Texture rows go down, the world goes up
+		let y = ((map_bounds.max.y - position.y) / TILE_SIZE) as u32;
+
+		if x < width && y < height {
+			let index = ((y * width + x) * 4) as usize;
+			data[index..index + 4].copy_from_slice(&color);
+		}
+	};
+
+	for transform in floors.iter() {
+		paint(transform.translation, MINIMAP_FLOOR_COLOR);
+	}
+
+	// Painted after the floors, so walls placed over a floor tile still show up
+	for transform in walls.iter() {
+		paint(transform.translation, MINIMAP_WALL_COLOR);
+	}
+
+	image.0 = images.add(Image::new(
+		Extent3d {
+			width,
+			height,
+			depth_or_array_layers: 1,
+		},
+		TextureDimension::D2,
+		data,
+		TextureFormat::Rgba8UnormSrgb,
+	));
+
+	let scale = MINIMAP_MAX_SIZE / width.max(height) as f32;
+	minimap.size = Vec2::new(width as f32, height as f32) * scale;
+
+	style.size = Size::new(Val::Px(minimap.size.x), Val::Px(minimap.size.y));
+}
+
+pub fn spawn_minimap_dots(
+	mut commands: Commands,
+	minimap_query: Query<Entity, With<Minimap>>,
+	players: Query<Entity, Added<Player>>,
+	enemies: Query<Entity, Or<(Added<Enemy>, Added<Boss>)>>,
+) {
+	let minimap = match minimap_query.get_single() {
+		Ok(minimap) => minimap,
+		Err(_) => return,
+	};
+
+	// The player goes last, so that their dot is drawn over the enemies
+	let new_dots = enemies
+		.iter()
+		.map(|enemy| (enemy, MINIMAP_ENEMY_COLOR))
+		.chain(players.iter().map(|player| (player, MINIMAP_PLAYER_COLOR)));
+
+	for (target, color) in new_dots {
+		let dot = commands
+			.spawn_bundle(NodeBundle {
+				style: Style {
+					size: Size::new(Val::Px(MINIMAP_DOT_SIZE), Val::Px(MINIMAP_DOT_SIZE)),
+					position_type: PositionType::Absolute,
+					..Default::default()
+				},
+				color: color.into(),
+				..Default::default()
+			})
+			.insert(MinimapDot { target })
+			.insert(Name::new("MinimapDot"))
+			.id();
+
+		commands.entity(minimap).add_child(dot);
+	}
+}
+
+pub fn update_minimap_dots(
+	mut commands: Commands,
+	mut dots: Query<(Entity, &MinimapDot, &mut Style)>,
+	targets: Query<&Transform, Or<(With<Player>, With<Enemy>, With<Boss>)>>,
+	minimap_query: Query<&Minimap>,
+	map_bounds: Res<MapBounds>,
+) {
+	let minimap = match minimap_query.get_single() {
+		Ok(minimap) => minimap,
+		Err(_) => return,
+	};
+
+	let map_size = (map_bounds.max - map_bounds.min).max(Vec2::ONE);
+
+	for (entity, dot, mut style) in dots.iter_mut() {
+		let transform = match targets.get(dot.target) {
+			Ok(transform) => transform,
+			Err(_) => {
+				// Killed or despawned
+				commands.entity(entity).despawn_recursive();
+				continue;
+			}
+		};
+
+		let position = (transform.translation.truncate() - map_bounds.min) / map_size * minimap.size
+			- Vec2::splat(MINIMAP_DOT_SIZE / 2.0);
+
+		style.position = UiRect {
+			left: Val::Px(position.x),
+			bottom: Val::Px(position.y),
+			..Default::default()
+		};
+	}
+}
+
+pub fn toggle_minimap(
+	mut minimap_query: Query<&mut Style, (With<Minimap>, Without<MinimapDot>)>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+) {
+	if !keyboard.just_pressed(key_bindings.minimap) {
+		return;
+	}
+
+	for mut style in minimap_query.iter_mut() {
+		style.display = match style.display {
+			Display::None => Display::Flex,
+			_ => Display::None,
+		};
+	}
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::{lifetime::Lifetime, TILE_SIZE};
+
+pub const MUZZLE_FLASH_DURATION: f32 = 0.05;
+/// How far in front of the player the flash shows up
+pub const MUZZLE_FLASH_OFFSET: f32 = 0.8 * TILE_SIZE;
+pub const MUZZLE_FLASH_SIZE: Vec2 = Vec2::new(0.4 * TILE_SIZE, 0.5 * TILE_SIZE);
+
+#[derive(Component)]
+pub struct MuzzleFlash;
+
+#[derive(Bundle)]
+pub struct MuzzleFlashBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	muzzle_flash: MuzzleFlash,
+	lifetime: Lifetime,
+	name: Name,
+}
+
+impl MuzzleFlashBundle {
+	/// Flash in front of `position`, facing `direction`.
+	pub fn new(position: Vec3, direction: Vec2) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				sprite: Sprite {
+					color: Color::rgb(1.0, 0.85, 0.4),
+					custom_size: Some(MUZZLE_FLASH_SIZE),
+					..Default::default()
+				},
+				// Right above the player
+				transform: Transform::from_translation(
+					(position.truncate() + direction * MUZZLE_FLASH_OFFSET).extend(60.0),
+				)
+				.with_rotation(Quat::from_rotation_z(Vec2::Y.angle_between(direction))),
+				..Default::default()
+			},
+			muzzle_flash: MuzzleFlash,
+			lifetime: Lifetime::from_seconds(MUZZLE_FLASH_DURATION),
+			name: Name::new("MuzzleFlash"),
+		}
+	}
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::{time::TimeCounter, unit::Health};
+
+use super::Player;
+
+/// Health regenerated per second
+pub const REGEN_RATE: f32 = 4.0;
+/// Seconds without taking damage before the health starts coming back
+pub const REGEN_DELAY: f32 = 4.0;
+
+#[derive(Component)]
+pub struct Regen {
+	pub enabled: bool,
+	pub rate: f32,
+	pub time_since_damage: Timer,
+	/// Health during the last update, to notice when damage was taken
+	last_health: Option<f32>,
+}
+
+impl Regen {
+	pub fn new(rate: f32, delay: f32) -> Self {
+		Self {
+			enabled: true,
+			rate,
+			time_since_damage: Timer::from_seconds(delay, false),
+			last_health: None,
+		}
+	}
+}
+
+impl Default for Regen {
+	fn default() -> Self {
+		Self::new(REGEN_RATE, REGEN_DELAY)
+	}
+}
+
+pub fn player_regen(
+	mut player_query: Query<(&mut Regen, &mut Health), With<Player>>,
+	time: Res<TimeCounter>,
+) {
+	let (mut regen, mut health) = player_query.single_mut();
+
+	if !regen.enabled {
+		return;
+	}
+
+	// Catches every source of damage without each one having to know about regen
+	if regen.last_health.map_or(false, |last_health| health.get_health() < last_health) {
+		regen.time_since_damage.reset();
+	}
+
+	regen.time_since_damage.tick(time.delta());
+
+	if regen.time_since_damage.finished() {
+		let rate = regen.rate;
+		health.heal(rate * time.delta_seconds());
+	}
+
+	regen.last_health = Some(health.get_health());
+}
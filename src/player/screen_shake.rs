@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+use crate::time::TimeCounter;
+
+/// Trauma added for every shot the player fires
+pub const SHOT_TRAUMA: f32 = 0.15;
+/// Trauma added when the player gets hit
+pub const DAMAGE_TRAUMA: f32 = 0.5;
+/// Trauma lost per second
+pub const TRAUMA_DECAY: f32 = 1.5;
+/// Offset of the camera at full trauma, in pixels
+pub const MAX_SHAKE_OFFSET: f32 = 20.0;
+
+/// How shaken up the camera is, from 0 to 1
+#[derive(Default)]
+pub struct ScreenShake {
+	trauma: f32,
+}
+
+impl ScreenShake {
+	pub fn add_trauma(&mut self, amount: f32) {
+		self.trauma = (self.trauma + amount).min(1.0);
+	}
+
+	/// Camera offset for the current trauma.
+	/// Squaring the trauma makes small hits subtle and big ones violent.
+	pub fn offset(&self, time: f32) -> Vec2 {
+		let shake = self.trauma * self.trauma * MAX_SHAKE_OFFSET;
+
+		Vec2::new(noise(time, 0.0), noise(time, 100.0)) * shake
+	}
+}
+
+/// Cheap smooth noise in -1..1, made from a few sines that don't line up
+fn noise(time: f32, seed: f32) -> f32 {
+	let t = time + seed;
+
+	((t * 37.0).sin() + (t * 53.0).sin() * 0.5 + (t * 71.0).sin() * 0.25) / 1.75
+}
+
+pub fn decay_screen_shake(mut screen_shake: ResMut<ScreenShake>, time: Res<TimeCounter>) {
+	screen_shake.trauma = (screen_shake.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.0);
+}
+
+pub fn reset_screen_shake(mut screen_shake: ResMut<ScreenShake>) {
+	screen_shake.trauma = 0.0;
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::time::TimeCounter;
+
+use super::Player;
+
+pub const SHIELD_CAPACITY: f32 = 40.0;
+/// Shield regenerated per second
+pub const SHIELD_REGEN_RATE: f32 = 10.0;
+/// Seconds without taking damage before the shield starts coming back
+pub const SHIELD_REGEN_DELAY: f32 = 3.0;
+/// Longer delay after the shield broke, so that it can't be refilled right away
+pub const SHIELD_BREAK_DELAY: f32 = 6.0;
+
+/// Takes hits in place of the health until it runs out
+#[derive(Component)]
+pub struct Shield {
+	amount: f32,
+	max_amount: f32,
+	pub rate: f32,
+	pub delay: f32,
+	pub break_delay: f32,
+	regen_delay: Timer,
+}
+
+impl Shield {
+	pub fn new(max_amount: f32, rate: f32, delay: f32, break_delay: f32) -> Self {
+		Self {
+			amount: max_amount,
+			max_amount,
+			rate,
+			delay,
+			break_delay,
+			regen_delay: Timer::from_seconds(delay, false),
+		}
+	}
+
+	/// Soaks up as much of the damage as there's shield left.
+	///
+	/// # Returns
+	/// The damage that got through and whether this hit broke the shield.
+	pub fn absorb(&mut self, damage: f32) -> (f32, bool) {
+		let absorbed = damage.min(self.amount);
+		let broke = self.amount > 0.0 && absorbed >= self.amount;
+
+		self.amount -= absorbed;
+
+		// Hits taken while broken mustn't cut the break delay short
+		let delay = if broke { self.break_delay } else { self.delay };
+		let remaining = self.regen_delay.duration().as_secs_f32() - self.regen_delay.elapsed_secs();
+
+		if delay >= remaining {
+			self.regen_delay = Timer::from_seconds(delay, false);
+		}
+
+		(damage - absorbed, broke)
+	}
+
+	pub fn get_amount(&self) -> f32 {
+		self.amount
+	}
+
+	pub fn get_max_amount(&self) -> f32 {
+		self.max_amount
+	}
+}
+
+impl Default for Shield {
+	fn default() -> Self {
+		Self::new(SHIELD_CAPACITY, SHIELD_REGEN_RATE, SHIELD_REGEN_DELAY, SHIELD_BREAK_DELAY)
+	}
+}
+
+pub fn shield_regen(mut player_query: Query<&mut Shield, With<Player>>, time: Res<TimeCounter>) {
+	let mut shield = player_query.single_mut();
+
+	shield.regen_delay.tick(time.delta());
+
+	if shield.regen_delay.finished() {
+		shield.amount = (shield.amount + shield.rate * time.delta_seconds()).min(shield.max_amount);
+	}
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use crate::{key_bindings::KeyBindings, time::TimeCounter, unit::Stamina};
+
+use super::Player;
+
+pub const SPRINT_SPEED_MULTIPLIER: f32 = 1.6;
+/// Stamina drained per second of sprinting
+pub const SPRINT_DRAIN_RATE: f32 = 35.0;
+/// Fraction of the max stamina needed to sprint again after running out
+pub const SPRINT_REENABLE_THRESHOLD: f32 = 0.25;
+
+#[derive(Component)]
+pub struct Sprint {
+	pub speed_multiplier: f32,
+	pub drain_rate: f32,
+	active: bool,
+	exhausted: bool,
+}
+
+impl Sprint {
+	pub fn is_sprinting(&self) -> bool {
+		self.active
+	}
+
+	/// Factor applied to the movement speed
+	pub fn speed_multiplier(&self) -> f32 {
+		if self.active {
+			self.speed_multiplier
+		} else {
+			1.0
+		}
+	}
+}
+
+impl Default for Sprint {
+	fn default() -> Self {
+		Self {
+			speed_multiplier: SPRINT_SPEED_MULTIPLIER,
+			drain_rate: SPRINT_DRAIN_RATE,
+			active: false,
+			exhausted: false,
+		}
+	}
+}
+
+pub fn player_sprint(
+	mut player_query: Query<(&mut Sprint, &mut Stamina), With<Player>>,
+	keyboard: Res<Input<KeyCode>>,
+	key_bindings: Res<KeyBindings>,
+	time: Res<TimeCounter>,
+) {
+	let (mut sprint, mut stamina) = player_query.single_mut();
+
+	// Once exhausted, wait for some stamina to come back, instead of flickering at zero
+	if sprint.exhausted
+		&& stamina.get_stamina() >= stamina.get_max_stamina() * SPRINT_REENABLE_THRESHOLD
+	{
+		sprint.exhausted = false;
+	}
+
+	sprint.active = keyboard.pressed(key_bindings.sprint) && !sprint.exhausted;
+
+	if sprint.active && stamina.drain(sprint.drain_rate * time.delta_seconds()) {
+		sprint.exhausted = true;
+	}
+}
+
+pub fn regenerate_stamina(
+	mut player_query: Query<(&Sprint, &mut Stamina), With<Player>>,
+	time: Res<TimeCounter>,
+) {
+	let (sprint, mut stamina) = player_query.single_mut();
+
+	if !sprint.is_sprinting() {
+		let regen_rate = stamina.regen_rate;
+		stamina.regenerate(regen_rate * time.delta_seconds());
+	}
+}
@@ -1,15 +1,77 @@
 use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
-use crate::{unit::{Health, Inventory}, fonts::{PaintFont, RobotoFont}, stats::Stats};
+use crate::{door::KeyColor, unit::{Health, Inventory}, fonts::{PaintFont, RobotoFont}, settings::Settings, stats::{Score, Stats}, time::TimeCounter, wave::WaveSpawner};
 
-use super::{Player, effect::EffectData};
+use super::{Player, bullet_time::BulletTime, dash::Dash, effect::EffectData, minimap::Minimap, shield::Shield};
 
+/// Health bar colors, it fades from full to mid above the threshold and from mid to low below it
+pub const HEALTH_FULL_COLOR: Color = Color::rgb(0.1, 0.8, 0.2);
+pub const HEALTH_MID_COLOR: Color = Color::rgb(0.95, 0.85, 0.1);
+pub const HEALTH_LOW_COLOR: Color = Color::rgb(0.95, 0.04, 0.07);
+/// Share of the max health at which the bar is fully `HEALTH_MID_COLOR`
+pub const HEALTH_MID_THRESHOLD: f32 = 0.5;
+/// How quickly the health bar catches up with the health, higher is snappier
+pub const HEALTH_BAR_SMOOTHING: f32 = 15.0;
+/// Seconds the ghost bar stays put after a hit before it starts draining
+pub const GHOST_BAR_DELAY: f32 = 0.5;
+/// Share of the whole bar the ghost bar drains every second
+pub const GHOST_BAR_DRAIN_SPEED: f32 = 0.6;
+pub const GHOST_BAR_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+/// Closer than this, the animated bars snap onto the value they're heading for
+const BAR_SNAP_DISTANCE: f32 = 0.001;
+/// Share of the max health below which the screen edges start turning red
+pub const VIGNETTE_HEALTH_THRESHOLD: f32 = 0.35;
+/// Opacity of the vignette at zero health
+pub const VIGNETTE_MAX_ALPHA: f32 = 0.8;
+/// Pulses per second
+pub const VIGNETTE_PULSE_SPEED: f32 = 1.5;
+/// Share of the opacity a pulse takes away at its lowest
+pub const VIGNETTE_PULSE_DEPTH: f32 = 0.35;
+/// The vignette texture is generated, it gets stretched over the whole screen anyway
+const VIGNETTE_TEXTURE_SIZE: u32 = 128;
+/// Distance from the center, where the corners are at about 1.4, at which the red starts showing
+const VIGNETTE_INNER_RADIUS: f32 = 0.55;
 
 #[derive(Component)]
 pub struct PlayerUi;
 
+/// Eases its width towards the player's health instead of jumping to it
 #[derive(Component)]
-pub struct HealthBar;
+pub struct HealthBar {
+    displayed: f32,
+}
+
+impl Default for HealthBar {
+    fn default() -> Self {
+        Self { displayed: 1.0 }
+    }
+}
+
+/// Trails behind the health bar to show how much health was lost recently
+#[derive(Component)]
+pub struct GhostHealthBar {
+    displayed: f32,
+    last_health: f32,
+    delay: Timer,
+}
+
+impl Default for GhostHealthBar {
+    fn default() -> Self {
+        Self {
+            displayed: 1.0,
+            last_health: 1.0,
+            delay: Timer::from_seconds(GHOST_BAR_DELAY, false),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ShieldBar;
+
+/// Red screen edges shown when the player is close to death
+#[derive(Component)]
+pub struct LowHealthVignette;
 
 #[derive(Component)]
 pub struct SmallPowerUpCounterNumber;
@@ -26,11 +88,60 @@ pub struct PowerupBar;
 #[derive(Component)]
 pub struct LevelTimerUI;
 
+#[derive(Component)]
+pub struct ScoreUI;
+
+#[derive(Component)]
+pub struct WaveUI;
 
-pub fn ui_setup(mut commands: Commands, font: Res<PaintFont>, roboto_font: Res<RobotoFont>,) {
+#[derive(Component)]
+pub struct KeyUI;
+
+#[derive(Component)]
+pub struct AbilityUI;
+
+
+/// Transparent in the middle, getting more and more red towards the edges
+fn vignette_image() -> Image {
+    let size = VIGNETTE_TEXTURE_SIZE;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+
+    for y in 0..size {
+        for x in 0..size {
+            let position = (Vec2::new(x as f32, y as f32) + 0.5) / size as f32 * 2.0 - Vec2::ONE;
+            let t = ((position.length() - VIGNETTE_INNER_RADIUS) / (2.0_f32.sqrt() - VIGNETTE_INNER_RADIUS))
+                .clamp(0.0, 1.0);
+
+            // Smoothstep, so that there's no visible edge where the red starts
+            let alpha = t * t * (3.0 - 2.0 * t);
+
+            data.extend_from_slice(&[255, 0, 0, (alpha * 255.0) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+pub fn ui_setup(
+    mut commands: Commands,
+    font: Res<PaintFont>,
+    roboto_font: Res<RobotoFont>,
+    mut images: ResMut<Assets<Image>>,
+) {
     let font = &font.0;
     let roboto_font = &roboto_font.0;
 
+    let vignette = images.add(vignette_image());
+
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -44,6 +155,26 @@ pub fn ui_setup(mut commands: Commands, font: Res<PaintFont>, roboto_font: Res<R
         .insert(Name::new("UI"))
         .insert(PlayerUi)
         .with_children(|parent| {
+            // Spawned first so that the rest of the UI is drawn over it
+            parent
+                .spawn_bundle(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(0.0),
+                            bottom: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    image: UiImage(vignette),
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("LowHealthVignette"))
+                .insert(LowHealthVignette);
+
             parent
                 .spawn_bundle(NodeBundle {
                     style: Style {
@@ -89,6 +220,7 @@ pub fn ui_setup(mut commands: Commands, font: Res<PaintFont>, roboto_font: Res<R
                                 })
                                 .insert(Name::new("HealthBarContainer"))
                                 .with_children(|parent| {
+                                    // Spawned first so that the health bar is drawn over it
                                     parent
                                         .spawn_bundle(NodeBundle {
                                             style: Style {
@@ -96,13 +228,69 @@ pub fn ui_setup(mut commands: Commands, font: Res<PaintFont>, roboto_font: Res<R
                                                     Val::Percent(100.0),
                                                     Val::Percent(100.0),
                                                 ),
+                                                position_type: PositionType::Absolute,
+                                                position: UiRect {
+                                                    left: Val::Px(0.0),
+                                                    bottom: Val::Px(0.0),
+                                                    ..Default::default()
+                                                },
                                                 ..Default::default()
                                             },
-                                            color: Color::rgb(0.95, 0.04, 0.07).into(),
+                                            color: GHOST_BAR_COLOR.into(),
+                                            ..Default::default()
+                                        })
+                                        .insert(Name::new("GhostHealthBar"))
+                                        .insert(GhostHealthBar::default());
+
+                                    parent
+                                        .spawn_bundle(NodeBundle {
+                                            style: Style {
+                                                size: Size::new(
+                                                    Val::Percent(100.0),
+                                                    Val::Percent(100.0),
+                                                ),
+                                                ..Default::default()
+                                            },
+                                            color: HEALTH_FULL_COLOR.into(),
                                             ..Default::default()
                                         })
                                         .insert(Name::new("HealthBar"))
-                                        .insert(HealthBar);
+                                        .insert(HealthBar::default());
+                                });
+
+                            // Column goes upwards, so this ends up above the health bar
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Percent(100.0), Val::Px(20.0)),
+                                        padding: UiRect::all(Val::Px(5.0)),
+                                        margin: UiRect::new(
+                                            Val::Px(0.0),
+                                            Val::Px(0.0),
+                                            Val::Px(0.0),
+                                            Val::Px(5.0),
+                                        ),
+                                        ..Default::default()
+                                    },
+                                    color: Color::BLACK.into(),
+                                    ..Default::default()
+                                })
+                                .insert(Name::new("ShieldBarContainer"))
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn_bundle(NodeBundle {
+                                            style: Style {
+                                                size: Size::new(
+                                                    Val::Percent(100.0),
+                                                    Val::Percent(100.0),
+                                                ),
+                                                ..Default::default()
+                                            },
+                                            color: Color::rgb(0.3, 0.75, 1.0).into(),
+                                            ..Default::default()
+                                        })
+                                        .insert(Name::new("ShieldBar"))
+                                        .insert(ShieldBar);
                                 });
                         });
 
@@ -302,6 +490,106 @@ pub fn ui_setup(mut commands: Commands, font: Res<PaintFont>, roboto_font: Res<R
                     )
                     .insert(Name::new("Timer"))
                     .insert(LevelTimerUI);
+
+                    parent.spawn_bundle(
+                        TextBundle::from_section(
+                            "0",
+                            TextStyle {
+                                font: roboto_font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        ).with_style(
+                            Style {
+                                size: Size::new(Val::Auto, Val::Auto),
+                                ..Default::default()
+                            }
+                        )
+                    )
+                    .insert(Name::new("Score"))
+                    .insert(ScoreUI);
+
+                    parent.spawn_bundle(
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: roboto_font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        ).with_style(
+                            Style {
+                                size: Size::new(Val::Auto, Val::Auto),
+                                ..Default::default()
+                            }
+                        )
+                    )
+                    .insert(Name::new("Wave"))
+                    .insert(WaveUI);
+
+                    // One section for every key color, filled in by update_key_ui
+                    parent.spawn_bundle(
+                        TextBundle::from_sections(KeyColor::ALL.iter().map(|key_color| {
+                            TextSection::new(
+                                "",
+                                TextStyle {
+                                    font: roboto_font.clone(),
+                                    font_size: 32.0,
+                                    color: key_color.color(),
+                                },
+                            )
+                        }))
+                        .with_style(
+                            Style {
+                                size: Size::new(Val::Auto, Val::Auto),
+                                ..Default::default()
+                            }
+                        )
+                    )
+                    .insert(Name::new("Keys"))
+                    .insert(KeyUI);
+
+                    let ability_section = || {
+                        TextSection::new(
+                            "",
+                            TextStyle {
+                                font: roboto_font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                    };
+
+                    // One section for every ability with a cooldown, filled in by update_ability_ui
+                    parent.spawn_bundle(
+                        TextBundle::from_sections([ability_section(), ability_section()])
+                        .with_style(
+                            Style {
+                                size: Size::new(Val::Auto, Val::Auto),
+                                ..Default::default()
+                            }
+                        )
+                    )
+                    .insert(Name::new("Abilities"))
+                    .insert(AbilityUI);
+
+                    // Gets its texture and size once the level is loaded
+                    parent
+                        .spawn_bundle(ImageBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(0.0), Val::Px(0.0)),
+                                margin: UiRect::new(
+                                    Val::Px(0.0),
+                                    Val::Px(0.0),
+                                    Val::Px(10.0),
+                                    Val::Px(0.0),
+                                ),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .insert(Name::new("Minimap"))
+                        .insert(Minimap::default());
                 });
         });
 }
@@ -312,14 +600,14 @@ pub fn drop_ui(mut commands: Commands, ui_query: Query<Entity, With<PlayerUi>>)
 }
 
 pub fn update_ui(
-    player_query: Query<(&Health, &Inventory, &EffectData), With<Player>>,
-    mut health_bar_query: Query<&mut Style, With<HealthBar>>,
+    player_query: Query<(&Inventory, &EffectData), With<Player>>,
     mut small_powerup_counter_query: Query<
         &mut Text,
         (
             With<SmallPowerUpCounterNumber>,
             Without<BigPowerUpCounterNumber>,
             Without<LevelTimerUI>,
+            Without<ScoreUI>,
         ),
     >,
     mut big_powerup_counter_query: Query<
@@ -328,6 +616,7 @@ pub fn update_ui(
             With<BigPowerUpCounterNumber>,
             Without<SmallPowerUpCounterNumber>,
             Without<LevelTimerUI>,
+            Without<ScoreUI>,
         ),
     >,
     mut powerup_bar_container_query: Query<
@@ -348,15 +637,22 @@ pub fn update_ui(
         With<LevelTimerUI>,
         Without<SmallPowerUpCounterNumber>,
         Without<BigPowerUpCounterNumber>,
+        Without<ScoreUI>,
     )
     >,
+    mut score_ui_query: Query<
+        &mut Text,
+        (
+            With<ScoreUI>,
+            Without<SmallPowerUpCounterNumber>,
+            Without<BigPowerUpCounterNumber>,
+            Without<LevelTimerUI>,
+        ),
+    >,
     stats: Res<Stats>,
+    score: Res<Score>,
 ) {
-    let (player_health, inventory, effect_data) = player_query.single();
-
-    let mut health_bar_style = health_bar_query.single_mut();
-    health_bar_style.size.width =
-        Val::Percent(player_health.get_health() / player_health.get_max_health() * 100.0);
+    let (inventory, effect_data) = player_query.single();
 
     let mut small_powerup_counter = small_powerup_counter_query.single_mut();
     small_powerup_counter.sections[0].value = inventory.get_small_powerup_quantity().to_string();
@@ -381,4 +677,163 @@ pub fn update_ui(
 
     let mut level_timer_ui = level_timer_ui_query.single_mut();
     level_timer_ui.sections[0].value = format!("{:.2}", stats.timer.elapsed_secs());
+
+    let mut score_ui = score_ui_query.single_mut();
+    score_ui.sections[0].value = score.points.to_string();
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+
+    Color::rgb(
+        from.r() + (to.r() - from.r()) * t,
+        from.g() + (to.g() - from.g()) * t,
+        from.b() + (to.b() - from.b()) * t,
+    )
+}
+
+/// Goes from green at full health through yellow to red when close to death
+fn health_color(health_fraction: f32) -> Color {
+    if health_fraction >= HEALTH_MID_THRESHOLD {
+        let t = (health_fraction - HEALTH_MID_THRESHOLD) / (1.0 - HEALTH_MID_THRESHOLD);
+        lerp_color(HEALTH_MID_COLOR, HEALTH_FULL_COLOR, t)
+    } else {
+        lerp_color(HEALTH_LOW_COLOR, HEALTH_MID_COLOR, health_fraction / HEALTH_MID_THRESHOLD)
+    }
+}
+
+/// Moves towards the target by the given share of the gap, landing exactly on it once it's close enough
+fn approach(current: f32, target: f32, t: f32) -> f32 {
+    let next = current + (target - current) * t;
+
+    if (target - next).abs() < BAR_SNAP_DISTANCE {
+        target
+    } else {
+        next
+    }
+}
+
+pub fn update_health_bar(
+    player_query: Query<&Health, With<Player>>,
+    mut health_bar_query: Query<(&mut Style, &mut UiColor, &mut HealthBar)>,
+    mut ghost_bar_query: Query<(&mut Style, &mut GhostHealthBar), Without<HealthBar>>,
+    time: Res<TimeCounter>,
+) {
+    let player_health = player_query.single();
+    let health = (player_health.get_health() / player_health.get_max_health()).clamp(0.0, 1.0);
+
+    // On real time, so that the bar doesn't crawl during bullet time
+    let delta = time.real_delta_seconds();
+
+    let (mut health_bar_style, mut health_bar_color, mut health_bar) = health_bar_query.single_mut();
+
+    // Exponential smoothing can't overshoot, it only needs the snap to ever arrive
+    let t = 1.0 - (-HEALTH_BAR_SMOOTHING * delta).exp();
+    health_bar.displayed = approach(health_bar.displayed, health, t);
+
+    health_bar_style.size.width = Val::Percent(health_bar.displayed * 100.0);
+    *health_bar_color = health_color(health_bar.displayed).into();
+
+    let (mut ghost_bar_style, mut ghost_bar) = ghost_bar_query.single_mut();
+
+    // Every new hit holds the ghost bar in place for a bit longer
+    if health < ghost_bar.last_health {
+        ghost_bar.delay.reset();
+    }
+    ghost_bar.last_health = health;
+
+    ghost_bar.delay.tick(time.real_delta());
+
+    if ghost_bar.delay.finished() {
+        ghost_bar.displayed = (ghost_bar.displayed - GHOST_BAR_DRAIN_SPEED * delta).max(health);
+    }
+
+    // Healing has nothing to trail behind, and the ghost is never shorter than the bar in front of it
+    ghost_bar.displayed = ghost_bar.displayed.max(health_bar.displayed);
+
+    ghost_bar_style.size.width = Val::Percent(ghost_bar.displayed * 100.0);
+}
+
+pub fn update_low_health_vignette(
+    player_query: Query<&Health, With<Player>>,
+    mut vignette_query: Query<&mut UiColor, With<LowHealthVignette>>,
+    settings: Res<Settings>,
+    time: Res<TimeCounter>,
+) {
+    let player_health = player_query.single();
+    let health = player_health.get_health() / player_health.get_max_health();
+
+    // Nothing above the threshold, full strength at zero health
+    let intensity = ((VIGNETTE_HEALTH_THRESHOLD - health) / VIGNETTE_HEALTH_THRESHOLD).clamp(0.0, 1.0);
+
+    let pulse = if settings.reduce_motion {
+        1.0
+    } else {
+        let wave = (time.seconds_since_startup() * VIGNETTE_PULSE_SPEED * std::f32::consts::TAU).sin() * 0.5 + 0.5;
+        1.0 - VIGNETTE_PULSE_DEPTH * wave
+    };
+
+    let mut vignette_color = vignette_query.single_mut();
+    *vignette_color = Color::rgba(1.0, 1.0, 1.0, intensity * pulse * VIGNETTE_MAX_ALPHA).into();
+}
+
+pub fn update_wave_ui(mut wave_ui_query: Query<&mut Text, With<WaveUI>>, spawner: Res<WaveSpawner>) {
+    let mut wave_ui = wave_ui_query.single_mut();
+
+    // Stays empty until the first wave shows up, so levels without waves don't show it
+    wave_ui.sections[0].value = if spawner.current_wave > 0 {
+        format!("Wave {}/{}", spawner.current_wave, spawner.wave_count())
+    } else {
+        String::new()
+    };
+}
+
+pub fn update_key_ui(
+    player_query: Query<&Inventory, With<Player>>,
+    mut key_ui_query: Query<&mut Text, With<KeyUI>>,
+) {
+    let inventory = player_query.single();
+    let mut key_ui = key_ui_query.single_mut();
+
+    // Colors with no keys are left out, so nothing shows until the first key is picked up
+    for (section, key_color) in key_ui.sections.iter_mut().zip(KeyColor::ALL) {
+        let count = inventory.get_key_quantity(key_color);
+
+        section.value = if count > 0 {
+            format!(" {:?} key x{}", key_color, count)
+        } else {
+            String::new()
+        };
+    }
+}
+
+pub fn update_shield_ui(
+    player_query: Query<&Shield, With<Player>>,
+    mut shield_bar_query: Query<&mut Style, With<ShieldBar>>,
+) {
+    let shield = player_query.single();
+
+    let mut shield_bar_style = shield_bar_query.single_mut();
+    shield_bar_style.size.width =
+        Val::Percent(shield.get_amount() / shield.get_max_amount() * 100.0);
+}
+
+/// The ability's name with the seconds left on its cooldown, or nothing once it's ready
+fn cooldown_text(name: &str, remaining: f32) -> String {
+    if remaining > 0.0 {
+        format!(" {} {:.1}s", name, remaining)
+    } else {
+        String::new()
+    }
+}
+
+pub fn update_ability_ui(
+    player_query: Query<(&Dash, &BulletTime), With<Player>>,
+    mut ability_ui_query: Query<&mut Text, With<AbilityUI>>,
+) {
+    let (dash, bullet_time) = player_query.single();
+    let mut ability_ui = ability_ui_query.single_mut();
+
+    ability_ui.sections[0].value = cooldown_text("Dash", dash.cooldown_remaining());
+    ability_ui.sections[1].value = cooldown_text("Bullet time", bullet_time.cooldown_remaining());
 }
@@ -0,0 +1,98 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::bullet::Bullet;
+use crate::particle::Particle;
+use crate::GameState;
+
+/// Entities of one kind that aren't in use, kept around to be reused instead of despawning them
+/// and spawning new ones all the time. The kind is told apart by its `T` component.
+pub struct Pool<T: Component> {
+	free: Vec<Entity>,
+	marker: PhantomData<T>,
+}
+
+impl<T: Component> Default for Pool<T> {
+	fn default() -> Self {
+		Self {
+			free: Vec::new(),
+			marker: PhantomData,
+		}
+	}
+}
+
+impl<T: Component> Pool<T> {
+	/// Puts the bundle on a free entity, or on a new one parented to `parent` when there are none left.
+	/// The bundle needs to contain `T`, so that the entity counts as in use again.
+	pub fn spawn(&mut self, commands: &mut Commands, parent: Entity, bundle: impl Bundle) -> Entity {
+		match self.free.pop() {
+			Some(entity) => {
+				commands.entity(entity).insert_bundle(bundle);
+				entity
+			}
+			None => {
+				let entity = commands.spawn_bundle(bundle).id();
+				commands.entity(parent).add_child(entity);
+				entity
+			}
+		}
+	}
+
+	/// Hides the entity and takes its `T` away, so that the systems working on it skip it until it's reused.
+	pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+		commands
+			.entity(entity)
+			.remove::<T>()
+			.insert(Visibility { is_visible: false });
+
+		self.free.push(entity);
+	}
+
+	/// Only the tests need to look into the pool
+	#[cfg(test)]
+	pub fn free_count(&self) -> usize {
+		self.free.len()
+	}
+
+	/// Forgets the free entities, for when they get despawned together with the level.
+	pub fn clear(&mut self) {
+		self.free.clear();
+	}
+}
+
+/// All of the game's pools at once, for the systems that throw away the level the pooled entities live under.
+#[derive(SystemParam)]
+pub struct Pools<'w, 's> {
+	bullets: ResMut<'w, Pool<Bullet>>,
+	particles: ResMut<'w, Pool<Particle>>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
+impl Pools<'_, '_> {
+	/// Forgets the free entities of every pool.
+	pub fn clear(&mut self) {
+		self.bullets.clear();
+		self.particles.clear();
+	}
+}
+
+pub struct PoolPlugin;
+
+impl Plugin for PoolPlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(Pool::<Bullet>::default())
+			.insert_resource(Pool::<Particle>::default())
+			.add_system_set(
+				SystemSet::on_exit(GameState::Game)
+					.with_system(clear_pool::<Bullet>)
+					.with_system(clear_pool::<Particle>),
+			);
+	}
+}
+
+fn clear_pool<T: Component>(mut pool: ResMut<Pool<T>>) {
+	pool.clear();
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::player::{blaster, burst_pistol, pistol, railgun, shotgun};
+use crate::unit::Weapon;
+
+/// How good a dropped weapon is, the rarer the better its stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rarity {
+	Common,
+	Uncommon,
+	Rare,
+	Epic,
+	Legendary,
+}
+
+impl Rarity {
+	pub const ALL: [Rarity; 5] = [
+		Rarity::Common,
+		Rarity::Uncommon,
+		Rarity::Rare,
+		Rarity::Epic,
+		Rarity::Legendary,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Rarity::Common => "Common",
+			Rarity::Uncommon => "Uncommon",
+			Rarity::Rare => "Rare",
+			Rarity::Epic => "Epic",
+			Rarity::Legendary => "Legendary",
+		}
+	}
+
+	/// Tint of the weapon's pickup and name
+	pub fn color(&self) -> Color {
+		match self {
+			Rarity::Common => Color::rgb(0.85, 0.85, 0.85),
+			Rarity::Uncommon => Color::rgb(0.35, 0.9, 0.35),
+			Rarity::Rare => Color::rgb(0.3, 0.55, 1.0),
+			Rarity::Epic => Color::rgb(0.7, 0.35, 1.0),
+			Rarity::Legendary => Color::rgb(1.0, 0.6, 0.1),
+		}
+	}
+
+	/// Weight of the rarity coming up in a drop
+	fn weight(&self) -> u32 {
+		match self {
+			Rarity::Common => 50,
+			Rarity::Uncommon => 25,
+			Rarity::Rare => 15,
+			Rarity::Epic => 8,
+			Rarity::Legendary => 2,
+		}
+	}
+
+	/// Lowest and highest multiplier of the weapon's damage, fire rate and range
+	pub fn stat_multipliers(&self) -> (f32, f32) {
+		match self {
+			Rarity::Common => (0.85, 1.0),
+			Rarity::Uncommon => (1.0, 1.15),
+			Rarity::Rare => (1.15, 1.3),
+			Rarity::Epic => (1.3, 1.5),
+			Rarity::Legendary => (1.5, 1.8),
+		}
+	}
+
+	/// A rarity picked by weight, the common ones come up the most.
+	pub fn roll(rng: &mut impl Rng) -> Self {
+		*Self::ALL
+			.choose_weighted(rng, |rarity| rarity.weight())
+			.expect("Every rarity has a weight!")
+	}
+}
+
+/// One of the player's weapons with its stats scaled by the rarity.
+/// Each stat is rolled on its own within the rarity's range, so two weapons of the same rarity can differ.
+pub fn generate_weapon(rarity: Rarity, rng: &mut impl Rng) -> Weapon {
+	let mut weapon = match rng.gen_range(0..5) {
+		0 => shotgun(),
+		1 => pistol(),
+		2 => burst_pistol(),
+		3 => railgun(),
+		_ => blaster(),
+	};
+
+	let (min, max) = rarity.stat_multipliers();
+	let damage_multiplier = rng.gen_range(min..=max);
+	let fire_rate_multiplier = rng.gen_range(min..=max);
+	let range_multiplier = rng.gen_range(min..=max);
+
+	weapon.damage *= damage_multiplier;
+	weapon.cooldown = Timer::from_seconds(
+		weapon.cooldown.duration().as_secs_f32() / fire_rate_multiplier,
+		false,
+	);
+	weapon.range *= range_multiplier;
+
+	// The falloff stretches along with the range
+	if let Some(falloff) = weapon.falloff.as_mut() {
+		falloff.effective_range *= range_multiplier;
+		falloff.max_range = weapon.range;
+	}
+
+	weapon.rarity = rarity;
+	weapon
+}
@@ -0,0 +1,219 @@
+use std::env;
+use std::hash::Hash;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use rand::random;
+use serde::{Deserialize, Serialize};
+
+use crate::player::{Aim, AimDevice, Player};
+use crate::rng::GameRng;
+use crate::save;
+use crate::tilemap::LevelSource;
+use crate::time::TimeCounter;
+use crate::GameState;
+
+/// File in the save directory replays are written to and played back from
+pub const REPLAY_FILE: &str = "replay.ron";
+
+/// Picked with the `--record` or `--replay` command line flag.
+/// Recording starts when a game starts and is saved when it ends, the replay plays back the last recording.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+	Off,
+	Record,
+	Replay,
+}
+
+impl ReplayMode {
+	fn from_args() -> Self {
+		let mut mode = Self::Off;
+
+		for argument in env::args() {
+			match argument.as_str() {
+				"--record" => mode = Self::Record,
+				"--replay" => mode = Self::Replay,
+				_ => {}
+			}
+		}
+
+		mode
+	}
+}
+
+/// Everything the gameplay depends on during one frame
+#[derive(Serialize, Deserialize)]
+struct ReplayFrame {
+	delta: f32,
+	keys: Vec<KeyCode>,
+	mouse_buttons: Vec<MouseButton>,
+	/// The aim comes from the cursor of the real window, so its result is stored instead
+	aim: Option<(f32, f32)>,
+}
+
+/// A recorded game, which plays out the same way when given the same seed, level and input
+#[derive(Default, Serialize, Deserialize)]
+pub struct Replay {
+	seed: u64,
+	level: Option<LevelSource>,
+	frames: Vec<ReplayFrame>,
+	#[serde(skip)]
+	next_frame: usize,
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(ReplayMode::from_args())
+			.insert_resource(Replay::default())
+			.add_system_to_stage(
+				CoreStage::PreUpdate,
+				replay_frame.after(InputSystem).after("update_time"),
+			)
+			.add_system_set(
+				SystemSet::on_enter(GameState::Game).with_system(start_replay.before("load_level")),
+			)
+			.add_system_set(
+				SystemSet::on_update(GameState::Game)
+					.with_system(replay_aim.after("player_aim").before("player_shoot")),
+			)
+			.add_system_set(SystemSet::on_exit(GameState::Game).with_system(finish_replay));
+	}
+}
+
+fn start_replay(
+	mode: Res<ReplayMode>,
+	mut replay: ResMut<Replay>,
+	mut rng: ResMut<GameRng>,
+	mut level_source: ResMut<LevelSource>,
+) {
+	match *mode {
+		ReplayMode::Off => return,
+		ReplayMode::Record => {
+			*replay = Replay {
+				seed: random(),
+				level: Some(*level_source),
+				..Default::default()
+			};
+
+			info!("Recording a replay");
+		}
+		ReplayMode::Replay => {
+			*replay = save::load(REPLAY_FILE);
+
+			if let Some(level) = replay.level {
+				*level_source = level;
+			}
+
+			info!("Playing back a replay of {} frames", replay.frames.len());
+		}
+	}
+
+	rng.reseed(replay.seed);
+}
+
+/// Records the input of a game frame, or puts the recorded input in place of the real one.
+/// Runs right after the input and the game clock get updated, before any gameplay system reads them.
+fn replay_frame(
+	mut mode: ResMut<ReplayMode>,
+	mut replay: ResMut<Replay>,
+	state: Res<State<GameState>>,
+	mut keyboard: ResMut<Input<KeyCode>>,
+	mut mouse_buttons: ResMut<Input<MouseButton>>,
+	mut time: ResMut<TimeCounter>,
+) {
+	if *state.current() != GameState::Game {
+		return;
+	}
+
+	match *mode {
+		ReplayMode::Off => {}
+		ReplayMode::Record => {
+			replay.frames.push(ReplayFrame {
+				delta: time.real_delta_seconds(),
+				keys: keyboard.get_pressed().copied().collect(),
+				mouse_buttons: mouse_buttons.get_pressed().copied().collect(),
+				aim: None,
+			});
+		}
+		ReplayMode::Replay => {
+			let frame = match replay.frames.get(replay.next_frame) {
+				Some(frame) => frame,
+				None => {
+					info!("The replay is over");
+					*mode = ReplayMode::Off;
+					return;
+				}
+			};
+
+			replace_input(&mut keyboard, &frame.keys);
+			replace_input(&mut mouse_buttons, &frame.mouse_buttons);
+			time.replace_delta(frame.delta);
+
+			replay.next_frame += 1;
+		}
+	}
+}
+
+/// Makes the input look like only the recorded buttons are held
+fn replace_input<T: Copy + Eq + Hash + Send + Sync + 'static>(input: &mut Input<T>, recorded: &[T]) {
+	let real: Vec<T> = input
+		.get_pressed()
+		.chain(input.get_just_pressed())
+		.chain(input.get_just_released())
+		.copied()
+		.collect();
+
+	for button in real {
+		if !recorded.contains(&button) {
+			input.reset(button);
+		}
+	}
+
+	// Still held ones stay pressed without being just pressed again
+	for button in recorded {
+		if !input.pressed(*button) {
+			input.press(*button);
+		}
+	}
+}
+
+fn replay_aim(
+	mode: Res<ReplayMode>,
+	mut replay: ResMut<Replay>,
+	mut aim: ResMut<Aim>,
+	mut player_query: Query<&mut Transform, With<Player>>,
+) {
+	match *mode {
+		ReplayMode::Off => {}
+		ReplayMode::Record => {
+			let direction = aim.direction;
+
+			if let Some(frame) = replay.frames.last_mut() {
+				frame.aim = Some((direction.x, direction.y));
+			}
+		}
+		ReplayMode::Replay => {
+			// The frame was already advanced past in this frame's input
+			let frame = replay.next_frame.checked_sub(1).and_then(|index| replay.frames.get(index));
+
+			if let Some((x, y)) = frame.and_then(|frame| frame.aim) {
+				aim.direction = Vec2::new(x, y);
+				// Shown like gamepad aim, so that the crosshair follows the replay instead of the real cursor
+				aim.device = AimDevice::Gamepad;
+
+				let mut player_transform = player_query.single_mut();
+				player_transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_between(aim.direction));
+			}
+		}
+	}
+}
+
+fn finish_replay(mode: Res<ReplayMode>, replay: Res<Replay>) {
+	if *mode == ReplayMode::Record {
+		save::save(REPLAY_FILE, replay.as_ref());
+
+		info!("Saved a replay of {} frames", replay.frames.len());
+	}
+}
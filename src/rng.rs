@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Randomness that decides how the game plays out, like damage rolls and bullet spread.
+/// Seeding it makes a run reproducible, the looks and sounds keep using `thread_rng`
+/// so that they can't shift the sequence.
+#[derive(Deref, DerefMut)]
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+	pub fn from_seed(seed: u64) -> Self {
+		Self(StdRng::seed_from_u64(seed))
+	}
+}
+
+impl Default for GameRng {
+	fn default() -> Self {
+		Self(StdRng::from_entropy())
+	}
+}
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+	fn build(&self, app: &mut App) {
+		// Setting GAME_SEED replays the same run, otherwise a generator inserted before the plugin is kept
+		match std::env::var("GAME_SEED").ok().and_then(|seed| seed.parse().ok()) {
+			Some(seed) => {
+				info!("Seeding the game with {}", seed);
+				app.insert_resource(GameRng::from_seed(seed));
+			}
+			None => {
+				app.init_resource::<GameRng>();
+			}
+		}
+	}
+}
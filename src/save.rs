@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(not(target_arch="wasm32"))]
+use std::{fs, path::PathBuf};
+
+/// Folder inside the OS config directory the save files go to
+#[cfg(not(target_arch="wasm32"))]
+const SAVE_DIRECTORY: &str = "crackhead-adventures";
+
+#[cfg(not(target_arch="wasm32"))]
+fn save_path(file_name: &str) -> Option<PathBuf> {
+	dirs::config_dir().map(|dir| dir.join(SAVE_DIRECTORY).join(file_name))
+}
+
+/// Reads a RON save file, falling back to the default value if it's missing or broken.
+#[cfg(not(target_arch="wasm32"))]
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+	let path = match save_path(file_name) {
+		Some(path) => path,
+		None => {
+			warn!("Couldn't find the config directory, using default {}", file_name);
+			return T::default();
+		}
+	};
+
+	let contents = match fs::read_to_string(&path) {
+		Ok(contents) => contents,
+		// No save file yet, which is fine on the first launch
+		Err(_) => return T::default(),
+	};
+
+	match ron::from_str(&contents) {
+		Ok(value) => value,
+		Err(error) => {
+			error!("Failed to parse {}, using defaults: {}", path.display(), error);
+			T::default()
+		}
+	}
+}
+
+#[cfg(not(target_arch="wasm32"))]
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+	let path = match save_path(file_name) {
+		Some(path) => path,
+		None => {
+			warn!("Couldn't find the config directory, {} won't be saved", file_name);
+			return;
+		}
+	};
+
+	let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+		Ok(contents) => contents,
+		Err(error) => {
+			error!("Failed to serialize {}: {}", file_name, error);
+			return;
+		}
+	};
+
+	if let Some(directory) = path.parent() {
+		if let Err(error) = fs::create_dir_all(directory) {
+			error!("Failed to create {}: {}", directory.display(), error);
+			return;
+		}
+	}
+
+	if let Err(error) = fs::write(&path, contents) {
+		error!("Failed to write {}: {}", path.display(), error);
+	}
+}
+
+// There's no file system on the web, so nothing gets saved there
+#[cfg(target_arch="wasm32")]
+pub fn load<T: DeserializeOwned + Default>(_file_name: &str) -> T {
+	T::default()
+}
+
+#[cfg(target_arch="wasm32")]
+pub fn save<T: Serialize>(_file_name: &str, _value: &T) {}
@@ -1,11 +1,26 @@
 use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+	audio::ShotgunSound,
+	audio_player::{AudioPlayer, PLAYER_SHOT_VOLUME},
 	button::ColoredButton,
+	crosshair::CrosshairStyle,
 	fonts::{PaintFont, RobotoFont},
-	GameState,
+	save, GameState,
 };
 
+const SETTINGS_FILE: &str = "settings.ron";
+
+const SLIDER_WIDTH: f32 = 300.0;
+const SLIDER_HEIGHT: f32 = 20.0;
+
+/// Range and step of the player speed setting, as a factor of the base speed
+const MIN_PLAYER_SPEED: f32 = 0.5;
+const MAX_PLAYER_SPEED: f32 = 1.5;
+const PLAYER_SPEED_STEP: f32 = 0.1;
+
 #[derive(Component)]
 struct SettingsUi;
 
@@ -30,19 +45,87 @@ struct SubMusicButton;
 #[derive(Component)]
 struct AddMusicButton;
 
+#[derive(Component)]
+struct PlayerSpeedAmount;
+
+#[derive(Component)]
+struct SubPlayerSpeedButton;
+
+#[derive(Component)]
+struct AddPlayerSpeedButton;
+
+#[derive(Component)]
+struct ScreenShakeButton;
+
+#[derive(Component)]
+struct ScreenShakeText;
+
+#[derive(Component)]
+struct ReduceMotionButton;
+
+#[derive(Component)]
+struct ReduceMotionText;
+
+#[derive(Component)]
+struct HoldToFireButton;
+
+#[derive(Component)]
+struct HoldToFireText;
+
+#[derive(Component)]
+struct CrosshairButton;
+
+#[derive(Component)]
+struct CrosshairText;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Volume {
+	Music,
+	Sfx,
+}
+
+/// Track of a volume slider, clicking or dragging on it sets the volume
+#[derive(Component)]
+struct VolumeSlider(Volume);
+
+/// Filled part of a volume slider, as wide as the current volume
+#[derive(Component)]
+struct VolumeSliderFill(Volume);
+
 pub struct SettingsPlugin;
 
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
 	pub sfx_volume: f64,
 	pub music_volume: f64,
+	pub screen_shake: bool,
+	/// Turns off the screen shake and the camera easing, and cuts down the particle bursts
+	pub reduce_motion: bool,
+	/// Keeps the single shot and burst weapons firing for as long as the trigger is held
+	pub hold_to_fire: bool,
+	pub crosshair: CrosshairStyle,
+	/// Multiplies the player's movement speed, read every frame so it applies to a live player
+	pub player_speed: f32,
 }
 
-impl Plugin for SettingsPlugin {
-	fn build(&self, app: &mut App) {
-		app.insert_resource(Settings {
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
 			sfx_volume: 1.0,
 			music_volume: 1.0,
-		})
+			screen_shake: true,
+			reduce_motion: false,
+			hold_to_fire: false,
+			crosshair: CrosshairStyle::Classic,
+			player_speed: 1.0,
+		}
+	}
+}
+
+impl Plugin for SettingsPlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(save::load::<Settings>(SETTINGS_FILE))
 		.add_system_set(SystemSet::on_enter(GameState::Settings).with_system(load_ui))
 		.add_system_set(SystemSet::on_update(GameState::Settings)
 			.with_system(update_ui)
@@ -51,8 +134,18 @@ impl Plugin for SettingsPlugin {
 			.with_system(add_music_button)
 			.with_system(sub_sfx_button)
 			.with_system(add_sfx_button)
+			.with_system(sub_player_speed_button)
+			.with_system(add_player_speed_button)
+			.with_system(drag_volume_sliders)
+			.with_system(screen_shake_button)
+			.with_system(reduce_motion_button)
+			.with_system(hold_to_fire_button)
+			.with_system(crosshair_button)
 		)
-		.add_system_set(SystemSet::on_exit(GameState::Settings).with_system(drop_ui));
+		.add_system_set(SystemSet::on_exit(GameState::Settings)
+			.with_system(drop_ui)
+			.with_system(save_settings)
+		);
 	}
 }
 
@@ -107,7 +200,7 @@ fn load_ui(
 			parent
 				.spawn_bundle(NodeBundle {
 					style: Style {
-						size: Size::new(Val::Percent(50.0), Val::Px(50.0)),
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
 						justify_content: JustifyContent::SpaceBetween,
 						margin: UiRect::new(
 							Val::Px(0.0),
@@ -139,6 +232,8 @@ fn load_ui(
 						)
 						.insert(Name::new("MusicVolumeLabel"));
 
+					spawn_volume_slider(parent, Volume::Music, settings.music_volume);
+
 					parent
 						.spawn_bundle(NodeBundle {
 							style: Style {
@@ -226,7 +321,7 @@ fn load_ui(
 			parent
 				.spawn_bundle(NodeBundle {
 					style: Style {
-						size: Size::new(Val::Percent(50.0), Val::Px(50.0)),
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
 						justify_content: JustifyContent::SpaceBetween,
 						..Default::default()
 					},
@@ -252,6 +347,8 @@ fn load_ui(
 						)
 						.insert(Name::new("SfxVolumeLabel"));
 
+					spawn_volume_slider(parent, Volume::Sfx, settings.sfx_volume);
+
 					parent
 						.spawn_bundle(NodeBundle {
 							style: Style {
@@ -336,16 +433,404 @@ fn load_ui(
 								});
 						});
 				});
+
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
+						justify_content: JustifyContent::SpaceBetween,
+						..Default::default()
+					},
+					color: Color::NONE.into(),
+					..Default::default()
+				})
+				.insert(Name::new("PlayerSpeedContainer"))
+				.with_children(|parent| {
+					parent
+						.spawn_bundle(
+							TextBundle::from_section(
+								"Player Speed: ",
+								TextStyle {
+									font: paint_font.0.clone(),
+									font_size: 32.0,
+									color: Color::WHITE,
+								},
+							)
+							.with_style(Style {
+								margin: UiRect::all(Val::Px(5.0)),
+								..default()
+							}),
+						)
+						.insert(Name::new("PlayerSpeedLabel"));
+
+					parent
+						.spawn_bundle(NodeBundle {
+							style: Style {
+								size: Size::new(Val::Px(200.0), Val::Percent(100.0)),
+								..Default::default()
+							},
+							color: Color::NONE.into(),
+							..Default::default()
+						})
+						.insert(Name::new("PlayerSpeedButtonsContainer"))
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(ButtonBundle {
+									style: Style {
+										size: Size::new(Val::Px(60.0), Val::Percent(100.0)),
+										justify_content: JustifyContent::Center,
+										align_items: AlignItems::Center,
+										..Default::default()
+									},
+									button: Button,
+									color: Color::RED.into(),
+									..Default::default()
+								})
+								.insert(Name::new("SubPlayerSpeedButton"))
+								.insert(SubPlayerSpeedButton)
+								.insert(ColoredButton::default())
+								.with_children(|parent| {
+									parent.spawn_bundle(TextBundle::from_section(
+										"-",
+										TextStyle {
+											font: roboto_font.0.clone(),
+											font_size: 32.0,
+											color: Color::BLACK,
+										},
+									));
+								});
+
+							parent
+								.spawn_bundle(
+									TextBundle::from_section(
+										format!("{:3.0}", settings.player_speed * 100.0),
+										TextStyle {
+											font: paint_font.0.clone(),
+											font_size: 32.0,
+											color: Color::WHITE,
+										},
+									)
+									.with_style(Style {
+										size: Size::new(Val::Px(100.0), Val::Percent(100.0)),
+										margin: UiRect::all(Val::Px(5.0)),
+										..default()
+									}),
+								)
+								.insert(Name::new("PlayerSpeedAmount"))
+								.insert(PlayerSpeedAmount);
+
+							parent
+								.spawn_bundle(ButtonBundle {
+									style: Style {
+										size: Size::new(Val::Px(60.0), Val::Percent(100.0)),
+										justify_content: JustifyContent::Center,
+										align_items: AlignItems::Center,
+										..Default::default()
+									},
+									button: Button,
+									color: Color::RED.into(),
+									..Default::default()
+								})
+								.insert(Name::new("AddPlayerSpeedButton"))
+								.insert(AddPlayerSpeedButton)
+								.insert(ColoredButton::default())
+								.with_children(|parent| {
+									parent.spawn_bundle(TextBundle::from_section(
+										"+",
+										TextStyle {
+											font: roboto_font.0.clone(),
+											font_size: 32.0,
+											color: Color::BLACK,
+										},
+									));
+								});
+						});
+				});
+
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
+						justify_content: JustifyContent::SpaceBetween,
+						..Default::default()
+					},
+					color: Color::NONE.into(),
+					..Default::default()
+				})
+				.insert(Name::new("ScreenShakeContainer"))
+				.with_children(|parent| {
+					parent
+						.spawn_bundle(
+							TextBundle::from_section(
+								format!("Screen Shake: "),
+								TextStyle {
+									font: paint_font.0.clone(),
+									font_size: 32.0,
+									color: Color::WHITE,
+								},
+							)
+							.with_style(Style {
+								margin: UiRect::all(Val::Px(5.0)),
+								..default()
+							}),
+						)
+						.insert(Name::new("ScreenShakeLabel"));
+
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(200.0), Val::Percent(100.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							button: Button,
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("ScreenShakeButton"))
+						.insert(ScreenShakeButton)
+						.insert(ColoredButton::default())
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(TextBundle::from_section(
+									on_off(settings.screen_shake),
+									TextStyle {
+										font: roboto_font.0.clone(),
+										font_size: 32.0,
+										color: Color::BLACK,
+									},
+								))
+								.insert(ScreenShakeText);
+						});
+				});
+
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
+						justify_content: JustifyContent::SpaceBetween,
+						..Default::default()
+					},
+					color: Color::NONE.into(),
+					..Default::default()
+				})
+				.insert(Name::new("ReduceMotionContainer"))
+				.with_children(|parent| {
+					parent
+						.spawn_bundle(
+							TextBundle::from_section(
+								"Reduce Motion: ",
+								TextStyle {
+									font: paint_font.0.clone(),
+									font_size: 32.0,
+									color: Color::WHITE,
+								},
+							)
+							.with_style(Style {
+								margin: UiRect::all(Val::Px(5.0)),
+								..default()
+							}),
+						)
+						.insert(Name::new("ReduceMotionLabel"));
+
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(200.0), Val::Percent(100.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							button: Button,
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("ReduceMotionButton"))
+						.insert(ReduceMotionButton)
+						.insert(ColoredButton::default())
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(TextBundle::from_section(
+									on_off(settings.reduce_motion),
+									TextStyle {
+										font: roboto_font.0.clone(),
+										font_size: 32.0,
+										color: Color::BLACK,
+									},
+								))
+								.insert(ReduceMotionText);
+						});
+				});
+
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
+						justify_content: JustifyContent::SpaceBetween,
+						..Default::default()
+					},
+					color: Color::NONE.into(),
+					..Default::default()
+				})
+				.insert(Name::new("HoldToFireContainer"))
+				.with_children(|parent| {
+					parent
+						.spawn_bundle(
+							TextBundle::from_section(
+								"Hold To Fire: ",
+								TextStyle {
+									font: paint_font.0.clone(),
+									font_size: 32.0,
+									color: Color::WHITE,
+								},
+							)
+							.with_style(Style {
+								margin: UiRect::all(Val::Px(5.0)),
+								..default()
+							}),
+						)
+						.insert(Name::new("HoldToFireLabel"));
+
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(200.0), Val::Percent(100.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							button: Button,
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("HoldToFireButton"))
+						.insert(HoldToFireButton)
+						.insert(ColoredButton::default())
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(TextBundle::from_section(
+									on_off(settings.hold_to_fire),
+									TextStyle {
+										font: roboto_font.0.clone(),
+										font_size: 32.0,
+										color: Color::BLACK,
+									},
+								))
+								.insert(HoldToFireText);
+						});
+				});
+
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(75.0), Val::Px(50.0)),
+						justify_content: JustifyContent::SpaceBetween,
+						..Default::default()
+					},
+					color: Color::NONE.into(),
+					..Default::default()
+				})
+				.insert(Name::new("CrosshairContainer"))
+				.with_children(|parent| {
+					parent
+						.spawn_bundle(
+							TextBundle::from_section(
+								"Crosshair: ",
+								TextStyle {
+									font: paint_font.0.clone(),
+									font_size: 32.0,
+									color: Color::WHITE,
+								},
+							)
+							.with_style(Style {
+								margin: UiRect::all(Val::Px(5.0)),
+								..default()
+							}),
+						)
+						.insert(Name::new("CrosshairLabel"));
+
+					parent
+						.spawn_bundle(ButtonBundle {
+							style: Style {
+								size: Size::new(Val::Px(200.0), Val::Percent(100.0)),
+								justify_content: JustifyContent::Center,
+								align_items: AlignItems::Center,
+								..Default::default()
+							},
+							button: Button,
+							color: Color::RED.into(),
+							..Default::default()
+						})
+						.insert(Name::new("CrosshairButton"))
+						.insert(CrosshairButton)
+						.insert(ColoredButton::default())
+						.with_children(|parent| {
+							parent
+								.spawn_bundle(TextBundle::from_section(
+									settings.crosshair.name(),
+									TextStyle {
+										font: roboto_font.0.clone(),
+										font_size: 32.0,
+										color: Color::BLACK,
+									},
+								))
+								.insert(CrosshairText);
+						});
+				});
+		});
+}
+
+fn on_off(value: bool) -> &'static str {
+	if value {
+		"On"
+	} else {
+		"Off"
+	}
+}
+
+fn spawn_volume_slider(parent: &mut ChildBuilder, volume: Volume, value: f64) {
+	parent
+		.spawn_bundle(ButtonBundle {
+			style: Style {
+				size: Size::new(Val::Px(SLIDER_WIDTH), Val::Px(SLIDER_HEIGHT)),
+				align_self: AlignSelf::Center,
+				..Default::default()
+			},
+			button: Button,
+			color: Color::DARK_GRAY.into(),
+			..Default::default()
+		})
+		.insert(Name::new("VolumeSlider"))
+		.insert(VolumeSlider(volume))
+		.with_children(|parent| {
+			parent
+				.spawn_bundle(NodeBundle {
+					style: Style {
+						size: Size::new(Val::Percent(value as f32 * 100.0), Val::Percent(100.0)),
+						..Default::default()
+					},
+					color: Color::RED.into(),
+					..Default::default()
+				})
+				.insert(Name::new("VolumeSliderFill"))
+				.insert(VolumeSliderFill(volume));
 		});
 }
 
 fn update_ui(
 	settings: Res<Settings>,
+	mut slider_fill_query: Query<(&mut Style, &VolumeSliderFill)>,
 	mut music_volume_query: Query<
         &mut Text,
         (
             With<MusicVolumeAmount>,
             Without<SfxVolumeAmount>,
+            Without<PlayerSpeedAmount>,
+            Without<ScreenShakeText>,
+            Without<ReduceMotionText>,
+            Without<HoldToFireText>,
+            Without<CrosshairText>,
         ),
     >,
 	mut sfx_volume_query: Query<
@@ -353,6 +838,71 @@ fn update_ui(
         (
             With<SfxVolumeAmount>,
             Without<MusicVolumeAmount>,
+            Without<PlayerSpeedAmount>,
+            Without<ScreenShakeText>,
+            Without<ReduceMotionText>,
+            Without<HoldToFireText>,
+            Without<CrosshairText>,
+        ),
+    >,
+	mut player_speed_query: Query<
+        &mut Text,
+        (
+            With<PlayerSpeedAmount>,
+            Without<MusicVolumeAmount>,
+            Without<SfxVolumeAmount>,
+            Without<ScreenShakeText>,
+            Without<ReduceMotionText>,
+            Without<HoldToFireText>,
+            Without<CrosshairText>,
+        ),
+    >,
+	mut screen_shake_query: Query<
+        &mut Text,
+        (
+            With<ScreenShakeText>,
+            Without<MusicVolumeAmount>,
+            Without<SfxVolumeAmount>,
+            Without<PlayerSpeedAmount>,
+            Without<ReduceMotionText>,
+            Without<HoldToFireText>,
+            Without<CrosshairText>,
+        ),
+    >,
+	mut crosshair_query: Query<
+        &mut Text,
+        (
+            With<CrosshairText>,
+            Without<MusicVolumeAmount>,
+            Without<SfxVolumeAmount>,
+            Without<PlayerSpeedAmount>,
+            Without<ScreenShakeText>,
+            Without<ReduceMotionText>,
+            Without<HoldToFireText>,
+        ),
+    >,
+	mut reduce_motion_query: Query<
+        &mut Text,
+        (
+            With<ReduceMotionText>,
+            Without<MusicVolumeAmount>,
+            Without<SfxVolumeAmount>,
+            Without<PlayerSpeedAmount>,
+            Without<ScreenShakeText>,
+            Without<HoldToFireText>,
+            Without<CrosshairText>,
+        ),
+    >,
+	mut hold_to_fire_query: Query<
+        &mut Text,
+        (
+            With<HoldToFireText>,
+            Without<MusicVolumeAmount>,
+            Without<SfxVolumeAmount>,
+            Without<PlayerSpeedAmount>,
+            Without<ScreenShakeText>,
+            Without<ReduceMotionText>,
+            Without<CrosshairText>,
         ),
     >,
 ) {
@@ -361,6 +911,86 @@ fn update_ui(
 
 	let mut sfx_volume = sfx_volume_query.single_mut();
 	sfx_volume.sections[0].value = format!("{:3.0}", settings.sfx_volume * 100.0);
+
+	let mut player_speed = player_speed_query.single_mut();
+	player_speed.sections[0].value = format!("{:3.0}", settings.player_speed * 100.0);
+
+	let mut screen_shake = screen_shake_query.single_mut();
+	screen_shake.sections[0].value = on_off(settings.screen_shake).to_string();
+
+	let mut reduce_motion = reduce_motion_query.single_mut();
+	reduce_motion.sections[0].value = on_off(settings.reduce_motion).to_string();
+
+	let mut hold_to_fire = hold_to_fire_query.single_mut();
+	hold_to_fire.sections[0].value = on_off(settings.hold_to_fire).to_string();
+
+	let mut crosshair = crosshair_query.single_mut();
+	crosshair.sections[0].value = settings.crosshair.name().to_string();
+
+	for (mut style, fill) in slider_fill_query.iter_mut() {
+		let value = match fill.0 {
+			Volume::Music => settings.music_volume,
+			Volume::Sfx => settings.sfx_volume,
+		};
+
+		style.size.width = Val::Percent(value as f32 * 100.0);
+	}
+}
+
+fn drag_volume_sliders(
+	slider_query: Query<(&Interaction, &Node, &GlobalTransform, &VolumeSlider)>,
+	windows: Res<Windows>,
+	mouse_input: Res<Input<MouseButton>>,
+	mut dragging_sfx: Local<bool>,
+	mut settings: ResMut<Settings>,
+	audio: Res<Audio>,
+	shot_sound: Res<ShotgunSound>,
+) {
+	let cursor_position = windows.get_primary().and_then(|window| window.cursor_position());
+
+	for (interaction, node, transform, slider) in slider_query.iter() {
+		// Clicked stays on while the button is held, even if the cursor leaves the slider
+		if *interaction != Interaction::Clicked {
+			continue;
+		}
+
+		let cursor_position = match cursor_position {
+			Some(position) => position,
+			None => continue,
+		};
+
+		// UI nodes are positioned by their center
+		let left = transform.translation().x - node.size.x / 2.0;
+		let value = ((cursor_position.x - left) / node.size.x).clamp(0.0, 1.0) as f64;
+
+		// Holding the slider still shouldn't mark the settings as changed every frame
+		match slider.0 {
+			Volume::Music => {
+				if settings.music_volume != value {
+					settings.music_volume = value;
+				}
+			}
+			Volume::Sfx => {
+				if settings.sfx_volume != value {
+					settings.sfx_volume = value;
+				}
+
+				*dragging_sfx = true;
+			}
+		}
+	}
+
+	// Let the player hear the new volume once they let go
+	if *dragging_sfx && mouse_input.just_released(MouseButton::Left) {
+		*dragging_sfx = false;
+		AudioPlayer::play_sfx(&audio, shot_sound.0.clone(), PLAYER_SHOT_VOLUME, &settings);
+	}
+}
+
+/// Written once when leaving the menu, instead of on every change, so that dragging a slider
+/// doesn't write the file every frame
+fn save_settings(settings: Res<Settings>) {
+	save::save(SETTINGS_FILE, settings.as_ref());
 }
 
 fn drop_ui(mut commands: Commands, ui: Query<Entity, With<SettingsUi>>) {
@@ -414,15 +1044,85 @@ fn add_sfx_button(
 	
 }
 
-fn main_menu_button(
-	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<MainMenuButton>)>,
-	mut state: ResMut<State<GameState>>,
+fn sub_player_speed_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<SubPlayerSpeedButton>)>,
+	mut settings: ResMut<Settings>
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			settings.player_speed =
+				(settings.player_speed - PLAYER_SPEED_STEP).clamp(MIN_PLAYER_SPEED, MAX_PLAYER_SPEED);
+		}
+	}
+}
+
+fn add_player_speed_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<AddPlayerSpeedButton>)>,
+	mut settings: ResMut<Settings>
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			settings.player_speed =
+				(settings.player_speed + PLAYER_SPEED_STEP).clamp(MIN_PLAYER_SPEED, MAX_PLAYER_SPEED);
+		}
+	}
+}
+
+fn screen_shake_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<ScreenShakeButton>)>,
+	mut settings: ResMut<Settings>
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			settings.screen_shake = !settings.screen_shake;
+		}
+	}
+}
+
+fn reduce_motion_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<ReduceMotionButton>)>,
+	mut settings: ResMut<Settings>
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			settings.reduce_motion = !settings.reduce_motion;
+		}
+	}
+}
+
+fn hold_to_fire_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<HoldToFireButton>)>,
+	mut settings: ResMut<Settings>
+) {
+	for interaction in &mut interaction_query {
+		if *interaction == Interaction::Clicked {
+			settings.hold_to_fire = !settings.hold_to_fire;
+		}
+	}
+}
+
+fn crosshair_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<CrosshairButton>)>,
+	mut settings: ResMut<Settings>
 ) {
 	for interaction in &mut interaction_query {
 		if *interaction == Interaction::Clicked {
-			state
-				.set(GameState::MainMenu)
-				.expect("Failed to change state!");
+			settings.crosshair = settings.crosshair.next();
 		}
 	}
 }
+
+fn main_menu_button(
+	mut interaction_query: Query<&Interaction, (Changed<Interaction>, With<MainMenuButton>)>,
+	keyboard: Res<Input<KeyCode>>,
+	mut state: ResMut<State<GameState>>,
+) {
+	// Escape works the same as clicking the button
+	let clicked = interaction_query.iter_mut().any(|interaction| *interaction == Interaction::Clicked);
+
+	if clicked || keyboard.just_pressed(KeyCode::Escape) {
+		state
+			.set(GameState::MainMenu)
+			.expect("Failed to change state!");
+	}
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::boss::Boss;
+use crate::enemy::Enemy;
+use crate::{GameState, TILE_SIZE};
+
+pub const GRID_CELL_SIZE: f32 = 4.0 * TILE_SIZE;
+
+/// Buckets entities by the cell they're in, so that finding the ones close to a point
+/// doesn't have to go through all of them.
+pub struct SpatialGrid {
+	cell_size: f32,
+	cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+}
+
+impl SpatialGrid {
+	pub fn new(cell_size: f32) -> Self {
+		Self {
+			cell_size,
+			cells: HashMap::new(),
+		}
+	}
+
+	fn cell(&self, position: Vec2) -> IVec2 {
+		(position / self.cell_size).floor().as_ivec2()
+	}
+
+	pub fn insert(&mut self, entity: Entity, position: Vec2) {
+		let cell = self.cell(position);
+		self.cells.entry(cell).or_default().push((entity, position));
+	}
+
+	pub fn clear(&mut self) {
+		self.cells.clear();
+	}
+
+	/// Entities within `radius` of the center, in no particular order.
+	pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<(Entity, Vec2)> {
+		let min = self.cell(center - Vec2::splat(radius));
+		let max = self.cell(center + Vec2::splat(radius));
+
+		let mut found = Vec::new();
+
+		for x in min.x..=max.x {
+			for y in min.y..=max.y {
+				if let Some(cell) = self.cells.get(&IVec2::new(x, y)) {
+					found.extend(
+						cell.iter()
+							.filter(|(_, position)| position.distance_squared(center) <= radius * radius),
+					);
+				}
+			}
+		}
+
+		found
+	}
+}
+
+/// Every enemy and boss, rebuilt each frame
+#[derive(Deref, DerefMut)]
+pub struct EnemyGrid(pub SpatialGrid);
+
+pub struct SpatialGridPlugin;
+
+impl Plugin for SpatialGridPlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(EnemyGrid(SpatialGrid::new(GRID_CELL_SIZE)))
+			.add_system_set(
+				SystemSet::on_update(GameState::Game)
+					.with_system(rebuild_enemy_grid.label("rebuild_enemy_grid")),
+			)
+			.add_system_set(SystemSet::on_exit(GameState::Game).with_system(clear_enemy_grid));
+	}
+}
+
+fn rebuild_enemy_grid(
+	mut grid: ResMut<EnemyGrid>,
+	enemies: Query<(Entity, &Transform), Or<(With<Enemy>, With<Boss>)>>,
+) {
+	grid.clear();
+
+	for (entity, transform) in enemies.iter() {
+		grid.insert(entity, transform.translation.truncate());
+	}
+}
+
+fn clear_enemy_grid(mut grid: ResMut<EnemyGrid>) {
+	grid.clear();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn found_ids(grid: &SpatialGrid, center: Vec2, radius: f32) -> Vec<u32> {
+		let mut ids: Vec<u32> = grid
+			.query_radius(center, radius)
+			.iter()
+			.map(|(entity, _)| entity.id())
+			.collect();
+		ids.sort_unstable();
+		ids
+	}
+
+	#[test]
+	fn finds_the_neighbors_across_cells() {
+		let mut grid = SpatialGrid::new(100.0);
+
+		// On both sides of the cell edges around the origin, so that the query has to look through several cells
+		grid.insert(Entity::from_raw(0), Vec2::new(10.0, 10.0));
+		grid.insert(Entity::from_raw(1), Vec2::new(-10.0, -10.0));
+		grid.insert(Entity::from_raw(2), Vec2::new(140.0, 0.0));
+		grid.insert(Entity::from_raw(3), Vec2::new(0.0, -149.0));
+		// In a cell the query looks through, but outside of the radius
+		grid.insert(Entity::from_raw(4), Vec2::new(120.0, 120.0));
+		grid.insert(Entity::from_raw(5), Vec2::new(-160.0, 0.0));
+		// Far away
+		grid.insert(Entity::from_raw(6), Vec2::new(1000.0, -1000.0));
+
+		assert_eq!(found_ids(&grid, Vec2::ZERO, 150.0), vec![0, 1, 2, 3]);
+	}
+
+	#[test]
+	fn matches_checking_every_entity() {
+		let mut grid = SpatialGrid::new(GRID_CELL_SIZE);
+		let mut positions = Vec::new();
+
+		for i in 0..200u32 {
+			// Spread out over a few dozen cells, without any randomness
+			let position = Vec2::new((i * 37 % 101) as f32 * 17.0 - 850.0, (i * 53 % 97) as f32 * 19.0 - 900.0);
+
+			grid.insert(Entity::from_raw(i), position);
+			positions.push(position);
+		}
+
+		let center = Vec2::new(35.0, -80.0);
+		let radius = 300.0;
+
+		let expected: Vec<u32> = (0..200)
+			.filter(|i| positions[*i as usize].distance_squared(center) <= radius * radius)
+			.collect();
+
+		assert!(!expected.is_empty());
+		assert_eq!(found_ids(&grid, center, radius), expected);
+	}
+
+	#[test]
+	fn clear_empties_the_grid() {
+		let mut grid = SpatialGrid::new(100.0);
+		grid.insert(Entity::from_raw(0), Vec2::ZERO);
+
+		grid.clear();
+
+		assert!(grid.query_radius(Vec2::ZERO, 1000.0).is_empty());
+	}
+}
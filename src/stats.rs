@@ -1,6 +1,10 @@
 use bevy::{prelude::*, time::Stopwatch};
 
-use crate::{GameState, fonts::{PaintFont, RobotoFont}, button::ColoredButton};
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, fonts::{PaintFont, RobotoFont}, button::ColoredButton, save};
+
+const HIGH_SCORE_FILE: &str = "high_score.ron";
 
 #[derive(Component)]
 struct StatsUi;
@@ -23,9 +27,40 @@ pub struct Stats{
     pub shot_accuracy: f32,
 }
 
+/// Points scored during the current run
+#[derive(Default)]
+pub struct Score {
+    pub points: u32,
+}
+
+/// Best score across all runs, kept on disk
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScore {
+    pub points: u32,
+}
+
+impl HighScore {
+    /// Saves the score if it beats the current high score.
+    /// # Returns
+    /// True if it was a new high score.
+    pub fn submit(&mut self, score: &Score) -> bool {
+        if score.points <= self.points {
+            return false;
+        }
+
+        self.points = score.points;
+        save::save(HIGH_SCORE_FILE, self);
+
+        true
+    }
+}
+
 impl Plugin for StatsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Stats{
+        app.insert_resource(Score::default())
+           .insert_resource(save::load::<HighScore>(HIGH_SCORE_FILE))
+           .insert_resource(Stats{
             timer: Stopwatch::new(),
             enemies_killed: 0,
             small_powerup_used: 0,
@@ -48,7 +83,9 @@ impl Plugin for StatsPlugin {
 }
 
 
-fn reset_stats(mut stats: ResMut<Stats>) {
+fn reset_stats(mut stats: ResMut<Stats>, mut score: ResMut<Score>) {
+    score.points = 0;
+
     stats.timer.reset();
     stats.timer.unpause();
 
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::bullet::ShotEvent;
+use crate::time::TimeCounter;
+use crate::unit::Movement;
+use crate::GameState;
+
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_system_set(
+			SystemSet::on_update(GameState::Game)
+				.with_system(apply_status_effects.label("apply_status_effects"))
+				.with_system(status_tick.after("apply_status_effects")),
+		);
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum StatusEffectKind {
+	Poison,
+	Burn,
+	Slow,
+}
+
+/// What a hit does to its target besides the damage
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct StatusEffect {
+	pub kind: StatusEffectKind,
+	/// Seconds the effect lasts
+	pub duration: f32,
+	/// Seconds between damage ticks, slows don't use it
+	pub interval: f32,
+	/// Damage of every tick for poison and burn, speed multiplier for slows
+	pub strength: f32,
+}
+
+impl StatusEffect {
+	pub const fn new(kind: StatusEffectKind, duration: f32, interval: f32, strength: f32) -> Self {
+		Self {
+			kind,
+			duration,
+			interval,
+			strength,
+		}
+	}
+}
+
+struct ActiveStatusEffect {
+	effect: StatusEffect,
+	remaining: Timer,
+	tick: Timer,
+}
+
+/// Effects currently on a unit.
+///
+/// Getting the same kind of effect again doesn't stack, it refreshes the duration
+/// and keeps whichever of the two is stronger.
+#[derive(Component, Default)]
+pub struct StatusEffects {
+	effects: Vec<ActiveStatusEffect>,
+}
+
+fn apply_status_effects(
+	mut units: Query<(&mut StatusEffects, Option<&mut Movement>)>,
+	mut shot_events: EventReader<ShotEvent>,
+) {
+	for shot in shot_events.iter() {
+		let effect = match shot.2 {
+			Some(effect) => effect,
+			None => continue,
+		};
+
+		let (mut status_effects, movement) = match units.get_mut(shot.0) {
+			Ok(unit) => unit,
+			// Walls and such
+			Err(_) => continue,
+		};
+
+		let existing = status_effects
+			.effects
+			.iter_mut()
+			.find(|active| active.effect.kind == effect.kind);
+
+		match existing {
+			Some(active) => {
+				active.remaining = Timer::from_seconds(effect.duration, false);
+
+				match effect.kind {
+					// A lower multiplier is a stronger slow
+					StatusEffectKind::Slow => {
+						if effect.strength < active.effect.strength {
+							if let Some(mut movement) = movement {
+								movement.speed *= effect.strength / active.effect.strength;
+							}
+
+							active.effect.strength = effect.strength;
+						}
+					}
+					StatusEffectKind::Poison | StatusEffectKind::Burn => {
+						active.effect.strength = active.effect.strength.max(effect.strength);
+					}
+				}
+			}
+			None => {
+				if effect.kind == StatusEffectKind::Slow {
+					if let Some(mut movement) = movement {
+						movement.speed *= effect.strength;
+					}
+				}
+
+				status_effects.effects.push(ActiveStatusEffect {
+					effect,
+					remaining: Timer::from_seconds(effect.duration, false),
+					tick: Timer::from_seconds(effect.interval, true),
+				});
+			}
+		}
+	}
+}
+
+fn status_tick(
+	mut units: Query<(Entity, &mut StatusEffects, Option<&mut Movement>)>,
+	mut shot_events: EventWriter<ShotEvent>,
+	time: Res<TimeCounter>,
+) {
+	for (entity, mut status_effects, mut movement) in units.iter_mut() {
+		status_effects.effects.retain_mut(|active| {
+			active.remaining.tick(time.delta());
+
+			if active.effect.kind != StatusEffectKind::Slow {
+				active.tick.tick(time.delta());
+
+				// Goes through the usual hit handling, so that deaths and damage numbers work the same
+				for _ in 0..active.tick.times_finished() {
+					shot_events.send(ShotEvent(entity, active.effect.strength, None, false));
+				}
+			}
+
+			if !active.remaining.finished() {
+				return true;
+			}
+
+			// Undo the slow now that it's over
+			if active.effect.kind == StatusEffectKind::Slow {
+				if let Some(movement) = movement.as_mut() {
+					movement.speed /= active.effect.strength;
+				}
+			}
+
+			false
+		});
+	}
+}
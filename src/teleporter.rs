@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+use crate::audio::{CraftingSound, PitchedSfxChannel};
+use crate::audio_player::{AudioPlayer, TELEPORT_VOLUME};
+use crate::particle::{spawn_burst, Particle};
+use crate::player::{CameraFocus, Player};
+use crate::pool::Pool;
+use crate::settings::Settings;
+use crate::tilemap::{CullableTile, Tile, Tilemap};
+use crate::time::TimeCounter;
+use crate::TILE_SIZE;
+
+/// Seconds after a teleport before the next one can happen
+pub const TELEPORT_COOLDOWN: f32 = 1.0;
+pub const TELEPORT_PARTICLES: u32 = 12;
+pub const TELEPORT_PARTICLE_COLOR: Color = Color::rgb(0.3, 0.8, 1.0);
+/// The crafting sound sped up into a whoosh
+pub const TELEPORT_SOUND_RATE: f64 = 1.6;
+
+/// Floor tile that sends the player to the other teleporter with the same link
+#[derive(Component)]
+pub struct Teleporter {
+	pub link: i32,
+}
+
+#[derive(Bundle)]
+pub struct TeleporterBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	teleporter: Teleporter,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for TeleporterBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			teleporter: Teleporter { link: 0 },
+			cullable: CullableTile,
+			name: Name::new("Teleporter"),
+		}
+	}
+}
+
+impl Tile for TeleporterBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_xyz(position.x, position.y, 0.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+impl TeleporterBundle {
+	pub fn with_link(mut self, link: i32) -> Self {
+		self.teleporter.link = link;
+		self
+	}
+}
+
+#[derive(Default)]
+pub struct TeleportState {
+	cooldown: f32,
+	/// The teleporter the player came out of, which doesn't send them back until they step off it
+	arrived_on: Option<Entity>,
+}
+
+pub fn use_teleporters(
+	mut commands: Commands,
+	teleporters: Query<(Entity, &Transform, &Teleporter), Without<Player>>,
+	mut player: Query<&mut Transform, With<Player>>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	mut focus: ResMut<CameraFocus>,
+	mut particle_pool: ResMut<Pool<Particle>>,
+	pitched_channel: Res<AudioChannel<PitchedSfxChannel>>,
+	teleport_sound: Res<CraftingSound>,
+	settings: Res<Settings>,
+	time: Res<TimeCounter>,
+	mut state: Local<TeleportState>,
+) {
+	state.cooldown = (state.cooldown - time.delta_seconds()).max(0.0);
+
+	let mut player_transform = player.single_mut();
+	let player_position = player_transform.translation.truncate();
+
+	// Like the hazards, the player is on the tile under their center
+	let current = teleporters.iter().find(|(_, transform, _)| {
+		let offset = (player_position - transform.translation.truncate()).abs();
+		offset.x < TILE_SIZE / 2.0 && offset.y < TILE_SIZE / 2.0
+	});
+
+	let (entity, from, teleporter) = match current {
+		Some(current) => current,
+		None => {
+			state.arrived_on = None;
+			return;
+		}
+	};
+
+	if state.arrived_on == Some(entity) || state.cooldown > 0.0 {
+		return;
+	}
+
+	let partner = teleporters
+		.iter()
+		.find(|(other, _, other_teleporter)| *other != entity && other_teleporter.link == teleporter.link);
+
+	let (partner, to, _) = match partner {
+		Some(partner) => partner,
+		None => {
+			warn!("Teleporter {} has no partner", teleporter.link);
+			state.arrived_on = Some(entity);
+			return;
+		}
+	};
+
+	player_transform.translation.x = to.translation.x;
+	player_transform.translation.y = to.translation.y;
+
+	state.arrived_on = Some(partner);
+	state.cooldown = TELEPORT_COOLDOWN;
+
+	// Easing across the whole map would just be a blur
+	focus.cut();
+
+	let tilemap = tilemap.single();
+
+	for end in [from.translation, to.translation] {
+		spawn_burst(
+			&mut commands,
+			&mut particle_pool,
+			tilemap,
+			end,
+			TELEPORT_PARTICLE_COLOR,
+			TELEPORT_PARTICLES,
+		);
+	}
+
+	AudioPlayer::play_sfx_with_rate(
+		pitched_channel.as_ref(),
+		teleport_sound.0.clone(),
+		TELEPORT_VOLUME,
+		settings.as_ref(),
+		TELEPORT_SOUND_RATE * time.scale() as f64,
+	);
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+
+use crate::tilemap::{CullableTile, Tile};
+use crate::TILE_SIZE;
+
+/// Speed multiplier of a slow tile that doesn't set its own
+pub const DEFAULT_SLOW_FACTOR: f32 = 0.5;
+/// How quickly the player's velocity follows the input on ice, higher is less slippery
+pub const ICE_GRIP: f32 = 1.5;
+
+/// Floor tile, like mud, that slows the player down while they're on it
+#[derive(Component)]
+pub struct SlowTile {
+	/// Multiplies the player's speed, 1 leaves it as it is
+	pub factor: f32,
+}
+
+#[derive(Bundle)]
+pub struct SlowTileBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	slow_tile: SlowTile,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for SlowTileBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			slow_tile: SlowTile {
+				factor: DEFAULT_SLOW_FACTOR,
+			},
+			cullable: CullableTile,
+			name: Name::new("SlowTile"),
+		}
+	}
+}
+
+impl Tile for SlowTileBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				// Same height as the floor it replaces
+				transform: Transform::from_xyz(position.x, position.y, 0.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+impl SlowTileBundle {
+	pub fn with_factor(mut self, factor: f32) -> Self {
+		self.slow_tile.factor = factor;
+		self
+	}
+}
+
+/// Floor tile the player slides around on
+#[derive(Component)]
+pub struct IceTile {
+	pub grip: f32,
+}
+
+#[derive(Bundle)]
+pub struct IceTileBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	ice_tile: IceTile,
+	cullable: CullableTile,
+	name: Name,
+}
+
+impl Default for IceTileBundle {
+	fn default() -> Self {
+		Self {
+			sprite_bundle: SpriteBundle::default(),
+			ice_tile: IceTile { grip: ICE_GRIP },
+			cullable: CullableTile,
+			name: Name::new("IceTile"),
+		}
+	}
+}
+
+impl Tile for IceTileBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_xyz(position.x, position.y, 0.0),
+				texture,
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					// Tinted, so that it reads as ice on top of any floor texture
+					color: Color::rgb(0.75, 0.9, 1.0),
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+fn overlaps_tile(position: Vec2, radius: f32, tile_position: Vec2) -> bool {
+	let offset = (position - tile_position).abs();
+	offset.x < TILE_SIZE / 2.0 + radius && offset.y < TILE_SIZE / 2.0 + radius
+}
+
+/// Speed multiplier for a body of the given radius at the given position.
+/// Only the slowest tile it overlaps counts, so standing on the seam between two mud tiles
+/// isn't any slower than standing on one of them.
+pub fn slow_factor<'a>(
+	position: Vec2,
+	radius: f32,
+	slow_tiles: impl Iterator<Item = (&'a Transform, &'a SlowTile)>,
+) -> f32 {
+	slow_tiles
+		.filter(|(transform, _)| overlaps_tile(position, radius, transform.translation.truncate()))
+		.map(|(_, slow_tile)| slow_tile.factor)
+		.fold(1.0, f32::min)
+}
+
+/// Grip of the most slippery ice tile the body overlaps, or `None` when it's off the ice.
+pub fn ice_grip<'a>(
+	position: Vec2,
+	radius: f32,
+	ice_tiles: impl Iterator<Item = (&'a Transform, &'a IceTile)>,
+) -> Option<f32> {
+	ice_tiles
+		.filter(|(transform, _)| overlaps_tile(position, radius, transform.translation.truncate()))
+		.map(|(_, ice_tile)| ice_tile.grip)
+		.reduce(f32::min)
+}
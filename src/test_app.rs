@@ -0,0 +1,58 @@
+//! A headless app for testing the gameplay systems, without a window, a renderer or sound.
+//!
+//! To test a system, build the app with `test_app`, add the system to an on_update set of
+//! `GameState::Game` and insert the resources it reads that aren't set up here. Then spawn what
+//! the test needs, call `step` for every frame and check the components with `app.world`.
+//! The physics only knows about colliders after the frame they were spawned in, so call
+//! `step(&mut app, 0.0)` once before anything starts moving.
+
+use bevy::hierarchy::HierarchyPlugin;
+use bevy::prelude::*;
+use bevy::transform::TransformPlugin;
+use bevy_rapier2d::prelude::*;
+
+use crate::key_bindings::KeyBindings;
+use crate::settings::Settings;
+use crate::tilemap::TileCollider;
+use crate::time::TimeCounter;
+use crate::GameState;
+
+/// The resources that `DefaultPlugins` would add for the input, plus the game's own clock,
+/// settings and physics. Nothing is read from the settings file.
+pub fn test_app() -> App {
+	let mut app = App::new();
+
+	app.add_plugins(MinimalPlugins)
+		.add_plugin(TransformPlugin)
+		.add_plugin(HierarchyPlugin)
+		.add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+		.add_state(GameState::Game)
+		.insert_resource(TimeCounter::new())
+		.insert_resource(Settings::default())
+		.insert_resource(KeyBindings::default())
+		.init_resource::<Input<KeyCode>>()
+		.init_resource::<Input<MouseButton>>()
+		.init_resource::<Gamepads>()
+		.init_resource::<Input<GamepadButton>>()
+		.init_resource::<Axis<GamepadAxis>>()
+		.init_resource::<Axis<GamepadButton>>();
+
+	app
+}
+
+/// Runs a single frame, `delta` seconds long on the game clock.
+/// The physics still goes by the real time, which only matters for rigid bodies.
+pub fn step(app: &mut App, delta: f32) {
+	app.world.resource_mut::<TimeCounter>().step(delta);
+	app.update();
+}
+
+/// Spawns a solid wall collider, the same kind the level loader makes out of the wall tiles.
+pub fn spawn_wall(app: &mut App, center: Vec2, half_size: Vec2) -> Entity {
+	app.world
+		.spawn()
+		.insert_bundle(TransformBundle::from_transform(Transform::from_translation(center.extend(10.0))))
+		.insert(TileCollider { half_size })
+		.insert(Collider::cuboid(half_size.x, half_size.y))
+		.id()
+}
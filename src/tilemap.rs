@@ -1,35 +1,105 @@
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use std::io::ErrorKind;
 use std::path::{PathBuf, Path};
 
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use tiled::{Chunk, LayerType, Loader, TileLayer, Tileset, Map, ResourceReader, DefaultResourceCache};
+use tiled::{Chunk, LayerType, Loader, TileLayer, Tileset, Map, PropertyValue, ResourceReader, DefaultResourceCache};
+use serde::{Deserialize, Serialize};
 
+use crate::boss::BossBundle;
+use crate::chest::{open_chests, ChestBundle, ChestTier};
 use crate::cocaine::CocaineBundle;
+use crate::destructible::{damage_destructibles, DestructibleWallBundle};
+use crate::door::{update_doors, DoorBundle, DoorMode, KeyColor};
 use crate::enemy::EnemyBundle;
 use crate::enemy_nav_mesh::EnemyNavMesh;
-use crate::player::PlayerBundle;
-use crate::win::{WinBundle, WinMaterial};
+use crate::enemy_type::{EnemyType, EnemyTypes};
+use crate::hazard::{damage_on_hazards, HazardBundle, HazardMode};
+use crate::level_gen::{generate_level, GeneratedLevel};
+use crate::pickup::{AmmoPickupBundle, HealthPickupBundle, KeyPickupBundle};
+use crate::teleporter::{use_teleporters, TeleporterBundle};
+use crate::terrain::{IceTileBundle, SlowTileBundle};
+use crate::player::{view_half_size, PlayerBundle};
+use crate::post_processing::MainCamera;
+use crate::wave::EnemySpawnPointBundle;
+use crate::win::{WinBundle, WinGoal, WinMaterial};
+use crate::time::TimeCounter;
 use crate::{GameState, TILE_SIZE};
 
 #[derive(Component)]
 pub struct Tilemap;
 
+/// Solid wall collision, covering a whole rectangle of wall tiles
 #[derive(Component)]
-pub struct TileCollider;
+pub struct TileCollider {
+	pub half_size: Vec2,
+}
+
+/// Where the next level comes from
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelSource {
+	/// The hand made level from `assets/level/level.tmx`
+	Tiled,
+	/// A randomly generated level, the same seed gives the same level
+	Generated { seed: u64 },
+}
+
+/// Static tile sprites, hidden while they are off-screen
+#[derive(Component, Default)]
+pub struct CullableTile;
+
+/// Tile cycling through a list of textures, set up from the tile animations in Tiled
+#[derive(Component)]
+pub struct AnimatedTile {
+	/// Each texture together with how many seconds it stays up
+	frames: Vec<(Handle<Image>, f32)>,
+	current: usize,
+	timer: Timer,
+}
+
+impl AnimatedTile {
+	pub fn new(frames: Vec<(Handle<Image>, f32)>) -> Self {
+		let first_duration = frames.first().map(|frame| frame.1).unwrap_or(0.0);
+
+		Self {
+			frames,
+			current: 0,
+			timer: Timer::from_seconds(first_duration, false),
+		}
+	}
+}
+
+/// Area covered by the tiles of the current level
+#[derive(Default)]
+pub struct MapBounds {
+	pub min: Vec2,
+	pub max: Vec2,
+}
 
 pub struct TileMapPlugin;
 
 impl Plugin for TileMapPlugin {
 	fn build(&self, app: &mut App) {
 		app.insert_resource(EnemyNavMesh::new())
+			.insert_resource(MapBounds::default())
+			.insert_resource(LevelSource::Tiled)
 			.insert_resource(TexturesMemo {
 				memoized: HashMap::new(),
 			})
 			.add_system_set(
 				SystemSet::on_enter(GameState::Game).with_system(load_level.label("load_level")),
 			)
+			.add_system_set(
+				SystemSet::on_update(GameState::Game)
+					.with_system(cull_tiles.after("camera_follow_player"))
+					.with_system(damage_destructibles)
+					.with_system(update_doors)
+					.with_system(open_chests)
+					.with_system(damage_on_hazards)
+					.with_system(use_teleporters.after("player_movement").before("camera_follow_player"))
+					.with_system(animate_tiles),
+			)
 			.add_system_set(
 				SystemSet::on_exit(GameState::Game)
 					.with_system(drop_level)
@@ -46,15 +116,18 @@ pub trait Tile {
 
 // Tiles
 #[derive(Component)]
-struct Wall;
+pub struct Wall;
 
+#[derive(Component)]
+pub struct Floor;
+
+/// Only the looks of a wall, the collision is done by `WallColliderBundle`
 #[derive(Bundle)]
-struct WallBundle {
+pub(crate) struct WallBundle {
 	#[bundle]
 	sprite_bundle: SpriteBundle,
-	collider: TileCollider,
-	rapier_collider: Collider,
 	wall: Wall,
+	cullable: CullableTile,
 	name: Name,
 }
 
@@ -64,14 +137,38 @@ impl Default for WallBundle {
 			sprite_bundle: SpriteBundle {
 				..Default::default()
 			},
-			collider: TileCollider,
 			wall: Wall,
-			rapier_collider: Collider::cuboid(TILE_SIZE / 2.0, TILE_SIZE / 2.0),
+			cullable: CullableTile,
 			name: Name::new("Wall"),
 		}
 	}
 }
 
+#[derive(Bundle)]
+struct WallColliderBundle {
+	#[bundle]
+	transform_bundle: TransformBundle,
+	collider: TileCollider,
+	rapier_collider: Collider,
+	name: Name,
+}
+
+impl WallColliderBundle {
+	/// # Arguments
+	/// `min` and `size` are in tiles, with y going down like in Tiled.
+	fn new(min: IVec2, size: IVec2) -> Self {
+		let center = (min.as_vec2() + (size - IVec2::ONE).as_vec2() / 2.0) * Vec2::new(1.0, -1.0) * TILE_SIZE;
+		let half_size = size.as_vec2() * TILE_SIZE / 2.0;
+
+		Self {
+			transform_bundle: TransformBundle::from_transform(Transform::from_translation(center.extend(10.0))),
+			collider: TileCollider { half_size },
+			rapier_collider: Collider::cuboid(half_size.x, half_size.y),
+			name: Name::new("WallCollider"),
+		}
+	}
+}
+
 impl Tile for WallBundle {
 	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
 		Self {
@@ -91,9 +188,11 @@ impl Tile for WallBundle {
 }
 
 #[derive(Bundle)]
-struct FloorBundle {
+pub(crate) struct FloorBundle {
 	#[bundle]
 	sprite_bundle: SpriteBundle,
+	floor: Floor,
+	cullable: CullableTile,
 	name: Name,
 }
 
@@ -101,6 +200,8 @@ impl Default for FloorBundle {
 	fn default() -> Self {
 		Self {
 			sprite_bundle: Default::default(),
+			floor: Floor,
+			cullable: CullableTile,
 			name: Name::new("Floor"),
 		}
 	}
@@ -181,16 +282,103 @@ fn load_level(
 	mut textures: ResMut<TexturesMemo>,
 	mut nav_mesh: ResMut<EnemyNavMesh>,
 	mut win_materials: ResMut<Assets<WinMaterial>>,
+	mut map_bounds: ResMut<MapBounds>,
+	mut win_goal: ResMut<WinGoal>,
+	enemy_types: Res<EnemyTypes>,
+	level_source: Res<LevelSource>,
 ) {
+	let entities = spawn_level(
+		&mut commands,
+		&asset_server,
+		&mut textures,
+		&mut nav_mesh,
+		&mut win_materials,
+		&mut map_bounds,
+		&mut win_goal,
+		&enemy_types,
+		*level_source,
+		true,
+	);
+
+	spawn_tilemap(&mut commands, &entities);
+}
+
+/// Spawns every entity of the level, builds its nav mesh and sets its win goal.
+/// # Returns
+/// The spawned entities, for them to be put under a tilemap.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_level(
+	commands: &mut Commands,
+	asset_server: &Res<AssetServer>,
+	textures: &mut TexturesMemo,
+	nav_mesh: &mut EnemyNavMesh,
+	win_materials: &mut Assets<WinMaterial>,
+	map_bounds: &mut MapBounds,
+	win_goal: &mut WinGoal,
+	enemy_types: &EnemyTypes,
+	level_source: LevelSource,
+	spawn_player: bool,
+) -> Vec<Entity> {
+	if let LevelSource::Generated { seed } = level_source {
+		info!("Generating a level with the seed {}", seed);
+
+		let level = generate_level(seed);
+
+		// Generated levels always have an exit and never any spawn tiles
+		*win_goal = WinGoal::ReachExit;
+
+		let mut entities = spawn_generated_level(
+			commands,
+			&level,
+			textures,
+			asset_server,
+			win_materials,
+			enemy_types,
+			spawn_player,
+		);
+
+		let is_wall_at = |x: i32, y: i32| level.walls.contains(&IVec2::new(x, y));
+
+		for floor in level.floors.iter() {
+			register_nav_rect(nav_mesh, floor.x, floor.y, is_wall_at);
+		}
+
+		nav_mesh.bake();
+
+		entities.extend(spawn_wall_colliders(commands, &level.walls));
+
+		// The walls surround everything else
+		let min = level.walls.iter().fold(IVec2::splat(i32::MAX), |min, wall| min.min(*wall));
+		let max = level.walls.iter().fold(IVec2::splat(i32::MIN), |max, wall| max.max(*wall));
+
+		*map_bounds = MapBounds {
+			min: Vec2::new(min.x as f32, -max.y as f32) * TILE_SIZE - Vec2::splat(TILE_SIZE / 2.0),
+			max: Vec2::new(max.x as f32, -min.y as f32) * TILE_SIZE + Vec2::splat(TILE_SIZE / 2.0),
+		};
+
+		return entities;
+	}
+
 	let (map, tileset) = load_tilemap();
 
+	*win_goal = match map.properties.get("goal") {
+		Some(PropertyValue::StringValue(name)) => WinGoal::from_name(name),
+		_ => None,
+	}
+	.unwrap_or(WinGoal::ReachExit);
+
+	let mut min_tile = Vec2::splat(f32::MAX);
+	let mut max_tile = Vec2::splat(f32::MIN);
+
 	let layers = map.layers();
 
 	let mut entities = Vec::new();
+	let mut wall_tiles = HashSet::new();
 
 	let is_wall_at = |x: i32, y: i32| {
 		let wall_layer = map
-			.get_layer(1)
+			.layers()
+			.find(|layer| layer.name == "Wall Layer")
 			.expect("There is no wall layer in the map file!");
 
 		match wall_layer.layer_type() {
@@ -206,21 +394,10 @@ fn load_level(
 		}
 	};
 
-	let mut register_nav_mesh = |x, y| {
-		let top = (y as f32 + if !is_wall_at(x, y - 1) { -0.5 } else { 0.0 }) * TILE_SIZE;
-		let bottom = (y as f32 + if !is_wall_at(x, y + 1) { 0.5 } else { 0.0 }) * TILE_SIZE;
-		let left = (x as f32 + if !is_wall_at(x - 1, y) { -0.5 } else { 0.0 }) * TILE_SIZE;
-		let right = (x as f32 + if !is_wall_at(x + 1, y) { 0.5 } else { 0.0 }) * TILE_SIZE;
-
-		nav_mesh.insert_rect(
-			Vec2::new(left, -top),
-			Vec2::new(right, -top),
-			Vec2::new(right, -bottom),
-			Vec2::new(left, -bottom),
-		);
-	};
+	for layer in layers {
+		// Layers go by their name in Tiled, so that they can be added and moved around in any order
+		let layer_name = layer.name.clone();
 
-	for (layer_num, layer) in layers.enumerate() {
 		match layer.layer_type() {
 			LayerType::Tiles(layer) => {
 				match layer {
@@ -237,6 +414,9 @@ fn load_level(
 											-(chunk_pos.1 * Chunk::HEIGHT as i32 + y) as f32,
 										) * TILE_SIZE;
 
+										min_tile = min_tile.min(tile_pos);
+										max_tile = max_tile.max(tile_pos);
+
 										let (flip_x, flip_y) = (
 											tile.flip_h || tile.flip_d,
 											tile.flip_v || tile.flip_d,
@@ -249,76 +429,156 @@ fn load_level(
 											// .unwrap()
 											// .source.display());
 
-											let image_source = tile
-												.image
-												.as_ref()
-												.unwrap()
-												.source
-												.strip_prefix("..")
-												.expect("what")
-												.to_path_buf();
-
-											let image_source = Path::new("./").join(image_source);
+											let image_source = tile_image_source(&tile);
 
 											entities.push(
-												match layer_num {
-													0 => {
-														// Floor layer
-														register_nav_mesh(
+												match layer_name.as_str() {
+													"Floor Layer" => {
+														register_nav_rect(
+															nav_mesh,
 															chunk_pos.0 * Chunk::WIDTH as i32 + x,
 															chunk_pos.1 * Chunk::HEIGHT as i32 + y,
+															is_wall_at,
 														);
 
-														commands.spawn_bundle(FloorBundle::spawn(
-															tile_pos,
-															textures
-																.get(&image_source, &asset_server),
-															flip_x,
-															flip_y,
-														)).id()
+														let hazard_mode = match tile.properties.get("hazard") {
+															Some(PropertyValue::StringValue(name)) => HazardMode::from_name(name),
+															_ => None,
+														};
+
+														if let Some(hazard_mode) = hazard_mode {
+															commands.spawn_bundle(HazardBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															).with_mode(hazard_mode)).id()
+														} else if let Some(PropertyValue::FloatValue(factor)) = tile.properties.get("slow") {
+															commands.spawn_bundle(SlowTileBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															).with_factor(*factor)).id()
+														} else if let Some(PropertyValue::IntValue(link)) = tile.properties.get("teleporter") {
+															commands.spawn_bundle(TeleporterBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															).with_link(*link)).id()
+														} else if let Some(PropertyValue::BoolValue(true)) = tile.properties.get("ice") {
+															commands.spawn_bundle(IceTileBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															)).id()
+														} else {
+															commands.spawn_bundle(FloorBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															)).id()
+														}
 													}
-													1 => {
-														// Wall layer
-														commands.spawn_bundle(WallBundle::spawn(
-															tile_pos,
-															textures
-																.get(&image_source, &asset_server),
-															flip_x,
-															flip_y,
-														)).id()
+													"Wall Layer" => {
+														let tile_x = chunk_pos.0 * Chunk::WIDTH as i32 + x;
+														let tile_y = chunk_pos.1 * Chunk::HEIGHT as i32 + y;
+
+														let lock = match tile.properties.get("lock") {
+															Some(PropertyValue::StringValue(name)) => KeyColor::from_name(name),
+															_ => None,
+														};
+
+														// A lock makes a door no matter the "door" property
+														let door_mode = match tile.properties.get("door") {
+															Some(PropertyValue::StringValue(name)) => DoorMode::from_name(name),
+															_ => None,
+														};
+														let door_mode = lock.map(DoorMode::Locked).or(door_mode);
+
+														if let Some(door_mode) = door_mode {
+															// Slide into whichever wall the door is set in, y goes up in the world but down in the map
+															let slide_direction = if is_wall_at(tile_x - 1, tile_y) {
+																Vec2::new(-1.0, 0.0)
+															} else if is_wall_at(tile_x + 1, tile_y) {
+																Vec2::new(1.0, 0.0)
+															} else if is_wall_at(tile_x, tile_y - 1) {
+																Vec2::new(0.0, 1.0)
+															} else {
+																Vec2::new(0.0, -1.0)
+															};
+
+															commands.spawn_bundle(DoorBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															).with_mode(door_mode).sliding_towards(slide_direction)).id()
+														} else if let Some(PropertyValue::BoolValue(true)) = tile.properties.get("destructible") {
+															commands.spawn_bundle(DestructibleWallBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															)).id()
+														} else {
+															wall_tiles.insert(IVec2::new(tile_x, tile_y));
+
+															commands.spawn_bundle(WallBundle::spawn(
+																tile_pos,
+																textures
+																	.get(&image_source, asset_server),
+																flip_x,
+																flip_y,
+															)).id()
+														}
 													}
-													2 => {
-														// Player layer
+													// The player stays where they are when the level is reloaded
+													"Player Layer" if !spawn_player => continue,
+													"Player Layer" => {
 														commands.spawn_bundle(PlayerBundle::spawn(
 															tile_pos,
 															textures
-																.get(&image_source, &asset_server),
+																.get(&image_source, asset_server),
 															flip_x,
 															flip_y,
 														)).id()
 													}
-													3 => {
-														// Enemy layer
+													"Enemy Layer" => {
+														let enemy_type = match tile.properties.get("enemy_type") {
+															Some(PropertyValue::StringValue(name)) => EnemyType::from_name(name),
+															_ => None,
+														}
+														.unwrap_or(EnemyType::Grunt);
+
 														commands.spawn_bundle(EnemyBundle::spawn(
 															tile_pos,
 															textures
-																.get(&image_source, &asset_server),
+																.get(&image_source, asset_server),
 															flip_x,
 															flip_y,
-														)).id()
+														).with_stats(enemy_types.get(enemy_type))).id()
 													}
-													4 => {
-														// Cocaine layer
+													"Cocaine Layer" => {
 														commands.spawn_bundle(CocaineBundle::spawn(
 															tile_pos,
 															textures
-																.get(&image_source, &asset_server),
+																.get(&image_source, asset_server),
 															flip_x,
 															flip_y,
 														)).id()
 													}
-													5 => {
-														// Details layer
+													"Detail Layer" => {
 														commands.spawn_bundle(SpriteBundle {
 															transform: Transform::from_translation(
 																tile_pos.extend(20.0),
@@ -329,17 +589,16 @@ fn load_level(
 																..Default::default()
 															},
 															texture: textures
-																.get(&image_source, &asset_server),
+																.get(&image_source, asset_server),
 															..Default::default()
-														}).id()
+														}).insert(CullableTile).id()
 													}
-													6 => {
-														// Win layer
+													"Win Layer" => {
 														let material =
 															win_materials.add(WinMaterial {
 																source_image: textures.get(
 																	&image_source,
-																	&asset_server,
+																	asset_server,
 																),
 																time: 0,
 															});
@@ -347,16 +606,102 @@ fn load_level(
 														commands.spawn_bundle(WinBundle::spawn(
 															tile_pos,
 															textures
-																.get(&image_source, &asset_server),
+																.get(&image_source, asset_server),
 															flip_x,
 															flip_y,
 														)).insert(material).id()
 													}
-													_ => {
-														panic!("Too much layers in the level file");
+													"Health Pickup Layer" => {
+														commands.spawn_bundle(HealthPickupBundle::spawn(
+															tile_pos,
+															textures
+																.get(&image_source, asset_server),
+															flip_x,
+															flip_y,
+														)).id()
+													}
+													"Ammo Pickup Layer" => {
+														commands.spawn_bundle(AmmoPickupBundle::spawn(
+															tile_pos,
+															textures
+																.get(&image_source, asset_server),
+															flip_x,
+															flip_y,
+														)).id()
+													}
+													"Key Layer" => {
+														let color = match tile.properties.get("key") {
+															Some(PropertyValue::StringValue(name)) => KeyColor::from_name(name),
+															_ => None,
+														}
+														.unwrap_or(KeyColor::Red);
+
+														commands.spawn_bundle(KeyPickupBundle::spawn(
+															tile_pos,
+															textures
+																.get(&image_source, asset_server),
+															flip_x,
+															flip_y,
+														).with_color(color)).id()
+													}
+													"Chest Layer" => {
+														let tier = match tile.properties.get("chest") {
+															Some(PropertyValue::StringValue(name)) => ChestTier::from_name(name),
+															_ => None,
+														}
+														.unwrap_or(ChestTier::Common);
+
+														commands.spawn_bundle(ChestBundle::spawn(
+															tile_pos,
+															textures
+																.get(&image_source, asset_server),
+															flip_x,
+															flip_y,
+														).with_tier(tier)).id()
+													}
+													"Spawn Layer" => {
+														commands.spawn_bundle(EnemySpawnPointBundle::spawn(
+															tile_pos,
+															textures
+																.get(&image_source, asset_server),
+															flip_x,
+															flip_y,
+														)).id()
+													}
+													"Boss Layer" => {
+														commands.spawn_bundle(BossBundle::spawn(
+															tile_pos,
+															textures
+																.get(&image_source, asset_server),
+															flip_x,
+															flip_y,
+														)).id()
+													}
+													other => {
+														panic!("Unknown layer \"{}\" in the level file", other);
 													}
 												},
 											);
+
+											// Only the sprite's texture gets swapped around, the tile stays the same
+											if let Some(animation) = &tile.animation {
+												let frames: Vec<_> = animation
+													.iter()
+													.filter_map(|frame| {
+														let frame_tile = tileset.get_tile(frame.tile_id)?;
+
+														Some((
+															textures.get(&tile_image_source(&frame_tile), asset_server),
+															frame.duration as f32 / 1000.0,
+														))
+													})
+													.collect();
+
+												if !frames.is_empty() {
+													let entity = *entities.last().unwrap();
+													commands.entity(entity).insert(AnimatedTile::new(frames));
+												}
+											}
 										}
 									}
 								}
@@ -371,6 +716,150 @@ fn load_level(
 
 	nav_mesh.bake();
 
+	entities.extend(spawn_wall_colliders(commands, &wall_tiles));
+
+	// Tile positions are their centers
+	*map_bounds = MapBounds {
+		min: min_tile - Vec2::splat(TILE_SIZE / 2.0),
+		max: max_tile + Vec2::splat(TILE_SIZE / 2.0),
+	};
+
+	entities
+}
+
+/// Adds the nav mesh rectangle of the floor tile at the given tile coordinates.
+fn register_nav_rect(nav_mesh: &mut EnemyNavMesh, x: i32, y: i32, is_wall_at: impl Fn(i32, i32) -> bool) {
+	let top = (y as f32 + if !is_wall_at(x, y - 1) { -0.5 } else { 0.0 }) * TILE_SIZE;
+	let bottom = (y as f32 + if !is_wall_at(x, y + 1) { 0.5 } else { 0.0 }) * TILE_SIZE;
+	let left = (x as f32 + if !is_wall_at(x - 1, y) { -0.5 } else { 0.0 }) * TILE_SIZE;
+	let right = (x as f32 + if !is_wall_at(x + 1, y) { 0.5 } else { 0.0 }) * TILE_SIZE;
+
+	nav_mesh.insert_rect(
+		Vec2::new(left, -top),
+		Vec2::new(right, -top),
+		Vec2::new(right, -bottom),
+		Vec2::new(left, -bottom),
+	);
+}
+
+fn spawn_generated_level(
+	commands: &mut Commands,
+	level: &GeneratedLevel,
+	textures: &mut TexturesMemo,
+	asset_server: &Res<AssetServer>,
+	win_materials: &mut Assets<WinMaterial>,
+	enemy_types: &EnemyTypes,
+	spawn_player: bool,
+) -> Vec<Entity> {
+	let mut texture = |path: &str| textures.get(&PathBuf::from(path), asset_server);
+	// Same layout as Tiled, y goes down
+	let position = |tile: &IVec2| Vec2::new(tile.x as f32, -tile.y as f32) * TILE_SIZE;
+
+	let mut entities = Vec::new();
+
+	for floor in level.floors.iter() {
+		entities.push(
+			commands
+				.spawn_bundle(FloorBundle::spawn(position(floor), texture("img/concrete.png"), false, false))
+				.id(),
+		);
+	}
+
+	// There are no autotiling rules for the wall pieces, pillars fit together from any side
+	for wall in level.walls.iter() {
+		entities.push(
+			commands
+				.spawn_bundle(WallBundle::spawn(position(wall), texture("img/pillar.png"), false, false))
+				.id(),
+		);
+	}
+
+	if spawn_player {
+		entities.push(
+			commands
+				.spawn_bundle(PlayerBundle::spawn(position(&level.player), texture("img/player.png"), false, false))
+				.id(),
+		);
+	}
+
+	for enemy in level.enemies.iter() {
+		entities.push(
+			commands
+				.spawn_bundle(
+					EnemyBundle::spawn(position(enemy), texture("img/enemy_idle.png"), false, false)
+						.with_stats(enemy_types.get(EnemyType::Grunt)),
+				)
+				.id(),
+		);
+	}
+
+	let exit_texture = texture("img/fun_dust.png");
+	let material = win_materials.add(WinMaterial {
+		source_image: exit_texture.clone(),
+		time: 0,
+	});
+
+	entities.push(
+		commands
+			.spawn_bundle(WinBundle::spawn(position(&level.exit), exit_texture, false, false))
+			.insert(material)
+			.id(),
+	);
+
+	entities
+}
+
+/// Covers the walls with as few rectangle colliders as it can, so that the physics
+/// and the wall checks don't have to go through every single wall tile.
+fn spawn_wall_colliders(commands: &mut Commands, walls: &HashSet<IVec2>) -> Vec<Entity> {
+	merge_wall_tiles(walls)
+		.into_iter()
+		.map(|(min, size)| commands.spawn_bundle(WallColliderBundle::new(min, size)).id())
+		.collect()
+}
+
+/// Greedy meshing: grows every rectangle as far right as it can, then as far down as the whole width allows.
+/// # Returns
+/// The top left tile and the size of every rectangle.
+fn merge_wall_tiles(walls: &HashSet<IVec2>) -> Vec<(IVec2, IVec2)> {
+	let mut tiles: Vec<IVec2> = walls.iter().copied().collect();
+	tiles.sort_by_key(|tile| (tile.y, tile.x));
+
+	let mut covered = HashSet::new();
+	let mut rectangles = Vec::new();
+
+	let is_free = |tile: IVec2, covered: &HashSet<IVec2>| walls.contains(&tile) && !covered.contains(&tile);
+
+	for start in tiles {
+		if covered.contains(&start) {
+			continue;
+		}
+
+		let mut width = 1;
+
+		while is_free(start + IVec2::new(width, 0), &covered) {
+			width += 1;
+		}
+
+		let mut height = 1;
+
+		while (0..width).all(|x| is_free(start + IVec2::new(x, height), &covered)) {
+			height += 1;
+		}
+
+		for x in 0..width {
+			for y in 0..height {
+				covered.insert(start + IVec2::new(x, y));
+			}
+		}
+
+		rectangles.push((start, IVec2::new(width, height)));
+	}
+
+	rectangles
+}
+
+pub(crate) fn spawn_tilemap(commands: &mut Commands, entities: &[Entity]) {
 	commands
 		.spawn()
 		.insert(Name::new("Tilemap"))
@@ -379,10 +868,152 @@ fn load_level(
 		.insert(Transform::default())
 		.insert(GlobalTransform::default())
 		.insert(Tilemap)
-		.push_children(&entities);
+		.push_children(entities);
+}
+
+fn cull_tiles(
+	mut tiles: Query<(&Transform, &mut Visibility), With<CullableTile>>,
+	camera: Query<(&Transform, &OrthographicProjection), (With<MainCamera>, Without<CullableTile>)>,
+) {
+	let (camera_transform, projection) = camera.single();
+	let camera_position = camera_transform.translation.truncate();
+
+	// A tile of margin, so that the tiles at the edges don't pop in
+	let visible_half_size = view_half_size(projection) + Vec2::splat(TILE_SIZE);
+
+	for (transform, mut visibility) in tiles.iter_mut() {
+		let offset = (transform.translation.truncate() - camera_position).abs();
+		let is_visible = offset.x <= visible_half_size.x && offset.y <= visible_half_size.y;
+
+		// Writing every frame would mark every tile as changed
+		if visibility.is_visible != is_visible {
+			visibility.is_visible = is_visible;
+		}
+	}
+}
+
+fn animate_tiles(
+	mut tiles: Query<(&mut AnimatedTile, &mut Handle<Image>)>,
+	time: Res<TimeCounter>,
+) {
+	for (mut animated_tile, mut texture) in tiles.iter_mut() {
+		if !animated_tile.timer.tick(time.delta()).finished() {
+			continue;
+		}
+
+		animated_tile.current = (animated_tile.current + 1) % animated_tile.frames.len();
+
+		let (frame_texture, duration) = animated_tile.frames[animated_tile.current].clone();
+
+		*texture = frame_texture;
+		animated_tile.timer = Timer::from_seconds(duration, false);
+	}
+}
+
+/// Where the image of a tile from the tileset is, relative to the assets folder
+fn tile_image_source(tile: &tiled::TileData) -> PathBuf {
+	let image_source = tile
+		.image
+		.as_ref()
+		.unwrap()
+		.source
+		.strip_prefix("..")
+		.expect("what")
+		.to_path_buf();
+
+	Path::new("./").join(image_source)
 }
 
 fn drop_level(mut commands: Commands, tilemap: Query<Entity, With<Tilemap>>) {
 	let tilemap = tilemap.single();
 	commands.entity(tilemap).despawn_recursive();
 }
+
+#[cfg(test)]
+mod tests {
+	use bevy::sprite::collide_aabb::collide;
+
+	use super::*;
+
+	/// A long wall with a branch going down from it and a thick block at its end, in tiles
+	fn long_wall() -> HashSet<IVec2> {
+		let mut walls = HashSet::new();
+
+		for x in 0..30 {
+			walls.insert(IVec2::new(x, 0));
+		}
+
+		for y in 1..6 {
+			walls.insert(IVec2::new(10, y));
+		}
+
+		for x in 26..30 {
+			for y in 1..3 {
+				walls.insert(IVec2::new(x, y));
+			}
+		}
+
+		walls
+	}
+
+	/// Center and size of every collider, as `collide` takes them
+	fn collider_boxes(rectangles: impl Iterator<Item = (IVec2, IVec2)>) -> Vec<(Vec3, Vec2)> {
+		rectangles
+			.map(|(min, size)| {
+				let collider = WallColliderBundle::new(min, size);
+
+				(collider.transform_bundle.local.translation, collider.collider.half_size * 2.0)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn merged_rectangles_cover_every_wall_once() {
+		let walls = long_wall();
+		let mut covered = HashSet::new();
+
+		for (min, size) in merge_wall_tiles(&walls) {
+			for x in 0..size.x {
+				for y in 0..size.y {
+					let tile = min + IVec2::new(x, y);
+
+					assert!(walls.contains(&tile), "{} is covered but isn't a wall", tile);
+					assert!(covered.insert(tile), "{} is covered twice", tile);
+				}
+			}
+		}
+
+		assert_eq!(covered, walls);
+	}
+
+	#[test]
+	fn straight_wall_merges_into_one_rectangle() {
+		let walls: HashSet<IVec2> = (0..30).map(|x| IVec2::new(x, 0)).collect();
+
+		assert_eq!(merge_wall_tiles(&walls), vec![(IVec2::new(0, 0), IVec2::new(30, 1))]);
+	}
+
+	#[test]
+	fn merged_colliders_collide_like_the_tiles() {
+		let walls = long_wall();
+
+		let merged = collider_boxes(merge_wall_tiles(&walls).into_iter());
+		let tiles = collider_boxes(walls.iter().map(|tile| (*tile, IVec2::ONE)));
+
+		assert!(merged.len() < tiles.len());
+
+		let probe_size = Vec2::splat(TILE_SIZE * 0.8);
+
+		// Off the tile grid by an odd amount, so that no probe lines up exactly with a collider's edge
+		for x in -12..130 {
+			for y in -30..12 {
+				let probe = Vec3::new(x as f32 * TILE_SIZE / 4.0 + 0.37, y as f32 * TILE_SIZE / 4.0 + 0.37, 10.0);
+
+				let hits_merged = merged.iter().any(|(position, size)| collide(probe, probe_size, *position, *size).is_some());
+				let hits_tiles = tiles.iter().any(|(position, size)| collide(probe, probe_size, *position, *size).is_some());
+
+				assert_eq!(hits_merged, hits_tiles, "The colliders disagree at {}", probe);
+			}
+		}
+	}
+}
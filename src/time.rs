@@ -2,8 +2,17 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
+/// Longest frame the game clock lets through, at the player's base speed that's a tile
+pub const MAX_DELTA: f32 = 0.1;
+
 pub struct TimeCounter {
+	/// Changed by effects like the powerups and bullet time, which multiply it and divide it back
 	pub timescale: f32,
+	/// Speeds up or slows down the whole game on top of the effects, for testing. Zero pauses it.
+	pub debug_scale: f32,
+	/// Frames taking longer than this, like when the window gets dragged, are counted as this long,
+	/// so that nothing can jump past a wall in a single frame
+	pub max_delta: f32,
 	delta: f32,
 	seconds_since_startup: f32,
 }
@@ -12,22 +21,48 @@ impl TimeCounter {
 	pub fn new() -> Self {
 		Self {
 			timescale: 1.0,
+			debug_scale: 1.0,
+			max_delta: MAX_DELTA,
 			delta: 0.0,
 			seconds_since_startup: 0.0,
 		}
 	}
 
 	pub fn step(&mut self, delta: f32) {
+		let delta = delta.min(self.max_delta);
+
 		self.delta = delta;
 		self.seconds_since_startup += delta;
 	}
 
+	/// Swaps this frame's delta for another one, like a recorded one when playing back a replay
+	pub fn replace_delta(&mut self, delta: f32) {
+		let delta = delta.min(self.max_delta);
+
+		self.seconds_since_startup += delta - self.delta;
+		self.delta = delta;
+	}
+
 	pub fn delta(&self) -> Duration {
 		Duration::from_millis((self.delta_seconds() * 1000.0) as u64)
 	}
 
 	pub fn delta_seconds(&self) -> f32 {
-		self.timescale * self.delta
+		self.scale() * self.delta
+	}
+
+	/// How fast the game runs compared to real time
+	pub fn scale(&self) -> f32 {
+		self.timescale * self.debug_scale
+	}
+
+	/// The frame time without the timescale, for things that shouldn't slow down with the game
+	pub fn real_delta(&self) -> Duration {
+		Duration::from_secs_f32(self.real_delta_seconds())
+	}
+
+	pub fn real_delta_seconds(&self) -> f32 {
+		self.delta
 	}
 
 	pub fn seconds_since_startup(&self) -> f32 {
@@ -42,7 +77,7 @@ impl Plugin for TimePlugin {
 		app.add_startup_system(register_time)
 			.add_system_set_to_stage(
 				CoreStage::PreUpdate,
-				SystemSet::new().with_system(update_time),
+				SystemSet::new().with_system(update_time.label("update_time")),
 			);
 	}
 }
@@ -54,3 +89,48 @@ fn register_time(mut commands: Commands) {
 fn update_time(mut time: ResMut<TimeCounter>, game_time: Res<Time>) {
 	time.step(game_time.delta_seconds());
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn huge_delta_is_capped() {
+		let mut time = TimeCounter::new();
+
+		time.step(5.0);
+
+		assert_eq!(time.real_delta_seconds(), MAX_DELTA);
+		assert_eq!(time.seconds_since_startup(), MAX_DELTA);
+	}
+
+	#[test]
+	fn normal_delta_goes_through() {
+		let mut time = TimeCounter::new();
+
+		time.step(0.016);
+
+		assert_eq!(time.real_delta_seconds(), 0.016);
+	}
+
+	#[test]
+	fn cap_comes_before_the_timescale() {
+		let mut time = TimeCounter::new();
+		time.timescale = 2.0;
+
+		time.step(5.0);
+
+		assert_eq!(time.delta_seconds(), MAX_DELTA * 2.0);
+	}
+
+	#[test]
+	fn replaced_delta_is_capped() {
+		let mut time = TimeCounter::new();
+		time.max_delta = 0.05;
+
+		time.step(0.01);
+		time.replace_delta(1.0);
+
+		assert_eq!(time.real_delta_seconds(), 0.05);
+	}
+}
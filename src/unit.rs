@@ -1,21 +1,492 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_inspector_egui::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
 
+use crate::door::KeyColor;
+use crate::rarity::Rarity;
+use crate::status_effect::StatusEffect;
 use crate::time::TimeCounter;
 
+/// Share of a hit that always gets through, no matter the armor
+pub const MIN_DAMAGE_FRACTION: f32 = 0.2;
+/// Weapon slots a unit has, one for every number key
+pub const MAX_WEAPONS: usize = 9;
+
 #[derive(Default, Reflect, Inspectable, Component)]
 #[reflect(Component)]
 pub struct Movement {
 	pub speed: f32,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShotMode {
+	/// Hits instantly with a raycast
+	Hitscan,
+	/// Spawns bullets that travel across the map
+	Projectile,
+}
+
+/// How the weapon responds to the trigger
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FireMode {
+	/// One shot for every pull
+	Single,
+	/// A few shots in a quick row for every pull, the cooldown starts after the last one
+	Burst { shots: u32, interval: f32 },
+	/// Keeps firing for as long as the trigger is held
+	Auto,
+	/// Charges up while the trigger is held and fires when it's let go.
+	/// The damage gets multiplied by `min_multiplier` for no charge, going up to `max_multiplier` after `max_time` seconds.
+	Charge {
+		max_time: f32,
+		min_multiplier: f32,
+		max_multiplier: f32,
+	},
+}
+
+/// Drops the damage linearly past `effective_range`, down to `min_damage_fraction` at `max_range`
+#[derive(Debug, Clone, Copy)]
+pub struct Falloff {
+	pub effective_range: f32,
+	pub max_range: f32,
+	pub min_damage_fraction: f32,
+}
+
+impl Falloff {
+	/// Damage left of a hit from the given distance.
+	pub fn damage_at(&self, damage: f32, distance: f32) -> f32 {
+		if distance <= self.effective_range {
+			return damage;
+		}
+
+		let falloff_length = (self.max_range - self.effective_range).max(f32::EPSILON);
+		let progress = ((distance - self.effective_range) / falloff_length).min(1.0);
+
+		damage * (1.0 - progress * (1.0 - self.min_damage_fraction))
+	}
+}
+
+/// Heat building up with every shot, in place of ammo.
+/// Going past the limit locks the weapon up until it has cooled down completely.
+#[derive(Debug, Clone)]
+pub struct Overheat {
+	/// From 0 to 1
+	heat: f32,
+	pub heat_per_shot: f32,
+	/// Heat lost every second, once the weapon stopped firing for `dissipation_delay`
+	pub dissipation: f32,
+	pub dissipation_delay: Timer,
+	pub lockout: Timer,
+	locked: bool,
+}
+
+impl Overheat {
+	pub fn new(heat_per_shot: f32, dissipation: f32, dissipation_delay: f32, lockout_time: f32) -> Self {
+		Self {
+			heat: 0.0,
+			heat_per_shot,
+			dissipation,
+			dissipation_delay: Timer::from_seconds(dissipation_delay, false),
+			lockout: Timer::from_seconds(lockout_time, false),
+			locked: false,
+		}
+	}
+
+	pub fn heat(&self) -> f32 {
+		self.heat
+	}
+
+	pub fn is_locked(&self) -> bool {
+		self.locked
+	}
+
+	/// Heats the weapon up for a shot.
+	/// # Returns
+	/// True if the shot overheated it.
+	pub fn add_heat(&mut self) -> bool {
+		self.heat = (self.heat + self.heat_per_shot).min(1.0);
+		self.dissipation_delay.reset();
+
+		if self.heat >= 1.0 {
+			self.locked = true;
+			self.lockout.reset();
+		}
+
+		self.locked
+	}
+
+	pub fn tick(&mut self, delta: Duration) {
+		if self.locked {
+			// Cools down all the way over the lockout
+			self.lockout.tick(delta);
+			self.heat = 1.0 - self.lockout.percent();
+
+			if self.lockout.finished() {
+				self.locked = false;
+				self.heat = 0.0;
+			}
+
+			return;
+		}
+
+		if self.dissipation_delay.tick(delta).finished() {
+			self.heat = (self.heat - self.dissipation * delta.as_secs_f32()).max(0.0);
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct Weapon {
+	pub name: &'static str,
+	/// Common unless the weapon was dropped as loot
+	pub rarity: Rarity,
+	pub cooldown: Timer,
+	pub range: f32,
+	pub damage: f32,
+	pub mode: ShotMode,
+	pub fire_mode: FireMode,
+	/// Shots left of the burst being fired
+	pub burst_left: u32,
+	/// Time until the next shot of the burst
+	pub burst_timer: Timer,
+	/// Seconds the trigger has been held for, `None` unless the weapon is charging
+	pub charge: Option<f32>,
+	/// Speed the target is pushed away with, for every pellet that hits
+	pub knockback: f32,
+	/// Bullets fired at once, fanned out evenly over `spread_angle` radians
+	pub pellet_count: u32,
+	pub spread_angle: f32,
+	pub magazine_size: u32,
+	pub current_ammo: u32,
+	/// Spare rounds reloads take from, `None` if they're unlimited
+	pub reserve: Option<u32>,
+	pub max_reserve: u32,
+	pub reload: Timer,
+	/// Applied to whatever the weapon hits
+	pub status_effect: Option<StatusEffect>,
+	/// From 0 to 1
+	pub crit_chance: f32,
+	pub crit_multiplier: f32,
+	pub falloff: Option<Falloff>,
+	/// Enemies a shot goes through before it stops, walls always stop it
+	pub pierce_count: u32,
+	/// Weapons that heat up don't use ammo
+	pub overheat: Option<Overheat>,
+}
+
+impl Weapon {
+	pub fn new(cooldown: f32, range: f32, damage: f32, magazine_size: u32, reload_time: f32) -> Self {
+		let mut reload = Timer::from_seconds(reload_time, false);
+
+		// Start with a full magazine instead of reloading
+		let elapsed = reload.duration();
+		reload.tick(elapsed);
+
+		Self {
+			name: "Weapon",
+			rarity: Rarity::Common,
+			cooldown: Timer::from_seconds(cooldown, false),
+			range,
+			damage,
+			mode: ShotMode::Projectile,
+			fire_mode: FireMode::Single,
+			burst_left: 0,
+			burst_timer: Timer::default(),
+			charge: None,
+			knockback: 0.0,
+			pellet_count: 1,
+			spread_angle: 0.0,
+			magazine_size,
+			current_ammo: magazine_size,
+			reserve: None,
+			max_reserve: 0,
+			reload,
+			status_effect: None,
+			crit_chance: 0.0,
+			crit_multiplier: 1.0,
+			falloff: None,
+			pierce_count: 0,
+			overheat: None,
+		}
+	}
+
+	pub fn with_name(mut self, name: &'static str) -> Self {
+		self.name = name;
+		self
+	}
+
+	/// From 0 to 1, `None` unless the weapon is charging
+	pub fn charge_fraction(&self) -> Option<f32> {
+		match (self.fire_mode, self.charge) {
+			(FireMode::Charge { max_time, .. }, Some(charge)) => Some((charge / max_time.max(f32::EPSILON)).min(1.0)),
+			_ => None,
+		}
+	}
+
+	/// Lets go of the charge.
+	/// # Returns
+	/// What the shot's damage gets multiplied by, 1 for the weapons that don't charge.
+	pub fn release_charge(&mut self) -> f32 {
+		let fraction = self.charge_fraction().unwrap_or(0.0);
+		self.charge = None;
+
+		match self.fire_mode {
+			FireMode::Charge {
+				min_multiplier,
+				max_multiplier,
+				..
+			} => min_multiplier + (max_multiplier - min_multiplier) * fraction,
+			_ => 1.0,
+		}
+	}
+
+	/// Name shown when the weapon is picked up, like "Rare Shotgun"
+	pub fn display_name(&self) -> String {
+		format!("{} {}", self.rarity.name(), self.name)
+	}
+
+	/// Limits the spare ammo, starting with a full reserve.
+	pub fn with_reserve(mut self, max_reserve: u32) -> Self {
+		self.reserve = Some(max_reserve);
+		self.max_reserve = max_reserve;
+		self
+	}
+
+	pub fn with_mode(mut self, mode: ShotMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	pub fn with_fire_mode(mut self, fire_mode: FireMode) -> Self {
+		self.fire_mode = fire_mode;
+		self
+	}
+
+	pub fn with_knockback(mut self, knockback: f32) -> Self {
+		self.knockback = knockback;
+		self
+	}
+
+	pub fn with_crit(mut self, crit_chance: f32, crit_multiplier: f32) -> Self {
+		self.crit_chance = crit_chance;
+		self.crit_multiplier = crit_multiplier;
+		self
+	}
+
+	/// Makes hits past `effective_range` weaker, down to `min_damage_fraction` of the damage at the weapon's range.
+	pub fn with_falloff(mut self, effective_range: f32, min_damage_fraction: f32) -> Self {
+		self.falloff = Some(Falloff {
+			effective_range,
+			max_range: self.range,
+			min_damage_fraction,
+		});
+		self
+	}
+
+	pub fn with_pierce(mut self, pierce_count: u32) -> Self {
+		self.pierce_count = pierce_count;
+		self
+	}
+
+	pub fn with_overheat(mut self, overheat: Overheat) -> Self {
+		self.overheat = Some(overheat);
+		self
+	}
+
+	/// # Returns
+	/// True if the weapon is too hot to fire.
+	pub fn is_overheated(&self) -> bool {
+		self.overheat.as_ref().map_or(false, |overheat| overheat.is_locked())
+	}
+
+	/// Damage of a single shot, rolling for a crit with the given random number generator.
+	///
+	/// # Returns
+	/// The damage and whether it's a critical hit.
+	pub fn roll_damage(&self, rng: &mut impl Rng) -> (f32, bool) {
+		if rng.gen::<f32>() < self.crit_chance {
+			(self.damage * self.crit_multiplier, true)
+		} else {
+			(self.damage, false)
+		}
+	}
+
+	pub fn with_spread(mut self, pellet_count: u32, spread_angle: f32) -> Self {
+		self.pellet_count = pellet_count;
+		self.spread_angle = spread_angle;
+		self
+	}
+
+	/// Angle of the given pellet relative to the aim direction.
+	pub fn pellet_angle(&self, pellet: u32) -> f32 {
+		if self.pellet_count <= 1 {
+			return 0.0;
+		}
+
+		self.spread_angle * (pellet as f32 / (self.pellet_count - 1) as f32 - 0.5)
+	}
+
+	pub fn is_reloading(&self) -> bool {
+		!self.reload.finished()
+	}
+
+	/// # Returns
+	/// True if the magazine is empty or being reloaded. The weapons that heat up never run out.
+	pub fn is_out_of_ammo(&self) -> bool {
+		self.overheat.is_none() && (self.current_ammo == 0 || self.is_reloading())
+	}
+
+	/// Takes a round out of the magazine for a shot.
+	pub fn use_round(&mut self) {
+		self.current_ammo = self.current_ammo.saturating_sub(1);
+	}
+
+	/// # Returns
+	/// True if the magazine isn't full and there's ammo left to put in it.
+	pub fn can_reload(&self) -> bool {
+		self.current_ammo < self.magazine_size && self.reserve != Some(0)
+	}
+
+	pub fn start_reload(&mut self) {
+		self.reload.reset();
+	}
+
+	/// Advances the reload and refills the magazine once it's done.
+	pub fn tick_reload(&mut self, delta: Duration) {
+		if self.is_reloading() {
+			self.reload.tick(delta);
+
+			if self.reload.finished() {
+				let missing = self.magazine_size - self.current_ammo;
+
+				let loaded = match self.reserve.as_mut() {
+					Some(reserve) => {
+						let loaded = missing.min(*reserve);
+						*reserve -= loaded;
+						loaded
+					}
+					None => missing,
+				};
+
+				self.current_ammo += loaded;
+			}
+		}
+	}
+
+	/// Adds spare ammo, up to the reserve's capacity.
+	/// # Returns
+	/// True if any of it fit.
+	pub fn add_reserve(&mut self, amount: u32) -> bool {
+		match self.reserve.as_mut() {
+			Some(reserve) if *reserve < self.max_reserve => {
+				*reserve = (*reserve + amount).min(self.max_reserve);
+				true
+			}
+			_ => false,
+		}
+	}
+}
+
+/// The weapons a unit carries. Dereferences to the active one.
 #[derive(Component)]
 pub struct Shooting {
-	pub cooldown: Timer,
+	weapons: Vec<Weapon>,
+	active: usize,
+}
+
+impl Shooting {
+	pub fn new(weapons: Vec<Weapon>) -> Self {
+		assert!(!weapons.is_empty(), "A shooting unit needs at least one weapon!");
+
+		Self { weapons, active: 0 }
+	}
+
+	pub fn single(weapon: Weapon) -> Self {
+		Self::new(vec![weapon])
+	}
+
+	/// # Returns
+	/// True if there is a weapon in the given slot.
+	pub fn switch_to(&mut self, index: usize) -> bool {
+		if index < self.weapons.len() {
+			self.stop_firing();
+			self.active = index;
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Switches by `offset` slots, wrapping around at both ends.
+	pub fn cycle(&mut self, offset: isize) {
+		let count = self.weapons.len() as isize;
+		self.stop_firing();
+		self.active = (self.active as isize + offset).rem_euclid(count) as usize;
+	}
+
+	/// Holstering a weapon cuts its burst or charge short, so that it doesn't go on when it's drawn again
+	fn stop_firing(&mut self) {
+		let weapon = &mut self.weapons[self.active];
+		weapon.burst_left = 0;
+		weapon.charge = None;
+	}
+
+	/// Puts the weapon in a new slot and switches to it.
+	/// Once every slot is taken, it replaces the active weapon instead.
+	pub fn add_weapon(&mut self, weapon: Weapon) {
+		self.stop_firing();
+
+		if self.weapons.len() < MAX_WEAPONS {
+			self.weapons.push(weapon);
+			self.active = self.weapons.len() - 1;
+		} else {
+			self.weapons[self.active] = weapon;
+		}
+	}
+
+	pub fn weapons_mut(&mut self) -> impl Iterator<Item = &mut Weapon> {
+		self.weapons.iter_mut()
+	}
+
+	/// Ticks every weapon, so that holstered ones keep their own cooldowns going.
+	pub fn tick(&mut self, delta: Duration) {
+		for weapon in self.weapons.iter_mut() {
+			weapon.cooldown.tick(delta);
+			weapon.burst_timer.tick(delta);
+			weapon.tick_reload(delta);
+
+			if let Some(overheat) = weapon.overheat.as_mut() {
+				overheat.tick(delta);
+			}
+		}
+	}
+}
+
+impl std::ops::Deref for Shooting {
+	type Target = Weapon;
+
+	fn deref(&self) -> &Weapon {
+		&self.weapons[self.active]
+	}
+}
+
+impl std::ops::DerefMut for Shooting {
+	fn deref_mut(&mut self) -> &mut Weapon {
+		&mut self.weapons[self.active]
+	}
 }
 
 pub struct ShootEvent(pub Vec2);
 
+/// Velocity a unit has been pushed with, decaying over time
+#[derive(Default, Component)]
+pub struct Knockback {
+	pub velocity: Vec2,
+}
+
 #[derive(Default, Reflect, Inspectable, Component)]
 #[reflect(Component)]
 pub struct Health {
@@ -41,7 +512,6 @@ impl Health {
 		self.health <= 0.0
 	}
 
-	#[allow(unused)]
 	pub fn heal(&mut self, amount: f32) {
 		self.health += amount;
 
@@ -63,6 +533,63 @@ impl Health {
 	}
 }
 
+/// Takes a flat amount and then a share off every hit, the default one does nothing
+#[derive(Debug, Default, Clone, Copy, Reflect, Inspectable, Component, Deserialize)]
+#[reflect(Component)]
+pub struct Armor {
+	pub flat: f32,
+	/// From 0 to 1
+	pub percent: f32,
+}
+
+impl Armor {
+	/// # Returns
+	/// The damage left of a hit once the armor took its part, at least `MIN_DAMAGE_FRACTION` of it.
+	pub fn reduce(&self, damage: f32) -> f32 {
+		let reduced = (damage - self.flat) * (1.0 - self.percent);
+
+		reduced.max(damage * MIN_DAMAGE_FRACTION)
+	}
+}
+
+#[derive(Default, Reflect, Inspectable, Component)]
+#[reflect(Component)]
+pub struct Stamina {
+	stamina: f32,
+	max_stamina: f32,
+	pub regen_rate: f32,
+}
+
+impl Stamina {
+	pub fn new(max_stamina: f32, regen_rate: f32) -> Self {
+		Self {
+			stamina: max_stamina,
+			max_stamina,
+			regen_rate,
+		}
+	}
+
+	/// # Returns
+	/// True if the stamina reached zero.
+	pub fn drain(&mut self, amount: f32) -> bool {
+		self.stamina = (self.stamina - amount).max(0.0);
+
+		self.stamina <= 0.0
+	}
+
+	pub fn regenerate(&mut self, amount: f32) {
+		self.stamina = (self.stamina + amount).min(self.max_stamina);
+	}
+
+	pub fn get_stamina(&self) -> f32 {
+		self.stamina
+	}
+
+	pub fn get_max_stamina(&self) -> f32 {
+		self.max_stamina
+	}
+}
+
 pub trait Effect {
 	fn apply(&self, movement: &mut Movement, health: &mut Health, shooting: &mut Shooting, time: &mut TimeCounter);
 	fn finish(&self, movement: &mut Movement, health: &mut Health, shooting: &mut Shooting, time: &mut TimeCounter);
@@ -73,6 +600,10 @@ pub trait Effect {
 pub struct Inventory {
 	small_powerups: usize,
 	big_powerups: usize,
+	/// Keys held of every color, indexed by the color
+	#[reflect(ignore)]
+	#[inspectable(ignore)]
+	keys: [usize; KeyColor::ALL.len()],
 }
 
 #[allow(dead_code)]
@@ -81,6 +612,7 @@ impl Inventory {
 		Self {
 			small_powerups: 1,
 			big_powerups: 0,
+			keys: [0; KeyColor::ALL.len()],
 		}
 	}
 
@@ -117,4 +649,188 @@ impl Inventory {
 	pub fn add_big_powerup(&mut self, amount: usize) {
 		self.big_powerups += amount;
 	}
+
+	pub fn get_key_quantity(&self, color: KeyColor) -> usize {
+		self.keys[color as usize]
+	}
+
+	pub fn add_key(&mut self, color: KeyColor) {
+		self.keys[color as usize] += 1;
+	}
+
+	/// # Returns
+	/// False if there was no key of that color to use up.
+	pub fn use_key(&mut self, color: KeyColor) -> bool {
+		let keys = &mut self.keys[color as usize];
+
+		if *keys == 0 {
+			false
+		} else {
+			*keys -= 1;
+			true
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	use crate::rng::GameRng;
+
+	use super::*;
+
+	fn assert_close(actual: f32, expected: f32) {
+		assert!((actual - expected).abs() < 1e-4, "{} is not {}", actual, expected);
+	}
+
+	#[test]
+	fn enemy_survives_a_hit_smaller_than_its_health() {
+		let mut health = Health::new(100.0);
+
+		assert!(!health.take_damage(30.0));
+		assert_eq!(health.get_health(), 70.0);
+	}
+
+	#[test]
+	fn enemy_dies_once_the_hits_add_up_to_its_health() {
+		let mut health = Health::new(100.0);
+
+		assert!(!health.take_damage(30.0));
+		assert!(!health.take_damage(30.0));
+		assert!(!health.take_damage(30.0));
+		assert!(health.take_damage(30.0));
+	}
+
+	#[test]
+	fn hit_of_exactly_the_health_kills() {
+		let mut health = Health::new(100.0);
+
+		assert!(health.take_damage(100.0));
+	}
+
+	#[test]
+	fn no_armor_takes_nothing_off() {
+		let armor = Armor::default();
+
+		assert_close(armor.reduce(10.0), 10.0);
+	}
+
+	#[test]
+	fn flat_armor_takes_its_amount_off() {
+		let armor = Armor { flat: 5.0, percent: 0.0 };
+
+		assert_close(armor.reduce(20.0), 15.0);
+	}
+
+	#[test]
+	fn flat_armor_goes_before_the_percent() {
+		let armor = Armor { flat: 5.0, percent: 0.5 };
+
+		assert_close(armor.reduce(25.0), 10.0);
+	}
+
+	#[test]
+	fn armor_above_the_damage_still_lets_the_floor_through() {
+		let armor = Armor { flat: 50.0, percent: 0.0 };
+
+		assert_close(armor.reduce(10.0), 10.0 * MIN_DAMAGE_FRACTION);
+	}
+
+	#[test]
+	fn percent_armor_stops_at_the_floor() {
+		let armor = Armor { flat: 0.0, percent: 0.95 };
+
+		assert_close(armor.reduce(100.0), 100.0 * MIN_DAMAGE_FRACTION);
+	}
+
+	#[test]
+	fn sure_crit_always_multiplies() {
+		let weapon = Weapon::new(0.5, 100.0, 10.0, 6, 1.0).with_crit(1.0, 2.5);
+		let mut rng = StdRng::seed_from_u64(0);
+
+		for _ in 0..1000 {
+			let (damage, critical) = weapon.roll_damage(&mut rng);
+
+			assert!(critical);
+			assert_close(damage, 25.0);
+		}
+	}
+
+	#[test]
+	fn no_crit_chance_never_multiplies() {
+		let weapon = Weapon::new(0.5, 100.0, 10.0, 6, 1.0).with_crit(0.0, 2.5);
+		let mut rng = StdRng::seed_from_u64(0);
+
+		for _ in 0..1000 {
+			let (damage, critical) = weapon.roll_damage(&mut rng);
+
+			assert!(!critical);
+			assert_close(damage, 10.0);
+		}
+	}
+
+	#[test]
+	fn same_seed_rolls_the_same_damage() {
+		let weapon = Weapon::new(0.5, 100.0, 10.0, 6, 1.0).with_crit(0.5, 2.5);
+		let mut first = GameRng::from_seed(42);
+		let mut second = GameRng::from_seed(42);
+
+		let first_rolls: Vec<(f32, bool)> = (0..100).map(|_| weapon.roll_damage(&mut first.0)).collect();
+		let second_rolls: Vec<(f32, bool)> = (0..100).map(|_| weapon.roll_damage(&mut second.0)).collect();
+
+		assert_eq!(first_rolls, second_rolls);
+		// Both kinds of roll come up, so the sequence actually got compared
+		assert!(first_rolls.iter().any(|(_, critical)| *critical));
+		assert!(first_rolls.iter().any(|(_, critical)| !*critical));
+	}
+
+	#[test]
+	fn cant_fire_past_the_magazine_without_reloading() {
+		let mut weapon = Weapon::new(0.5, 100.0, 10.0, 6, 1.0).with_reserve(12);
+		let mut shots = 0;
+
+		for _ in 0..10 {
+			if !weapon.is_out_of_ammo() {
+				weapon.use_round();
+				shots += 1;
+			}
+		}
+
+		assert_eq!(shots, 6);
+		assert!(weapon.is_out_of_ammo());
+
+		weapon.start_reload();
+		assert!(weapon.is_out_of_ammo());
+
+		weapon.tick_reload(Duration::from_secs_f32(1.0));
+
+		assert!(!weapon.is_out_of_ammo());
+		assert_eq!(weapon.current_ammo, 6);
+		assert_eq!(weapon.reserve, Some(6));
+	}
+
+	#[test]
+	fn reload_takes_only_what_is_left_in_the_reserve() {
+		let mut weapon = Weapon::new(0.5, 100.0, 10.0, 6, 1.0).with_reserve(4);
+
+		for _ in 0..6 {
+			weapon.use_round();
+		}
+
+		weapon.start_reload();
+		weapon.tick_reload(Duration::from_secs_f32(1.0));
+
+		assert_eq!(weapon.current_ammo, 4);
+		assert_eq!(weapon.reserve, Some(0));
+		assert!(!weapon.can_reload());
+	}
+
+	#[test]
+	fn weapons_that_heat_up_never_run_out_of_ammo() {
+		let weapon = Weapon::new(0.15, 100.0, 10.0, 0, 0.0).with_overheat(Overheat::new(0.1, 0.5, 0.3, 2.0));
+
+		assert!(!weapon.is_out_of_ammo());
+	}
 }
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::enemy::{EnemyBundle, EnemyTextures};
+use crate::enemy_nav_mesh::EnemyNavMesh;
+use crate::enemy_type::{EnemyType, EnemyTypes};
+use crate::player::Player;
+use crate::tilemap::{Tile, Tilemap};
+use crate::time::TimeCounter;
+use crate::GameState;
+
+/// Seconds between two enemies of the same wave coming out
+pub const SPAWN_INTERVAL: f32 = 0.6;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Wave {
+	/// How many enemies of each kind the wave has
+	pub enemies: Vec<(EnemyType, u32)>,
+	/// Seconds to wait after the previous wave was cleared
+	pub delay: f32,
+}
+
+pub struct WaveSpawner {
+	pub waves: Vec<Wave>,
+	/// Number of the wave being fought, starting at 1. 0 before the first one.
+	pub current_wave: usize,
+	/// Enemies of the current wave that still have to be spawned
+	queue: Vec<EnemyType>,
+	wave_delay: Timer,
+	spawn_timer: Timer,
+	finished: bool,
+}
+
+impl WaveSpawner {
+	pub fn new(waves: Vec<Wave>) -> Self {
+		Self {
+			waves,
+			current_wave: 0,
+			queue: Vec::new(),
+			wave_delay: Timer::from_seconds(0.0, false),
+			spawn_timer: Timer::from_seconds(SPAWN_INTERVAL, true),
+			finished: false,
+		}
+	}
+
+	pub fn wave_count(&self) -> usize {
+		self.waves.len()
+	}
+
+	/// # Returns
+	/// True once every wave has been spawned and killed.
+	pub fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	fn reset(&mut self) {
+		*self = Self::new(std::mem::take(&mut self.waves));
+	}
+}
+
+/// Tile the waves come out of
+#[derive(Component, Default)]
+pub struct EnemySpawnPoint;
+
+/// Marks enemies that belong to a wave, so the level's own enemies don't hold it up
+#[derive(Component)]
+pub struct WaveEnemy;
+
+#[derive(Bundle, Default)]
+pub struct EnemySpawnPointBundle {
+	#[bundle]
+	sprite_bundle: SpriteBundle,
+	spawn_point: EnemySpawnPoint,
+}
+
+impl Tile for EnemySpawnPointBundle {
+	fn spawn(position: Vec2, texture: Handle<Image>, flip_x: bool, flip_y: bool) -> Self {
+		Self {
+			sprite_bundle: SpriteBundle {
+				transform: Transform::from_translation(position.extend(0.0)),
+				sprite: Sprite {
+					flip_x,
+					flip_y,
+					..Default::default()
+				},
+				texture,
+				// Only there to be seen in the editor
+				visibility: Visibility { is_visible: false },
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+}
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(WaveSpawner::new(load_waves()))
+			.add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_waves))
+			.add_system_set(SystemSet::on_update(GameState::Game).with_system(spawn_waves));
+	}
+}
+
+fn load_waves() -> Vec<Wave> {
+	// Baked into the binary, the same way as the enemy types
+	let source = include_str!("../assets/data/waves.ron");
+
+	match ron::from_str(source) {
+		Ok(waves) => waves,
+		Err(error) => {
+			error!("Failed to parse waves.ron, there won't be any waves: {}", error);
+			Vec::new()
+		}
+	}
+}
+
+fn reset_waves(mut spawner: ResMut<WaveSpawner>) {
+	spawner.reset();
+}
+
+fn spawn_waves(
+	mut commands: Commands,
+	mut spawner: ResMut<WaveSpawner>,
+	spawn_points: Query<&Transform, With<EnemySpawnPoint>>,
+	wave_enemies: Query<(), With<WaveEnemy>>,
+	player: Query<&Transform, With<Player>>,
+	tilemap: Query<Entity, With<Tilemap>>,
+	enemy_types: Res<EnemyTypes>,
+	enemy_textures: Res<EnemyTextures>,
+	nav_mesh: Res<EnemyNavMesh>,
+	time: Res<TimeCounter>,
+) {
+	// Levels without spawn tiles don't have waves
+	if spawner.is_finished() || spawn_points.is_empty() {
+		return;
+	}
+
+	if !spawner.queue.is_empty() {
+		spawner.spawn_timer.tick(time.delta());
+
+		if spawner.spawn_timer.just_finished() {
+			let enemy_type = spawner.queue.pop().unwrap();
+
+			// Take turns between the spawn tiles
+			let spawn_points: Vec<&Transform> = spawn_points.iter().collect();
+			let spawn_point = spawn_points[spawner.queue.len() % spawn_points.len()];
+
+			let enemy = commands
+				.spawn_bundle(
+					EnemyBundle::spawn(
+						spawn_point.translation.truncate(),
+						enemy_textures.idle.clone(),
+						false,
+						false,
+					)
+					.with_stats(enemy_types.get(enemy_type))
+					// Waves come looking for the player
+					.alerted_towards(player.single().translation.truncate(), &nav_mesh),
+				)
+				.insert(WaveEnemy)
+				.id();
+
+			commands.entity(tilemap.single()).add_child(enemy);
+		}
+
+		return;
+	}
+
+	if !wave_enemies.is_empty() {
+		return;
+	}
+
+	// The current wave is cleared
+	if spawner.current_wave == spawner.wave_count() {
+		spawner.finished = true;
+		return;
+	}
+
+	let delay = spawner.waves[spawner.current_wave].delay;
+	spawner.wave_delay.set_duration(Duration::from_secs_f32(delay));
+	spawner.wave_delay.tick(time.delta());
+
+	if spawner.wave_delay.finished() {
+		let wave = spawner.waves[spawner.current_wave].clone();
+
+		spawner.queue = wave
+			.enemies
+			.iter()
+			.flat_map(|(enemy_type, count)| std::iter::repeat(*enemy_type).take(*count as usize))
+			.collect();
+
+		spawner.current_wave += 1;
+		spawner.wave_delay.reset();
+		spawner.spawn_timer.reset();
+	}
+}
@@ -10,6 +10,29 @@ use crate::{
 #[derive(Component, Default)]
 pub struct Win;
 
+/// What the player has to do to beat the level, set by the "goal" property of the level file
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WinGoal {
+	/// Walk onto one of the win tiles
+	ReachExit,
+	/// Kill every enemy on the map
+	KillAll,
+	/// Survive every wave of the wave spawner
+	ClearWaves,
+}
+
+impl WinGoal {
+	/// Parses the "goal" property of a level.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name.to_lowercase().as_str() {
+			"reach_exit" => Some(Self::ReachExit),
+			"kill_all" => Some(Self::KillAll),
+			"clear_waves" => Some(Self::ClearWaves),
+			_ => None,
+		}
+	}
+}
+
 #[derive(Component)]
 struct WinUi;
 
@@ -26,7 +49,8 @@ pub struct WinPlugin;
 
 impl Plugin for WinPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_system_set(SystemSet::on_enter(GameState::Win).with_system(load_ui))
+		app.insert_resource(WinGoal::ReachExit)
+			.add_system_set(SystemSet::on_enter(GameState::Win).with_system(load_ui))
 		.add_plugin(Material2dPlugin::<WinMaterial>::default())
 			.add_system_set(
 				SystemSet::on_update(GameState::Win)